@@ -0,0 +1,107 @@
+//! `#[derive(HasLotusJson)]` for simple compound structs, see `forest-explorer`'s `lotus_json`
+//! module.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+/// Implements `HasLotusJson` for a struct with named fields. The lotus JSON companion struct,
+/// `<Name>LotusJson`, has the same fields in `PascalCase`, each (de)serialized as lotus JSON.
+///
+/// Fields accept `#[lotus_json(...)]` attributes:
+/// - `rename = "CID"` overrides the JSON name of the field.
+/// - `skip_if_none` leaves out `None` when serializing, and accepts a missing field.
+#[proc_macro_derive(HasLotusJson, attributes(lotus_json))]
+pub fn derive_has_lotus_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "HasLotusJson can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "HasLotusJson can only be derived for structs",
+            ))
+        }
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "HasLotusJson can't be derived for generic structs",
+        ));
+    }
+
+    let name = &input.ident;
+    let vis = &input.vis;
+    let lotus_json = format_ident!("{}LotusJson", name);
+    let idents = fields
+        .iter()
+        .map(|field| field.ident.as_ref().expect("fields are named"))
+        .collect::<Vec<_>>();
+    let lotus_json_fields = fields
+        .iter()
+        .map(|field| {
+            let ident = &field.ident;
+            let ty = &field.ty;
+            let mut serde_args = vec![quote!(with = "crate::lotus_json")];
+            for attr in field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("lotus_json"))
+            {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        let rename: LitStr = meta.value()?.parse()?;
+                        serde_args.push(quote!(rename = #rename));
+                        Ok(())
+                    } else if meta.path.is_ident("skip_if_none") {
+                        serde_args.push(quote!(skip_serializing_if = "Option::is_none", default));
+                        Ok(())
+                    } else {
+                        Err(meta.error("expected `rename = \"...\"` or `skip_if_none`"))
+                    }
+                })?;
+            }
+            Ok(quote! {
+                #[serde(#(#serde_args),*)]
+                #ident: #ty
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        #[derive(Clone, ::serde::Serialize, ::serde::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        #vis struct #lotus_json {
+            #(#lotus_json_fields,)*
+        }
+
+        impl crate::lotus_json::HasLotusJson for #name {
+            type LotusJson = #lotus_json;
+
+            fn into_lotus_json(self) -> Self::LotusJson {
+                let Self { #(#idents),* } = self;
+                Self::LotusJson { #(#idents),* }
+            }
+
+            fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+                let Self::LotusJson { #(#idents),* } = lotus_json;
+                Self { #(#idents),* }
+            }
+        }
+    })
+}
@@ -0,0 +1,67 @@
+use cid::Cid;
+use std::collections::HashMap;
+
+/// The kind of built-in actor behind an address, as far as users sending funds are concerned.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ActorKind {
+    Account,
+    EthAccount,
+    /// An address which received funds but has no actor behind it yet.
+    Placeholder,
+    Evm,
+    Other(String),
+}
+
+impl ActorKind {
+    /// Classifies `code` using the built-in actor code CIDs of the current network version, as
+    /// returned by `Filecoin.StateActorCodeCIDs`.
+    pub fn from_code(code: &Cid, code_cids: &HashMap<String, Cid>) -> Self {
+        let name = code_cids
+            .iter()
+            .find_map(|(name, cid)| (cid == code).then_some(name.as_str()));
+        match name {
+            Some("account") => ActorKind::Account,
+            Some("ethaccount") => ActorKind::EthAccount,
+            Some("placeholder") => ActorKind::Placeholder,
+            Some("evm") => ActorKind::Evm,
+            Some(name) => ActorKind::Other(name.to_string()),
+            None => ActorKind::Other(code.to_string()),
+        }
+    }
+
+    /// Whether funds sent to the actor may not be spendable the way users expect, e.g. because
+    /// it's a smart contract.
+    pub fn is_contract(&self) -> bool {
+        matches!(self, ActorKind::Evm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use multihash_codetable::{Code, MultihashDigest};
+
+    #[test]
+    fn test_from_code() {
+        let cid = |name: &str| Cid::new_v1(0x55, Code::Blake2b256.digest(name.as_bytes()));
+        let code_cids: HashMap<_, _> = ["account", "ethaccount", "evm", "multisig"]
+            .into_iter()
+            .map(|name| (name.to_string(), cid(name)))
+            .collect();
+
+        assert_eq!(
+            ActorKind::from_code(&cid("account"), &code_cids),
+            ActorKind::Account
+        );
+        assert_eq!(
+            ActorKind::from_code(&cid("evm"), &code_cids),
+            ActorKind::Evm
+        );
+        assert!(ActorKind::from_code(&cid("evm"), &code_cids).is_contract());
+        assert_eq!(
+            ActorKind::from_code(&cid("multisig"), &code_cids),
+            ActorKind::Other("multisig".to_string())
+        );
+        assert!(!ActorKind::from_code(&cid("unknown"), &code_cids).is_contract());
+    }
+}
@@ -1,40 +1,268 @@
-use anyhow::ensure;
-use fvm_shared::address::{Address, Network};
+use anyhow::{bail, ensure};
+use fvm_shared::address::{Address, Network, Payload, Protocol};
 use fvm_shared::ActorID;
+use sha3::{Digest, Keccak256};
+use std::fmt;
+
+use crate::rpc_context::RpcProvider;
 
 // '0x' + 20bytes
 const ETH_ADDRESS_LENGTH: usize = 42;
-const EAM_NAMESPACE: ActorID = 10;
+pub(crate) const EAM_NAMESPACE: ActorID = 10;
+
+/// Whether `addr` refers to `actor`, e.g. an ID address and the key address it was created from.
+/// `actor_id` is the ID of `actor` from [`lookup_id`], so it's only resolved once when comparing
+/// many addresses to it. Addresses that can't be resolved, e.g. because the node is unreachable,
+/// only refer to `actor` if they're identical.
+pub async fn refers_to_actor(
+    addr: &Address,
+    actor: &Address,
+    actor_id: Option<&Address>,
+    provider: &impl RpcProvider,
+) -> bool {
+    if addr == actor {
+        return true;
+    }
+    same_actor(lookup_id(addr, provider).await.as_ref(), actor_id)
+}
+
+/// The ID address `addr` resolves to, or `None` if it can't be resolved.
+pub async fn lookup_id(addr: &Address, provider: &impl RpcProvider) -> Option<Address> {
+    if addr.protocol() == Protocol::ID {
+        return Some(*addr);
+    }
+    provider
+        .state_lookup_id(*addr)
+        .await
+        .inspect_err(|e| log::warn!("Comparing {addr} without resolving it: {e}"))
+        .ok()
+        .flatten()
+}
+
+fn same_actor(a_id: Option<&Address>, b_id: Option<&Address>) -> bool {
+    a_id.is_some_and(|a_id| Some(a_id) == b_id)
+}
+
+fn network_prefix(n: Network) -> &'static str {
+    match n {
+        Network::Mainnet => "f",
+        Network::Testnet => "t",
+    }
+}
 
 fn is_valid_prefix(s: &str, n: Network) -> bool {
     if s.len() < 2 {
         return false;
     }
 
+    s.starts_with(network_prefix(n)) || s.starts_with("0x")
+}
+
+/// Formats an address for the given network, independently of the global network used by
+/// [`Address`]'s `Display` implementation.
+pub fn format_address(addr: &Address, n: Network) -> String {
+    // The network prefix is the only network-dependent part of the string form; the checksum
+    // covers the protocol and payload only.
+    let s = addr.to_string();
+    format!("{}{}", network_prefix(n), &s[1..])
+}
+
+/// The kind of account an address refers to, as shown to users.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressProtocol {
+    Id,
+    Secp256k1,
+    Actor,
+    Bls,
+    /// Any `f4` address, including Ethereum-compatible `f410` and `0x` ones.
+    Delegated,
+}
+
+impl fmt::Display for AddressProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AddressProtocol::Id => "ID",
+            AddressProtocol::Secp256k1 => "Secp256k1",
+            AddressProtocol::Actor => "Actor",
+            AddressProtocol::Bls => "BLS",
+            AddressProtocol::Delegated => "Delegated",
+        })
+    }
+}
+
+pub fn address_protocol(addr: &Address) -> AddressProtocol {
+    match addr.payload() {
+        Payload::ID(_) => AddressProtocol::Id,
+        Payload::Secp256k1(_) => AddressProtocol::Secp256k1,
+        Payload::Actor(_) => AddressProtocol::Actor,
+        Payload::BLS(_) => AddressProtocol::Bls,
+        Payload::Delegated(_) => AddressProtocol::Delegated,
+    }
+}
+
+/// The protocol of `addr` as shown to users, marking delegated addresses in the Ethereum address
+/// manager namespace.
+pub fn protocol_label(addr: &Address) -> String {
+    match f4_to_eth(addr) {
+        Some(_) => "Delegated (eth)".to_string(),
+        None => address_protocol(addr).to_string(),
+    }
+}
+
+/// One-line explanation of what kind of account an address belongs to, for users unfamiliar with
+/// Filecoin address formats.
+pub fn describe_address(addr: &Address) -> &'static str {
+    match address_protocol(addr) {
+        AddressProtocol::Id => {
+            "An ID address (f0/t0), the short on-chain alias of an existing actor."
+        }
+        AddressProtocol::Secp256k1 => {
+            "A Secp256k1 wallet address (f1/t1), used by most Filecoin wallets."
+        }
+        AddressProtocol::Actor => "An actor address (f2/t2), e.g. a multisig or payment channel.",
+        AddressProtocol::Bls => {
+            "A BLS wallet address (f3/t3), typically used by storage providers."
+        }
+        AddressProtocol::Delegated if f4_to_eth(addr).is_some() => {
+            "An Ethereum-compatible address (0x or f410/t410), e.g. from MetaMask."
+        }
+        AddressProtocol::Delegated => {
+            "A delegated address (f4/t4) managed by a user-defined actor."
+        }
+    }
+}
+
+pub fn network_name(n: Network) -> &'static str {
     match n {
-        Network::Mainnet => s.starts_with("f") || s.starts_with("0x"),
-        Network::Testnet => s.starts_with("t") || s.starts_with("0x"),
+        Network::Mainnet => "mainnet",
+        Network::Testnet => "calibnet",
+    }
+}
+
+/// The inverse of [`network_name`].
+pub fn network_from_name(name: &str) -> Option<Network> {
+    [Network::Mainnet, Network::Testnet]
+        .into_iter()
+        .find(|n| network_name(*n) == name)
+}
+
+/// The EIP-55 mixed-case form of a lowercase hex Ethereum address, without the `0x` prefix.
+fn eip55_checksum(lower: &str) -> String {
+    let hash = Keccak256::digest(lower.as_bytes());
+    lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = if i % 2 == 0 {
+                hash[i / 2] >> 4
+            } else {
+                hash[i / 2] & 0xf
+            };
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Parses a `0x` address in its original casing. Mixed-case addresses must carry a valid EIP-55
+/// checksum; all-lowercase and all-uppercase ones aren't checksummed.
+fn parse_eth_address(s: &str) -> anyhow::Result<Address> {
+    ensure!(s.len() == ETH_ADDRESS_LENGTH, "Invalid address length");
+    ensure!(
+        s.chars().skip(2).all(|c| c.is_ascii_hexdigit()),
+        "Invalid characters in address"
+    );
+
+    let hex = &s[2..];
+    let lower = hex.to_ascii_lowercase();
+    if hex != lower && hex != hex.to_ascii_uppercase() {
+        ensure!(hex == eip55_checksum(&lower), "Invalid address checksum");
     }
+    let addr = hex::decode(&lower)?;
+    Ok(Address::new_delegated(EAM_NAMESPACE, &addr)?)
 }
 
 pub fn parse_address(raw: &str, n: Network) -> anyhow::Result<Address> {
-    let s = raw.trim().to_lowercase();
+    parse_address_typed(raw, n).map(|(addr, _)| addr)
+}
 
-    ensure!(is_valid_prefix(&s, n), "Not a valid {:?} address", n);
+/// Parses an address, along with the kind of address that was given. `0x` addresses are
+/// [`AddressProtocol::Delegated`].
+pub fn parse_address_typed(raw: &str, n: Network) -> anyhow::Result<(Address, AddressProtocol)> {
+    let addr = parse_address_untyped(raw, n)?;
+    Ok((addr, address_protocol(&addr)))
+}
 
-    if s.len() > 2 && s.starts_with("0x") {
-        // Expecting an eth address, perform further validation
-        ensure!(s.len() == ETH_ADDRESS_LENGTH, "Invalid address length");
-        ensure!(
-            s.chars().skip(2).all(|c| c.is_ascii_hexdigit()),
-            "Invalid characters in address"
-        );
+fn parse_address_untyped(raw: &str, n: Network) -> anyhow::Result<Address> {
+    let s = raw.trim();
+    let lower = s.to_lowercase();
 
-        let addr = hex::decode(&s[2..])?;
-        Ok(Address::new_delegated(EAM_NAMESPACE, &addr)?)
+    ensure!(is_valid_prefix(&lower, n), "Not a valid {:?} address", n);
+
+    if lower.len() > 2 && lower.starts_with("0x") {
+        // Expecting an eth address, perform further validation. The casing is kept for the
+        // checksum.
+        parse_eth_address(s)
     } else {
-        Ok(n.parse_address(&s)?)
+        Ok(n.parse_address(&lower)?)
+    }
+}
+
+/// The Filecoin form of `addr` for the network, e.g. the `f410`/`t410` address funds sent to a
+/// `0x` address actually go to.
+pub fn to_filecoin_display(addr: &Address, n: Network) -> String {
+    format_address(addr, n)
+}
+
+/// The `0x` form of a delegated address in the Ethereum address manager namespace, or `None` for
+/// any other address.
+pub fn f4_to_eth(addr: &Address) -> Option<String> {
+    match addr.payload() {
+        Payload::Delegated(d) if d.namespace() == EAM_NAMESPACE => {
+            Some(format!("0x{}", hex::encode(d.subaddress())))
+        }
+        _ => None,
+    }
+}
+
+/// Parses a list of addresses separated by commas or whitespace, e.g. for batch drips.
+pub fn parse_addresses(raw: &str, n: Network) -> anyhow::Result<Vec<Address>> {
+    let addrs = raw
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(|s| parse_address(s, n))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    ensure!(!addrs.is_empty(), "No address given");
+    Ok(addrs)
+}
+
+/// The other network, if `raw` isn't valid on `n` but is on the other network, e.g. a mainnet
+/// address pasted into the calibnet faucet.
+pub fn wrong_network(raw: &str, n: Network) -> Option<Network> {
+    let other = match n {
+        Network::Mainnet => Network::Testnet,
+        Network::Testnet => Network::Mainnet,
+    };
+    (parse_addresses(raw, n).is_err() && parse_addresses(raw, other).is_ok()).then_some(other)
+}
+
+/// Parses an address for any network. The network is taken from the prefix, and is `None` for
+/// Ethereum addresses, which are the same on every network.
+pub fn parse_address_any(raw: &str) -> anyhow::Result<(Address, Option<Network>)> {
+    let raw = raw.trim();
+    let s = raw.to_lowercase();
+    if s.starts_with("0x") {
+        return Ok((parse_eth_address(raw)?, None));
     }
+    let n = match s.chars().next() {
+        Some('f') => Network::Mainnet,
+        Some('t') => Network::Testnet,
+        _ => bail!("Unknown address prefix"),
+    };
+    Ok((n.parse_address(&s)?, Some(n)))
 }
 
 #[cfg(test)]
@@ -47,6 +275,141 @@ mod tests {
     use fvm_shared::address::set_current_network;
     use rusty_fork::rusty_fork_test;
 
+    #[test]
+    fn test_same_actor() {
+        let sender = parse_address(
+            "t410f2oekwcmo2pueydmaq53eic2i62crtbeyuzx2gmy",
+            Network::Testnet,
+        )
+        .unwrap();
+        let eth_form = parse_address(
+            "0xd388ab098ed3e84c0d808776440b48f685198498",
+            Network::Testnet,
+        )
+        .unwrap();
+        let sender_id = Address::new_id(1234);
+
+        assert_eq!(eth_form, sender);
+        assert!(same_actor(Some(&sender_id), Some(&sender_id)));
+        assert!(!same_actor(Some(&Address::new_id(1235)), Some(&sender_id)));
+        // Unresolved addresses don't match.
+        assert!(!same_actor(Some(&sender_id), None));
+        assert!(!same_actor(None, None));
+    }
+
+    #[test]
+    fn test_parse_id_address() {
+        let (addr, protocol) = parse_address_typed("t0100", Network::Testnet).unwrap();
+        assert_eq!(protocol, AddressProtocol::Id);
+        assert_eq!(addr, Address::new_id(100));
+        assert_eq!(format_address(&addr, Network::Testnet), "t0100");
+        assert_eq!(parse_address(" T0100 ", Network::Testnet).unwrap(), addr);
+        assert!(parse_address("t0", Network::Testnet).is_err());
+        assert!(parse_address("t0100", Network::Mainnet).is_err());
+    }
+
+    #[test]
+    fn test_parse_address_typed() {
+        for (raw, protocol) in [
+            ("f01234", AddressProtocol::Id),
+            (
+                "f1alg2sxw32ns3ech2w7r3dmp2gl2fputkl7x7jta",
+                AddressProtocol::Secp256k1,
+            ),
+            (
+                "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+                AddressProtocol::Delegated,
+            ),
+            (
+                "f410f2oekwcmo2pueydmaq53eic2i62crtbeyuzx2gmy",
+                AddressProtocol::Delegated,
+            ),
+        ] {
+            let (addr, parsed) = parse_address_typed(raw, Network::Mainnet).unwrap();
+            assert_eq!(parsed, protocol);
+            assert_eq!(addr, parse_address(raw, Network::Mainnet).unwrap());
+        }
+        assert!(parse_address_typed("t01234", Network::Mainnet).is_err());
+    }
+
+    #[test]
+    fn test_parse_address_checksum() {
+        let checksummed = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let expected = parse_address(&checksummed.to_lowercase(), Network::Mainnet).unwrap();
+        assert_eq!(
+            parse_address(checksummed, Network::Mainnet).unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_address(
+                "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+                Network::Testnet
+            )
+            .unwrap(),
+            parse_address(
+                "0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359",
+                Network::Testnet
+            )
+            .unwrap()
+        );
+        // Uniform casing carries no checksum.
+        assert_eq!(
+            parse_address(
+                &checksummed.to_uppercase().replacen("0X", "0x", 1),
+                Network::Mainnet
+            )
+            .unwrap(),
+            expected
+        );
+
+        let corrupted = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD";
+        assert_eq!(
+            parse_address(corrupted, Network::Mainnet)
+                .unwrap_err()
+                .to_string(),
+            "Invalid address checksum"
+        );
+        assert!(parse_address_any(corrupted).is_err());
+        assert_eq!(parse_address_any(checksummed).unwrap(), (expected, None));
+    }
+
+    #[test]
+    fn test_wrong_network() {
+        let mainnet = "f1alg2sxw32ns3ech2w7r3dmp2gl2fputkl7x7jta";
+        let calibnet = "t1alg2sxw32ns3ech2w7r3dmp2gl2fputkl7x7jta";
+        assert_eq!(
+            wrong_network(mainnet, Network::Testnet),
+            Some(Network::Mainnet)
+        );
+        assert_eq!(
+            wrong_network(calibnet, Network::Mainnet),
+            Some(Network::Testnet)
+        );
+        assert_eq!(wrong_network(calibnet, Network::Testnet), None);
+        // Ethereum addresses are valid everywhere.
+        assert_eq!(
+            wrong_network(
+                "0xd388ab098ed3e84c0d808776440b48f685198498",
+                Network::Mainnet
+            ),
+            None
+        );
+        // Only a suggestion if every address would be valid on the other network.
+        assert_eq!(
+            wrong_network(&format!("{mainnet}, f1bad"), Network::Testnet),
+            None
+        );
+        assert_eq!(wrong_network("garbage", Network::Testnet), None);
+    }
+
+    #[test]
+    fn test_network_from_name() {
+        for n in [Network::Mainnet, Network::Testnet] {
+            assert_eq!(network_from_name(network_name(n)), Some(n));
+        }
+        assert_eq!(network_from_name("devnet"), None);
+    }
+
     #[test]
     fn test_check_address_prefix() {
         // Valid cases
@@ -94,6 +457,57 @@ mod tests {
     }
     }
 
+    #[test]
+    fn test_format_address() {
+        let addr_str = "f410f2oekwcmo2pueydmaq53eic2i62crtbeyuzx2gmy";
+        let addr = parse_address(addr_str, Network::Mainnet).unwrap();
+
+        assert_eq!(format_address(&addr, Network::Mainnet), addr_str);
+        assert_eq!(
+            format_address(&addr, Network::Testnet),
+            "t410f2oekwcmo2pueydmaq53eic2i62crtbeyuzx2gmy"
+        );
+    }
+
+    #[test]
+    fn test_parse_address_any() {
+        let (addr, n) = parse_address_any("f1alg2sxw32ns3ech2w7r3dmp2gl2fputkl7x7jta").unwrap();
+        assert_eq!(n, Some(Network::Mainnet));
+        assert_eq!(address_protocol(&addr), AddressProtocol::Secp256k1);
+
+        let (addr, n) = parse_address_any("t410f2oekwcmo2pueydmaq53eic2i62crtbeyuzx2gmy").unwrap();
+        assert_eq!(n, Some(Network::Testnet));
+        assert_eq!(address_protocol(&addr), AddressProtocol::Delegated);
+        assert_eq!(protocol_label(&addr), "Delegated (eth)");
+
+        let (addr, n) = parse_address_any("0xd388ab098ed3e84c0d808776440b48f685198498").unwrap();
+        assert_eq!(n, None);
+        assert_eq!(address_protocol(&addr), AddressProtocol::Delegated);
+
+        let (addr, _) = parse_address_any("t01234").unwrap();
+        assert_eq!(address_protocol(&addr), AddressProtocol::Id);
+        assert_eq!(protocol_label(&addr), "ID");
+
+        assert!(describe_address(&addr).starts_with("An ID address"));
+
+        assert!(parse_address_any("").is_err());
+        assert!(parse_address_any("x1234").is_err());
+    }
+
+    #[test]
+    fn test_parse_addresses() {
+        let addrs = parse_addresses(
+            " t01234, 0xd388ab098ed3e84c0d808776440b48f685198498\nt01235 ",
+            Network::Testnet,
+        )
+        .unwrap();
+        assert_eq!(addrs.len(), 3);
+        assert_eq!(addrs[0], Address::new_id(1234));
+
+        assert!(parse_addresses(" , ", Network::Testnet).is_err());
+        assert!(parse_addresses("t01234, f01235", Network::Testnet).is_err());
+    }
+
     #[test]
     fn test_parse_wrong_network() {
         let m_addr_str = "f1alg2sxw32ns3ech2w7r3dmp2gl2fputkl7x7jta";
@@ -116,6 +530,51 @@ mod tests {
         assert_eq!(exp_addr, addr);
     }
 
+    #[test]
+    fn test_to_filecoin_display() {
+        let addr = parse_address(
+            "0xd388ab098ed3e84c0d808776440b48f685198498",
+            Network::Testnet,
+        )
+        .unwrap();
+        assert_eq!(
+            to_filecoin_display(&addr, Network::Testnet),
+            "t410f2oekwcmo2pueydmaq53eic2i62crtbeyuzx2gmy"
+        );
+        assert_eq!(
+            to_filecoin_display(&addr, Network::Mainnet),
+            "f410f2oekwcmo2pueydmaq53eic2i62crtbeyuzx2gmy"
+        );
+    }
+
+    #[test]
+    fn test_f4_to_eth() {
+        let eth = "0xd388ab098ed3e84c0d808776440b48f685198498";
+        for (raw, n) in [
+            (eth, Network::Testnet),
+            (
+                "t410f2oekwcmo2pueydmaq53eic2i62crtbeyuzx2gmy",
+                Network::Testnet,
+            ),
+            (
+                "f410f2oekwcmo2pueydmaq53eic2i62crtbeyuzx2gmy",
+                Network::Mainnet,
+            ),
+        ] {
+            let addr = parse_address(raw, n).unwrap();
+            assert_eq!(f4_to_eth(&addr).as_deref(), Some(eth));
+            assert_eq!(parse_address(eth, n).unwrap(), addr);
+        }
+        assert_eq!(f4_to_eth(&Address::new_id(1234)), None);
+        let mainnet = "f1alg2sxw32ns3ech2w7r3dmp2gl2fputkl7x7jta";
+        assert_eq!(
+            f4_to_eth(&parse_address(mainnet, Network::Mainnet).unwrap()),
+            None
+        );
+        let other_namespace = Address::new_delegated(32, &[1; 20]).unwrap();
+        assert_eq!(f4_to_eth(&other_namespace), None);
+    }
+
     #[test]
     fn test_parse_eth_address_mainnet() {
         let addr_str = "0xd388ab098ed3e84c0d808776440b48f685198498";
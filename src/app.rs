@@ -1,9 +1,12 @@
+use crate::config::ConfigContext;
 use crate::rpc_context::{Provider, RpcContext};
+use crate::storage::{use_stored, StorageKind};
 use fvm_shared::address::Network;
 use leptos::prelude::*;
 use leptos::{component, leptos_dom::helpers::event_target_value, view, IntoView};
 use leptos_meta::*;
 use leptos_router::components::*;
+use leptos_router::hooks::use_location;
 use leptos_router::path;
 
 #[allow(dead_code)]
@@ -33,6 +36,36 @@ pub fn Loader(loading: impl Fn() -> bool + 'static + Send) -> impl IntoView {
 #[component]
 pub fn BlockchainExplorer() -> impl IntoView {
     let rpc_context = RpcContext::use_context();
+    let (rpc_url, set_rpc_url) = use_stored(
+        StorageKind::Local,
+        "explorer-rpc-url",
+        Provider::get_network_url(Network::Testnet),
+    );
+    let custom_url = RwSignal::new(String::new());
+    // Switch to the last-used node once it's read from storage.
+    Effect::new(move |_| {
+        let url = rpc_url.get();
+        if untrack(move || rpc_context.get()).url() == url {
+            return;
+        }
+        if rpc_context.try_set(&url).is_err() {
+            log::warn!("Ignoring stored RPC URL {url}");
+        } else if ![Network::Testnet, Network::Mainnet]
+            .into_iter()
+            .any(|network| Provider::get_network_url(network) == url)
+        {
+            custom_url.set(url);
+        }
+    });
+    let url_error = RwSignal::new(None::<String>);
+    let connect = move |url: String| match rpc_context.try_set(&url) {
+        Ok(()) => {
+            url_error.set(None);
+            set_rpc_url.run(rpc_context.get().url().to_string());
+        }
+        Err(e) => url_error.set(Some(e.to_string())),
+    };
+
     let network_name = LocalResource::new(move || {
         let provider = rpc_context.get();
         async move { provider.network_name().await.ok() }
@@ -43,15 +76,60 @@ pub fn BlockchainExplorer() -> impl IntoView {
         async move { provider.network_version().await.ok() }
     });
 
+    let chain_head = LocalResource::new(move || {
+        let provider = rpc_context.get();
+        async move { provider.chain_head().await.ok() }
+    });
+    #[cfg(feature = "hydrate")]
+    let _ = leptos_use::use_interval_fn(move || chain_head.refetch(), 5000);
+
+    // The full list of miners is large, so it's only fetched on request.
+    let miner_count = RwSignal::new(None::<String>);
+    Effect::new(move |_| {
+        let _ = rpc_context.get();
+        miner_count.set(None);
+    });
+    let count_miners = move |_| {
+        let provider = rpc_context.get();
+        miner_count.set(Some("Counting...".to_string()));
+        leptos::task::spawn_local(async move {
+            miner_count.set(Some(match provider.state_list_miners().await {
+                Ok(miners) => miners.len().to_string(),
+                Err(e) => e.to_string(),
+            }));
+        });
+    };
+
     view! {
         <div class="flex flex-col items-center">
         <h1 class="mb-4 text-4xl font-extrabold leading-none tracking-tight text-gray-900 md:text-5xl lg:text-6xl">
             Forest Explorer
         </h1>
-        <select on:change=move |ev| { rpc_context.set(event_target_value(&ev)) }>
+        <select prop:value=rpc_url on:change=move |ev| connect(event_target_value(&ev))>
             <option value=Provider::get_network_url(Network::Testnet)>Glif.io Calibnet</option>
             <option value=Provider::get_network_url(Network::Mainnet)>Glif.io Mainnet</option>
         </select>
+        <form
+            class="flex mt-2"
+            on:submit=move |ev| {
+                ev.prevent_default();
+                connect(custom_url.get());
+            }
+        >
+            <input
+                type="text"
+                placeholder="http://localhost:2345/rpc/v1"
+                class="w-80 border border-gray-300 p-1 rounded-l"
+                prop:value=custom_url
+                on:input=move |ev| custom_url.set(event_target_value(&ev))
+            />
+            <button type="submit" class="bg-blue-500 hover:bg-blue-700 text-white font-bold py-1 px-2 rounded-r">
+                "Connect"
+            </button>
+        </form>
+        <Show when=move || url_error.get().is_some()>
+            <p class="text-red-600 text-sm">{move || url_error.get()}</p>
+        </Show>
         <p>StateNetworkName</p>
         <Transition fallback={move || view!{ <p>Loading network name...</p> }}>
             <p class="px-8">
@@ -63,10 +141,59 @@ pub fn BlockchainExplorer() -> impl IntoView {
         <p>StateNetworkVersion</p>
         <Transition fallback={move || view!{ <p>Loading network version...</p> }}>
             <p class="px-8">
-                <span>{move || network_version.get().as_deref().flatten().cloned()}</span>
+                <span>
+                    {move || {
+                        network_version
+                            .get()
+                            .as_deref()
+                            .flatten()
+                            .map(|version| format!("{version} ({})", version.name()))
+                    }}
+                </span>
                 <Loader loading={move || network_version.get().is_none()} />
             </p>
         </Transition>
+
+        <p>ChainHead</p>
+        <Transition fallback={move || view!{ <p>Loading chain head...</p> }}>
+            <p class="px-8">
+                <span>
+                    {move || {
+                        chain_head
+                            .get()
+                            .as_deref()
+                            .flatten()
+                            .map(|head| format!("Epoch {} ({} blocks)", head.height, head.blocks.len()))
+                    }}
+                </span>
+                <Loader loading={move || chain_head.get().is_none()} />
+            </p>
+        </Transition>
+        <p>StateListMiners</p>
+        <p class="px-8">
+            {move || match miner_count.get() {
+                Some(count) => view! { <span>{count}</span> }.into_any(),
+                None => view! {
+                    <button class="text-blue-600 hover:underline" on:click=count_miners>
+                        "Count storage providers"
+                    </button>
+                }
+                .into_any(),
+            }}
+        </p>
+        <crate::components::ActorDetails
+            provider=Signal::derive(move || rpc_context.get())
+            network=Signal::derive(move || rpc_context.network())
+        />
+        <crate::components::BlockDetails provider=Signal::derive(move || rpc_context.get()) />
+        <crate::components::DealDetails
+            provider=Signal::derive(move || rpc_context.get())
+            network=Signal::derive(move || rpc_context.network())
+        />
+        <crate::components::WalletConfig
+            provider=Signal::derive(move || rpc_context.get())
+            network=Signal::derive(move || rpc_context.network())
+        />
         <button class="bg-blue-500 hover:bg-blue-700 text-white font-bold py-1 px-2 rounded-full">
           <a href="/faucet">To faucet list</a>
         </button>
@@ -76,27 +203,95 @@ pub fn BlockchainExplorer() -> impl IntoView {
 
 #[component]
 fn Footer() -> impl IntoView {
+    let location = use_location();
     view! {
-        <footer class="p-4 text-center">
-            <a class="text-green-600" target="_blank" rel="noopener noreferrer" href="https://github.com/ChainSafe/forest-explorer">Forest Explorer</a>", built with ❤️ by " <a class="text-blue-600" target="_blank" rel="noopener noreferrer" href="https://chainsafe.io">ChainSafe Systems</a>
-        </footer>
+        <Show when=move || !is_embed_path(&location.pathname.get())>
+            <footer class="p-4 text-center">
+                <a class="text-green-600" target="_blank" rel="noopener noreferrer" href="https://github.com/ChainSafe/forest-explorer">Forest Explorer</a>", built with ❤️ by " <a class="text-blue-600" target="_blank" rel="noopener noreferrer" href="https://chainsafe.io">ChainSafe Systems</a>
+            </footer>
+        </Show>
     }
 }
 
+/// Whether this deployment serves the explorer home page. Building with `FAUCET_ONLY` set turns
+/// `/` into a redirect to the faucet list.
+pub fn explorer_enabled() -> bool {
+    option_env!("FAUCET_ONLY").is_none()
+}
+
+/// Embedded pages are framed by other sites, so they skip the site chrome.
+pub fn is_embed_path(path: &str) -> bool {
+    path.trim_end_matches('/').ends_with("/embed")
+}
+
+/// Focuses the page's primary input, marked with `data-shortcut-focus`, when `/` is pressed
+/// outside of a text field, like on GitHub.
+fn use_focus_shortcut() {
+    use leptos::web_sys::HtmlElement;
+    use leptos_use::{use_document, use_event_listener};
+    use wasm_bindgen::JsCast as _;
+
+    let _ = use_event_listener(use_document(), leptos::ev::keydown, |ev| {
+        if ev.key() != "/" || ev.ctrl_key() || ev.meta_key() || ev.alt_key() {
+            return;
+        }
+        let document = document();
+        let typing = document.active_element().is_some_and(|el| {
+            matches!(el.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+                || el
+                    .dyn_ref::<HtmlElement>()
+                    .is_some_and(HtmlElement::is_content_editable)
+        });
+        if typing {
+            return;
+        }
+        if let Some(input) = document
+            .query_selector("[data-shortcut-focus]")
+            .ok()
+            .flatten()
+            .and_then(|el| el.dyn_into::<HtmlElement>().ok())
+        {
+            ev.prevent_default();
+            let _ = input.focus();
+        }
+    });
+}
+
 #[component]
 pub fn App() -> impl IntoView {
     provide_meta_context();
     RpcContext::provide_context();
+    ConfigContext::provide_context();
+    use_focus_shortcut();
 
     view! {
         <Stylesheet href="/style.css" />
         <Link rel="icon" type_="image/x-icon" href="/favicon.ico" />
         <Router>
             <Routes fallback=|| "Not found.">
-                <Route path=path!("/") view=BlockchainExplorer />
+                <Route
+                    path=path!("/")
+                    view=|| {
+                        if explorer_enabled() {
+                            view! { <BlockchainExplorer /> }.into_any()
+                        } else {
+                            view! { <Redirect path="/faucet" /> }.into_any()
+                        }
+                    }
+                />
+                <Route path=path!("/message/:cid") view=crate::components::MessagePage />
+                <Route path=path!("/address/:address") view=crate::components::AddressPage />
                 <Route path=path!("/faucet") view=crate::faucet::views::Faucets />
                 <Route path=path!("/faucet/calibnet") view=crate::faucet::views::Faucet_Calibnet />
                 <Route path=path!("/faucet/mainnet") view=crate::faucet::views::Faucet_Mainnet />
+                <Route
+                    path=path!("/faucet/calibnet/embed")
+                    view=|| view! { <crate::faucet::views::Faucet_Embed target_network=Network::Testnet /> }
+                />
+                <Route
+                    path=path!("/faucet/mainnet/embed")
+                    view=|| view! { <crate::faucet::views::Faucet_Embed target_network=Network::Mainnet /> }
+                />
             </Routes>
             <Footer />
         </Router>
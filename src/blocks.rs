@@ -0,0 +1,52 @@
+use cid::Cid;
+use fvm_shared::{address::Address, bigint::BigInt, clock::ChainEpoch};
+
+use crate::lotus_json::HasLotusJson;
+
+/// A set of CIDs forming a unique key for a tipset.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TipSetKey(pub Vec<Cid>);
+
+/// A tipset as returned by the node. Only the fields the explorer needs are kept.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TipSet {
+    pub key: TipSetKey,
+    pub blocks: Vec<BlockHeader>,
+    pub height: ChainEpoch,
+}
+
+/// The header of a block in a tipset. Only the fields the explorer needs are kept.
+#[derive(Clone, Debug, PartialEq, Eq, HasLotusJson)]
+pub struct BlockHeader {
+    pub miner: Address,
+    /// `None` for the genesis block.
+    pub ticket: Option<Ticket>,
+    /// `None` for the genesis block.
+    pub election_proof: Option<ElectionProof>,
+    pub parents: TipSetKey,
+    pub parent_weight: BigInt,
+    pub height: ChainEpoch,
+    /// Root of the messages included in the block.
+    pub messages: Cid,
+    /// Unix time at which the block was mined.
+    pub timestamp: u64,
+}
+
+/// A verifiable random function proof: a BLS signature over the VRF input.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VRFProof(pub Vec<u8>);
+
+/// The randomness a block's miner drew for the epoch.
+#[derive(Clone, Debug, Default, PartialEq, Eq, HasLotusJson)]
+pub struct Ticket {
+    #[lotus_json(rename = "VRFProof")]
+    pub vrfproof: VRFProof,
+}
+
+/// Proof that a block's miner won the election for the epoch, and how many times.
+#[derive(Clone, Debug, Default, PartialEq, Eq, HasLotusJson)]
+pub struct ElectionProof {
+    pub win_count: i64,
+    #[lotus_json(rename = "VRFProof")]
+    pub vrfproof: VRFProof,
+}
@@ -0,0 +1,662 @@
+use chrono::{DateTime, Utc};
+use cid::Cid;
+use fvm_shared::address::Network;
+use leptos::prelude::*;
+use leptos::{component, view, IntoView};
+use leptos_router::hooks::{use_navigate, use_params_map, use_query_map};
+#[cfg(feature = "hydrate")]
+use leptos_use::use_interval_fn;
+use leptos_use::{use_clipboard, UseClipboardReturn};
+use serde_json::{json, Value};
+use url::Url;
+use wasm_bindgen::prelude::*;
+
+use crate::actor::ActorKind;
+use crate::address::{format_address, network_from_name, network_name, parse_address_any};
+use crate::constants::{FIL_CALIBNET_UNIT, FIL_MAINNET_UNIT};
+use crate::faucet::utils::{format_balance, format_url, SearchPath};
+use crate::market::MarketDeal;
+use crate::rpc_context::{eth_address, Provider, RpcContext, RpcError};
+use crate::utils::seconds_until;
+
+/// Button copying `text` to the clipboard, briefly confirming once it's done. It's disabled while
+/// `text` is empty.
+#[component]
+pub fn CopyButton(
+    #[prop(into)] text: Signal<String>,
+    #[prop(default = "Copy")] label: &'static str,
+) -> impl IntoView {
+    let UseClipboardReturn {
+        is_supported,
+        copied,
+        copy,
+        ..
+    } = use_clipboard();
+
+    view! {
+        <button
+            class="text-sm text-blue-600 hover:underline disabled:text-gray-400 disabled:no-underline ml-2"
+            disabled=move || !is_supported.get() || text.get().is_empty()
+            on:click=move |_| copy(&text.get())
+        >
+            {move || if copied.get() { "Copied!" } else { label }}
+        </button>
+    }
+}
+
+/// A message CID, linking to the external explorer at `base` when one is configured, followed by
+/// a link to the message's page in this explorer.
+#[component]
+pub fn TransactionLink(cid: Cid, base: Option<Url>, network: Network) -> impl IntoView {
+    let cid = cid.to_string();
+    let details_url = format!("/message/{cid}?network={}", network_name(network));
+    let cid = match base.and_then(|base| format_url(&base, SearchPath::Transaction, &cid).ok()) {
+        Some(tx_url) => view! {
+            <a href=tx_url.to_string() target="_blank" rel="noopener noreferrer" class="text-blue-600 hover:underline">
+                {cid}
+            </a>
+        }
+        .into_any(),
+        None => view! { <span>{cid}</span> }.into_any(),
+    };
+    view! {
+        {cid}
+        " "
+        <a href=details_url class="text-sm text-blue-600 hover:underline">"(details)"</a>
+    }
+}
+
+/// Counts down to `deadline`, rendering the remaining time as `mm:ss`. Once it's reached,
+/// `deadline` is cleared and `on_complete` fires.
+#[component]
+pub fn Countdown(
+    deadline: RwSignal<Option<DateTime<Utc>>>,
+    #[prop(optional, into)] on_complete: Option<Callback<()>>,
+) -> impl IntoView {
+    let now = RwSignal::new(Utc::now());
+    #[cfg(feature = "hydrate")]
+    let _ = use_interval_fn(
+        move || {
+            now.set(Utc::now());
+            let due = deadline
+                .get_untracked()
+                .is_some_and(|deadline| seconds_until(deadline, now.get_untracked()) == 0);
+            if due {
+                deadline.set(None);
+                if let Some(on_complete) = on_complete {
+                    on_complete.run(());
+                }
+            }
+        },
+        1000,
+    );
+    #[cfg(not(feature = "hydrate"))]
+    let _ = on_complete;
+
+    view! {
+        <span>
+            {move || {
+                format_countdown(deadline.get().map_or(0, |deadline| seconds_until(deadline, now.get())))
+            }}
+        </span>
+    }
+}
+
+fn format_countdown(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+// EIP-1193 provider injected by MetaMask and compatible wallets.
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ethereum, js_name = request)]
+    async fn ethereum_request(args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+/// Chain parameters for `wallet_addEthereumChain` (EIP-3085).
+fn wallet_config(chain_id: u64, rpc_url: &str, network: Network) -> Value {
+    let (name, symbol) = match network {
+        Network::Mainnet => ("Filecoin - Mainnet", FIL_MAINNET_UNIT),
+        Network::Testnet => ("Filecoin - Calibration testnet", FIL_CALIBNET_UNIT),
+    };
+    json!({
+        "chainId": format!("0x{chain_id:x}"),
+        "chainName": name,
+        "nativeCurrency": { "name": symbol, "symbol": symbol, "decimals": 18 },
+        "rpcUrls": [rpc_url],
+    })
+}
+
+async fn add_to_wallet(config: Value) -> anyhow::Result<()> {
+    let args = json!({ "method": "wallet_addEthereumChain", "params": [config] });
+    let args = js_sys::JSON::parse(&args.to_string())
+        .map_err(|_| anyhow::anyhow!("Invalid wallet configuration"))?;
+    ethereum_request(args).await.map_err(|e| {
+        anyhow::anyhow!(
+            "Wallet rejected the network: {}",
+            e.as_string()
+                .or_else(|| js_sys::JSON::stringify(&e).ok().map(String::from))
+                .unwrap_or_default()
+        )
+    })?;
+    Ok(())
+}
+
+/// Copyable wallet settings (RPC URL, chain ID and currency) for the provider's network, with a
+/// button adding them to an injected Ethereum wallet such as MetaMask.
+#[component]
+pub fn WalletConfig(
+    #[prop(into)] provider: Signal<Provider>,
+    #[prop(into)] network: Signal<Network>,
+) -> impl IntoView {
+    let chain_id = LocalResource::new(move || {
+        let provider = provider.get();
+        async move { provider.eth().eth_chain_id().await.ok() }
+    });
+    let config = Signal::derive(move || {
+        chain_id
+            .get()
+            .as_deref()
+            .flatten()
+            .copied()
+            .map(|id| wallet_config(id, provider.get().url(), network.get()))
+    });
+    let config_text = Signal::derive(move || {
+        config
+            .get()
+            .and_then(|config| serde_json::to_string_pretty(&config).ok())
+            .unwrap_or_default()
+    });
+    let status = RwSignal::new(None::<String>);
+
+    view! {
+        <div class="max-w-lg w-full">
+            <p>
+                "Wallet configuration"
+                <CopyButton text=config_text />
+            </p>
+            <Transition fallback=move || view! { <p>Loading chain ID...</p> }>
+                <pre class="text-sm bg-gray-100 p-2 rounded overflow-x-auto">
+                    {move || match chain_id.get().as_deref() {
+                        Some(None) => "This node doesn't serve the eth namespace.".to_string(),
+                        _ => config_text.get(),
+                    }}
+                </pre>
+            </Transition>
+            <button
+                class="bg-orange-500 hover:bg-orange-600 disabled:bg-gray-400 text-white font-bold py-1 px-2 rounded"
+                disabled=move || config.get().is_none()
+                on:click=move |_| {
+                    if let Some(config) = config.get() {
+                        leptos::task::spawn_local(async move {
+                            status.set(Some(match add_to_wallet(config).await {
+                                Ok(()) => "Network added to your wallet.".to_string(),
+                                Err(e) => e.to_string(),
+                            }));
+                        });
+                    }
+                }
+            >
+                "Add to MetaMask"
+            </button>
+            <Show when=move || status.get().is_some()>
+                <p class="text-sm">{move || status.get()}</p>
+            </Show>
+        </div>
+    }
+}
+
+/// Outcome of a lookup: rows of named values, or the reason it failed.
+type Lookup = Option<Result<Vec<(&'static str, String)>, String>>;
+
+#[component]
+fn LookupResult(#[prop(into)] result: Signal<Lookup>) -> impl IntoView {
+    move || match result.get() {
+        Some(Ok(rows)) => view! {
+            <dl class="text-sm break-all">
+                {rows
+                    .into_iter()
+                    .map(|(name, value)| view! {
+                        <dt class="font-semibold">{name}</dt>
+                        <dd class="mb-1">{value}</dd>
+                    })
+                    .collect_view()}
+            </dl>
+        }
+        .into_any(),
+        Some(Err(e)) => view! { <p class="text-red-600 text-sm">{e}</p> }.into_any(),
+        None => ().into_any(),
+    }
+}
+
+/// Looks up the code, state, nonce and balance of the actor behind any address.
+#[component]
+pub fn ActorDetails(
+    #[prop(into)] provider: Signal<Provider>,
+    #[prop(into)] network: Signal<Network>,
+) -> impl IntoView {
+    let input = RwSignal::new(String::new());
+    let result = RwSignal::<Lookup>::new(None);
+    let lookup = move || {
+        let provider = provider.get();
+        let unit = match network.get() {
+            Network::Mainnet => FIL_MAINNET_UNIT,
+            Network::Testnet => FIL_CALIBNET_UNIT,
+        };
+        let raw = input.get();
+        leptos::task::spawn_local(async move {
+            let details = async {
+                let (addr, _) = parse_address_any(&raw)?;
+                let actor = match provider.state_get_actor(addr).await {
+                    Ok(actor) => actor,
+                    Err(e)
+                        if e.downcast_ref::<RpcError>()
+                            .is_some_and(RpcError::is_actor_not_found) =>
+                    {
+                        anyhow::bail!("No actor at {addr} yet")
+                    }
+                    Err(e) => return Err(e),
+                };
+                let mut rows = vec![
+                    ("Code", actor.code.to_string()),
+                    ("Head", actor.state.to_string()),
+                    ("Nonce", actor.sequence.to_string()),
+                    ("Balance", format_balance(&actor.balance, unit)),
+                ];
+                if let Ok(eth) = eth_address(&addr) {
+                    rows.push(("Ethereum address", eth));
+                }
+                let code_cids = provider
+                    .state_actor_code_cids(provider.network_version().await?)
+                    .await?;
+                if matches!(
+                    ActorKind::from_code(&actor.code, &code_cids),
+                    ActorKind::Other(name) if name == "storageminer"
+                ) {
+                    let info = provider.state_miner_info(addr).await?;
+                    rows.extend([
+                        ("Owner", info.owner.to_string()),
+                        ("Worker", info.worker.to_string()),
+                        ("Sector size", format!("{} B", info.sector_size)),
+                    ]);
+                    if let Some(peer_id) = info.peer_id {
+                        rows.push(("Peer ID", peer_id.to_string()));
+                    }
+                    rows.extend(
+                        info.multiaddrs
+                            .iter()
+                            .map(|addr| ("Multiaddress", addr.to_string())),
+                    );
+                }
+                anyhow::Ok(rows)
+            };
+            result.set(Some(details.await.map_err(|e| e.to_string())));
+        });
+    };
+
+    view! {
+        <div class="max-w-lg w-full my-2">
+            <p>"Actor lookup"</p>
+            <form
+                class="flex"
+                on:submit=move |ev| {
+                    ev.prevent_default();
+                    lookup();
+                }
+            >
+                <input
+                    type="text"
+                    placeholder="f0..., f1..., 0x..."
+                    data-shortcut-focus
+                    class="flex-grow border border-gray-300 p-1 rounded-l"
+                    prop:value=input
+                    on:input=move |ev| input.set(event_target_value(&ev))
+                />
+                <button type="submit" class="bg-blue-500 hover:bg-blue-700 text-white font-bold py-1 px-2 rounded-r">
+                    "Look up"
+                </button>
+            </form>
+            <LookupResult result />
+            <Show when=move || matches!(result.get(), Some(Ok(_)))>
+                <a href=move || format!("/address/{}", input.get().trim()) class="text-sm text-blue-600 hover:underline">
+                    "Open the address page"
+                </a>
+            </Show>
+        </div>
+    }
+}
+
+#[component]
+pub fn DealDetails(
+    #[prop(into)] provider: Signal<Provider>,
+    #[prop(into)] network: Signal<Network>,
+) -> impl IntoView {
+    let input = RwSignal::new(String::new());
+    let result = RwSignal::<Lookup>::new(None);
+    let lookup = move || {
+        let provider = provider.get();
+        let unit = match network.get() {
+            Network::Mainnet => FIL_MAINNET_UNIT,
+            Network::Testnet => FIL_CALIBNET_UNIT,
+        };
+        let raw = input.get();
+        leptos::task::spawn_local(async move {
+            let details = async {
+                let deal_id = raw
+                    .trim()
+                    .parse::<u64>()
+                    .map_err(|_| anyhow::anyhow!("Deal IDs are non-negative integers"))?;
+                let MarketDeal { proposal, state } =
+                    provider.state_market_storage_deal(deal_id).await?;
+                anyhow::Ok(vec![
+                    ("Piece CID", proposal.piece_cid.to_string()),
+                    ("Piece size", format!("{} B", proposal.piece_size.0)),
+                    ("Verified", proposal.verified_deal.to_string()),
+                    ("Client", proposal.client.to_string()),
+                    ("Provider", proposal.provider.to_string()),
+                    ("Label", proposal.label),
+                    (
+                        "Epochs",
+                        format!("{} to {}", proposal.start_epoch, proposal.end_epoch),
+                    ),
+                    (
+                        "Price per epoch",
+                        format_balance(&proposal.storage_price_per_epoch, unit),
+                    ),
+                    ("Sector start epoch", state.sector_start_epoch.to_string()),
+                    ("Slash epoch", state.slash_epoch.to_string()),
+                ])
+            };
+            result.set(Some(details.await.map_err(|e| e.to_string())));
+        });
+    };
+
+    view! {
+        <div class="max-w-lg w-full my-2">
+            <p>"Deal lookup"</p>
+            <form
+                class="flex"
+                on:submit=move |ev| {
+                    ev.prevent_default();
+                    lookup();
+                }
+            >
+                <input
+                    type="text"
+                    inputmode="numeric"
+                    placeholder="Deal ID"
+                    class="flex-grow border border-gray-300 p-1 rounded-l"
+                    prop:value=input
+                    on:input=move |ev| input.set(event_target_value(&ev))
+                />
+                <button type="submit" class="bg-blue-500 hover:bg-blue-700 text-white font-bold py-1 px-2 rounded-r">
+                    "Look up"
+                </button>
+            </form>
+            <LookupResult result />
+        </div>
+    }
+}
+
+#[component]
+pub fn BlockDetails(#[prop(into)] provider: Signal<Provider>) -> impl IntoView {
+    let input = RwSignal::new(String::new());
+    let result = RwSignal::<Lookup>::new(None);
+    let lookup = move || {
+        let provider = provider.get();
+        let raw = input.get();
+        leptos::task::spawn_local(async move {
+            let details = async {
+                let cid = Cid::try_from(raw.trim())
+                    .map_err(|e| anyhow::anyhow!("Invalid block CID: {e}"))?;
+                let (header, messages, receipts) = futures::try_join!(
+                    provider.chain_get_block(cid),
+                    provider.chain_get_parent_messages(cid),
+                    provider.chain_get_parent_receipts(cid)
+                )?;
+                let mut rows = vec![
+                    ("Miner", header.miner.to_string()),
+                    ("Height", header.height.to_string()),
+                    (
+                        "Timestamp",
+                        chrono::DateTime::from_timestamp(header.timestamp as i64, 0)
+                            .map_or_else(|| header.timestamp.to_string(), |t| t.to_rfc2822()),
+                    ),
+                    ("Parent weight", header.parent_weight.to_string()),
+                ];
+                if let Some(election_proof) = header.election_proof {
+                    rows.push(("Win count", election_proof.win_count.to_string()));
+                }
+                rows.push(("Parent messages", messages.len().to_string()));
+                // Only the failures, a busy tipset executes hundreds of messages.
+                rows.extend(
+                    messages
+                        .into_iter()
+                        .zip(receipts)
+                        .filter(|(_, receipt)| !receipt.exit_code.is_success())
+                        .map(|((cid, _), receipt)| {
+                            (
+                                "Failed message",
+                                format!("{cid} (exit code {})", receipt.exit_code),
+                            )
+                        }),
+                );
+                anyhow::Ok(rows)
+            };
+            result.set(Some(details.await.map_err(|e| e.to_string())));
+        });
+    };
+
+    view! {
+        <div class="max-w-lg w-full my-2">
+            <p>"Block lookup"</p>
+            <form
+                class="flex"
+                on:submit=move |ev| {
+                    ev.prevent_default();
+                    lookup();
+                }
+            >
+                <input
+                    type="text"
+                    placeholder="Block CID"
+                    class="flex-grow border border-gray-300 p-1 rounded-l"
+                    prop:value=input
+                    on:input=move |ev| input.set(event_target_value(&ev))
+                />
+                <button type="submit" class="bg-blue-500 hover:bg-blue-700 text-white font-bold py-1 px-2 rounded-r">
+                    "Look up"
+                </button>
+            </form>
+            <LookupResult result />
+        </div>
+    }
+}
+
+/// The message `:cid`, looked up on the network named by the `network` query parameter, or on the
+/// explorer's node without one.
+#[component]
+pub fn MessagePage() -> impl IntoView {
+    let params = use_params_map();
+    let query = use_query_map();
+    let rpc_context = RpcContext::use_context();
+    let details = LocalResource::new(move || {
+        let cid = params.read().get("cid").unwrap_or_default();
+        let (provider, network) = match query
+            .read()
+            .get("network")
+            .as_deref()
+            .and_then(network_from_name)
+        {
+            Some(network) => (Provider::from_network(network), network),
+            None => (rpc_context.get(), rpc_context.network()),
+        };
+        async move {
+            let details = async {
+                let cid = Cid::try_from(cid.as_str())
+                    .map_err(|e| anyhow::anyhow!("Invalid message CID: {e}"))?;
+                let unit = match network {
+                    Network::Mainnet => FIL_MAINNET_UNIT,
+                    Network::Testnet => FIL_CALIBNET_UNIT,
+                };
+                let (message, lookup) = futures::join!(
+                    provider.chain_get_message(cid),
+                    provider.state_search_msg(cid)
+                );
+                let lookup = lookup?;
+                let mut rows = vec![("CID", cid.to_string())];
+                match message {
+                    Ok(message) => rows.extend([
+                        ("From", format_address(&message.from, network)),
+                        ("To", format_address(&message.to, network)),
+                        ("Value", format_balance(&message.value, unit)),
+                        ("Nonce", message.sequence.to_string()),
+                        ("Method", message.method_num.to_string()),
+                    ]),
+                    // Pending messages are only in the mempool, which `ChainGetMessage` doesn't see.
+                    Err(e) if lookup.is_none() && e.downcast_ref::<RpcError>().is_some() => {}
+                    Err(e) => return Err(e),
+                }
+                match lookup {
+                    Some(lookup) => rows.extend([
+                        ("Height", lookup.height.to_string()),
+                        ("Exit code", lookup.receipt.exit_code.to_string()),
+                        ("Gas used", lookup.receipt.gas_used.to_string()),
+                    ]),
+                    None => rows.push(("Status", "Not on chain yet".to_string())),
+                }
+                anyhow::Ok(rows)
+            };
+            details.await.map_err(|e| e.to_string())
+        }
+    });
+
+    view! {
+        <div class="flex flex-col items-center">
+            <h1 class="mb-4 text-4xl font-extrabold leading-none tracking-tight text-gray-900 md:text-5xl lg:text-6xl">
+                "Message"
+            </h1>
+            <div class="max-w-lg w-full my-2">
+                <LookupResult result=Signal::derive(move || details.get().as_deref().cloned()) />
+            </div>
+            <a href="/" class="text-blue-600 hover:underline">"Back to the explorer"</a>
+        </div>
+    }
+}
+
+/// Both the ID and key forms of the address `:address`, with its balance. Addresses carry their
+/// network, except for Ethereum ones, which use the `network` query parameter or the explorer's
+/// node.
+#[component]
+pub fn AddressPage() -> impl IntoView {
+    let params = use_params_map();
+    let query = use_query_map();
+    let rpc_context = RpcContext::use_context();
+    let input = RwSignal::new(String::new());
+    let details = LocalResource::new(move || {
+        let raw = params.read().get("address").unwrap_or_default();
+        let query_network = query
+            .read()
+            .get("network")
+            .as_deref()
+            .and_then(network_from_name);
+        let context = (rpc_context.get(), rpc_context.network());
+        async move {
+            let details = async {
+                let (addr, network) = parse_address_any(&raw)?;
+                let (provider, network) = match network.or(query_network) {
+                    Some(network) => (Provider::from_network(network), network),
+                    None => context,
+                };
+                let unit = match network {
+                    Network::Mainnet => FIL_MAINNET_UNIT,
+                    Network::Testnet => FIL_CALIBNET_UNIT,
+                };
+                let (resolved, balance) = futures::try_join!(
+                    provider.resolve_address(addr),
+                    provider.wallet_balance(addr)
+                )?;
+                let key = match (resolved.id, resolved.key) {
+                    (_, Some(key)) => format_address(&key, network),
+                    (Some(_), None) => {
+                        "None, the actor isn't an account (e.g. a multisig or miner)".to_string()
+                    }
+                    (None, None) => "Unknown".to_string(),
+                };
+                anyhow::Ok(vec![
+                    (
+                        "ID address",
+                        resolved.id.map_or_else(
+                            || "No actor yet".to_string(),
+                            |id| format_address(&id, network),
+                        ),
+                    ),
+                    ("Key address", key),
+                    ("Balance", format_balance(&balance, unit)),
+                ])
+            };
+            details.await.map_err(|e| e.to_string())
+        }
+    });
+    let navigate = use_navigate();
+
+    view! {
+        <div class="flex flex-col items-center">
+            <h1 class="mb-4 text-4xl font-extrabold leading-none tracking-tight text-gray-900 md:text-5xl lg:text-6xl">
+                "Address"
+            </h1>
+            <div class="max-w-lg w-full my-2">
+                <form
+                    class="flex"
+                    on:submit=move |ev| {
+                        ev.prevent_default();
+                        navigate(&format!("/address/{}", input.get().trim()), Default::default());
+                    }
+                >
+                    <input
+                        type="text"
+                        placeholder="f0..., f1..., 0x..."
+                        data-shortcut-focus
+                        class="flex-grow border border-gray-300 p-1 rounded-l"
+                        prop:value=input
+                        on:input=move |ev| input.set(event_target_value(&ev))
+                    />
+                    <button type="submit" class="bg-blue-500 hover:bg-blue-700 text-white font-bold py-1 px-2 rounded-r">
+                        "Search"
+                    </button>
+                </form>
+                <p class="font-semibold break-all mt-2">{move || params.read().get("address")}</p>
+                <LookupResult result=Signal::derive(move || details.get().as_deref().cloned()) />
+            </div>
+            <a href="/" class="text-blue-600 hover:underline">"Back to the explorer"</a>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_countdown() {
+        assert_eq!(format_countdown(0), "00:00");
+        assert_eq!(format_countdown(59), "00:59");
+        assert_eq!(format_countdown(600), "10:00");
+        assert_eq!(format_countdown(-3), "00:00");
+    }
+
+    #[test]
+    fn test_wallet_config() {
+        let config = wallet_config(
+            314159,
+            "https://api.calibration.node.glif.io",
+            Network::Testnet,
+        );
+        assert_eq!(config["chainId"], "0x4cb2f");
+        assert_eq!(config["nativeCurrency"]["symbol"], "tFIL");
+        assert_eq!(config["nativeCurrency"]["decimals"], 18);
+        assert_eq!(config["rpcUrls"][0], "https://api.calibration.node.glif.io");
+        assert_eq!(wallet_config(314, "", Network::Mainnet)["chainId"], "0x13a");
+    }
+}
@@ -0,0 +1,448 @@
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::{address::Network, econ::TokenAmount, message::Message};
+use leptos::prelude::*;
+use leptos::server;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Faucet settings which may differ between deployments of the same build. Values missing from
+/// the runtime configuration fall back to the compile-time defaults in [`crate::constants`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub rate_limit_seconds: i64,
+    pub max_batch_recipients: usize,
+    pub max_fee_ratio: f64,
+    #[serde(with = "crate::lotus_json")]
+    pub mainnet_drip_amount: TokenAmount,
+    #[serde(with = "crate::lotus_json")]
+    pub calibnet_drip_amount: TokenAmount,
+    /// Amounts users may pick from. When empty, only the drip amount is offered.
+    #[serde(with = "crate::lotus_json")]
+    pub mainnet_allowed_amounts: Vec<TokenAmount>,
+    #[serde(with = "crate::lotus_json")]
+    pub calibnet_allowed_amounts: Vec<TokenAmount>,
+    /// Highest gas fee cap of drips, in attoFIL per unit of gas.
+    #[serde(with = "crate::lotus_json")]
+    pub mainnet_max_gas_fee_cap: TokenAmount,
+    #[serde(with = "crate::lotus_json")]
+    pub calibnet_max_gas_fee_cap: TokenAmount,
+    pub mainnet_limiter_failure_policy: LimiterFailurePolicy,
+    pub calibnet_limiter_failure_policy: LimiterFailurePolicy,
+    /// Origins the faucet may redirect to after a drip, e.g. `https://example.com`.
+    pub callback_origins: Vec<String>,
+    /// Tag attached to every drip, e.g. an event code, for later analysis of the chain.
+    pub drip_memo: Option<String>,
+    /// Balance below which the calibnet faucet asks `FAUCET_TOPUP_REQ_URL` for a top-up on its
+    /// own. Automatic top-ups are disabled when unset.
+    #[serde(with = "crate::lotus_json")]
+    pub calibnet_topup_threshold: Option<TokenAmount>,
+    pub topup_interval_seconds: i64,
+}
+
+/// What the faucet does when its rate limiter can't be reached.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimiterFailurePolicy {
+    /// Sign anyway. Acceptable where funds are worthless.
+    Open,
+    /// Refuse to sign.
+    Closed,
+}
+
+/// The settings of a single network's faucet, as shown to its users.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FaucetSettings {
+    #[serde(with = "crate::lotus_json")]
+    pub drip_amount: TokenAmount,
+    pub unit: String,
+    pub rate_limit_seconds: i64,
+    #[serde(with = "crate::lotus_json")]
+    pub allowed_amounts: Vec<TokenAmount>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            rate_limit_seconds: crate::constants::RATE_LIMIT_SECONDS,
+            max_batch_recipients: crate::constants::MAX_BATCH_RECIPIENTS,
+            max_fee_ratio: crate::constants::MAX_FEE_RATIO,
+            mainnet_drip_amount: crate::constants::MAINNET_DRIP_AMOUNT.clone(),
+            calibnet_drip_amount: crate::constants::CALIBNET_DRIP_AMOUNT.clone(),
+            mainnet_allowed_amounts: Vec::new(),
+            calibnet_allowed_amounts: Vec::new(),
+            mainnet_max_gas_fee_cap: crate::constants::MAINNET_MAX_GAS_FEE_CAP.clone(),
+            calibnet_max_gas_fee_cap: crate::constants::CALIBNET_MAX_GAS_FEE_CAP.clone(),
+            mainnet_limiter_failure_policy: LimiterFailurePolicy::Closed,
+            calibnet_limiter_failure_policy: LimiterFailurePolicy::Open,
+            callback_origins: Vec::new(),
+            drip_memo: None,
+            calibnet_topup_threshold: None,
+            topup_interval_seconds: crate::constants::TOPUP_INTERVAL_SECONDS,
+        }
+    }
+}
+
+impl Config {
+    pub fn drip_amount(&self, network: Network) -> &TokenAmount {
+        match network {
+            Network::Mainnet => &self.mainnet_drip_amount,
+            Network::Testnet => &self.calibnet_drip_amount,
+        }
+    }
+
+    pub fn allowed_amounts(&self, network: Network) -> Vec<TokenAmount> {
+        let allowed = match network {
+            Network::Mainnet => &self.mainnet_allowed_amounts,
+            Network::Testnet => &self.calibnet_allowed_amounts,
+        };
+        if allowed.is_empty() {
+            vec![self.drip_amount(network).clone()]
+        } else {
+            allowed.clone()
+        }
+    }
+
+    pub fn is_allowed_amount(&self, network: Network, amount: &TokenAmount) -> bool {
+        self.allowed_amounts(network).contains(amount)
+    }
+
+    pub fn max_gas_fee_cap(&self, network: Network) -> &TokenAmount {
+        match network {
+            Network::Mainnet => &self.mainnet_max_gas_fee_cap,
+            Network::Testnet => &self.calibnet_max_gas_fee_cap,
+        }
+    }
+
+    #[cfg(feature = "ssr")]
+    pub fn limiter_failure_policy(&self, network: Network) -> LimiterFailurePolicy {
+        match network {
+            Network::Mainnet => self.mainnet_limiter_failure_policy,
+            Network::Testnet => self.calibnet_limiter_failure_policy,
+        }
+    }
+
+    /// Whether `msg` may cost more per unit of gas than the faucet is willing to pay on `network`.
+    pub fn exceeds_gas_fee_cap(&self, network: Network, msg: &Message) -> bool {
+        msg.gas_fee_cap > *self.max_gas_fee_cap(network)
+    }
+
+    /// Balance below which `network`'s faucet requests a top-up. Only calibnet faucets do, and
+    /// only when a threshold is configured.
+    #[cfg(feature = "ssr")]
+    pub fn topup_threshold(&self, network: Network) -> Option<&TokenAmount> {
+        match network {
+            Network::Mainnet => None,
+            Network::Testnet => self.calibnet_topup_threshold.as_ref(),
+        }
+    }
+
+    /// Whether `network`'s faucet should request a top-up at `balance`.
+    #[cfg(feature = "ssr")]
+    pub fn needs_topup(&self, network: Network, balance: &TokenAmount) -> bool {
+        self.topup_threshold(network)
+            .is_some_and(|threshold| balance < threshold)
+    }
+
+    /// Params of drip messages: the memo as a CBOR string, or nothing without a memo. Transfers
+    /// don't invoke the recipient, so the params are only recorded on chain.
+    pub fn drip_params(&self) -> RawBytes {
+        self.drip_memo
+            .as_ref()
+            .and_then(|memo| RawBytes::serialize(memo).ok())
+            .unwrap_or_default()
+    }
+
+    /// The settings of `network`'s faucet.
+    pub fn faucet_settings(&self, network: Network) -> FaucetSettings {
+        FaucetSettings {
+            drip_amount: self.drip_amount(network).clone(),
+            unit: match network {
+                Network::Mainnet => crate::constants::FIL_MAINNET_UNIT,
+                Network::Testnet => crate::constants::FIL_CALIBNET_UNIT,
+            }
+            .to_string(),
+            rate_limit_seconds: self.rate_limit_seconds,
+            allowed_amounts: self.allowed_amounts(network),
+        }
+    }
+
+    /// Whether the faucet may redirect to `url`. Only allow-listed origins are, so the faucet
+    /// can't be abused as an open redirect.
+    pub fn is_allowed_callback(&self, url: &Url) -> bool {
+        let origin = url.origin().ascii_serialization();
+        self.callback_origins
+            .iter()
+            .any(|allowed| allowed.trim_end_matches('/') == origin)
+    }
+
+    /// Reads the configuration from the `FAUCET_CONFIG` worker variable, e.g.
+    /// `{"rate_limit_seconds": 300, "calibnet_drip_amount": "5000000000000000000"}`.
+    /// Settings that make no sense, e.g. a negative rate limit or a `NaN` fee ratio, fall back
+    /// to their defaults.
+    #[cfg(feature = "ssr")]
+    pub fn from_env(env: &worker::Env) -> Self {
+        let mut config: Self = env
+            .var("FAUCET_CONFIG")
+            .ok()
+            .and_then(|raw| {
+                serde_json::from_str(&raw.to_string())
+                    .inspect_err(|e| {
+                        worker::console_log!("Invalid FAUCET_CONFIG, using defaults: {e}")
+                    })
+                    .ok()
+            })
+            .unwrap_or_default();
+        for field in config.reset_invalid() {
+            worker::console_log!("Invalid {field} in FAUCET_CONFIG, using the default");
+        }
+        config
+    }
+
+    /// Resets the invalid settings to their defaults and returns their names.
+    #[cfg(feature = "ssr")]
+    fn reset_invalid(&mut self) -> Vec<&'static str> {
+        let default = Self::default();
+        let mut reset = Vec::new();
+        macro_rules! check {
+            ($field:ident, $is_invalid:expr) => {
+                if $is_invalid(&self.$field) {
+                    self.$field = default.$field.clone();
+                    reset.push(stringify!($field));
+                }
+            };
+        }
+        let negative_amount = |amount: &TokenAmount| amount.is_negative();
+        check!(rate_limit_seconds, |v: &i64| *v < 0);
+        check!(max_batch_recipients, |v: &usize| *v == 0);
+        check!(max_fee_ratio, |v: &f64| v.is_nan() || *v < 0.0);
+        check!(mainnet_drip_amount, negative_amount);
+        check!(calibnet_drip_amount, negative_amount);
+        check!(mainnet_allowed_amounts, |v: &Vec<TokenAmount>| v
+            .iter()
+            .any(negative_amount));
+        check!(calibnet_allowed_amounts, |v: &Vec<TokenAmount>| v
+            .iter()
+            .any(negative_amount));
+        check!(mainnet_max_gas_fee_cap, negative_amount);
+        check!(calibnet_max_gas_fee_cap, negative_amount);
+        check!(calibnet_topup_threshold, |v: &Option<TokenAmount>| v
+            .as_ref()
+            .is_some_and(negative_amount));
+        check!(topup_interval_seconds, |v: &i64| *v < 0);
+        reset
+    }
+}
+
+#[server]
+pub async fn runtime_config() -> Result<Config, ServerFnError> {
+    use axum::Extension;
+    use leptos_axum::extract;
+    use std::sync::Arc;
+    use worker::Env;
+
+    let Extension(env): Extension<Arc<Env>> = extract().await?;
+    Ok(Config::from_env(&env))
+}
+
+/// The runtime [`Config`], starting with the defaults until the server responds.
+#[derive(Clone, Copy)]
+pub struct ConfigContext {
+    config: RwSignal<Config>,
+    loaded: RwSignal<bool>,
+}
+
+impl ConfigContext {
+    pub fn new() -> Self {
+        let config = RwSignal::new(Config::default());
+        let loaded = RwSignal::new(false);
+        let response = LocalResource::new(runtime_config);
+        Effect::new(move |_| match response.get().as_deref() {
+            Some(Ok(response)) => {
+                config.set(response.clone());
+                loaded.set(true);
+            }
+            Some(Err(e)) => {
+                log::error!("Failed to load runtime config, using defaults: {e}");
+                loaded.set(true);
+            }
+            None => (),
+        });
+        Self { config, loaded }
+    }
+
+    pub fn provide_context() {
+        provide_context(ConfigContext::new());
+    }
+
+    /// Provides `config` as already loaded, for tests which can't reach the server.
+    #[cfg(test)]
+    pub fn provide_loaded(config: Config) {
+        provide_context(ConfigContext {
+            config: RwSignal::new(config),
+            loaded: RwSignal::new(true),
+        });
+    }
+
+    pub fn use_context() -> Self {
+        use_context::<Self>().expect("ConfigContext should be provided")
+    }
+
+    pub fn get(&self) -> Config {
+        self.config.get()
+    }
+
+    pub fn get_untracked(&self) -> Config {
+        self.config.get_untracked()
+    }
+
+    /// Whether the server answered, so the config is no longer the defaults. Drips built before
+    /// then could carry the wrong memo and be refused.
+    pub fn is_loaded(&self) -> bool {
+        self.loaded.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_config_uses_defaults() {
+        let config: Config = serde_json::from_str(
+            r#"{"rate_limit_seconds": 30, "calibnet_drip_amount": "5000000000000000000"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.rate_limit_seconds, 30);
+        assert_eq!(
+            config.drip_amount(Network::Testnet),
+            &TokenAmount::from_whole(5)
+        );
+        assert_eq!(
+            config.drip_amount(Network::Mainnet),
+            &*crate::constants::MAINNET_DRIP_AMOUNT
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "ssr")]
+    fn test_reset_invalid() {
+        let mut config: Config = serde_json::from_str(
+            r#"{"rate_limit_seconds": -1, "max_fee_ratio": -0.5, "calibnet_drip_amount": "-5",
+                "mainnet_allowed_amounts": ["1", "-1"], "topup_interval_seconds": 60}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.reset_invalid(),
+            vec![
+                "rate_limit_seconds",
+                "max_fee_ratio",
+                "calibnet_drip_amount",
+                "mainnet_allowed_amounts"
+            ]
+        );
+        assert_eq!(
+            config,
+            Config {
+                topup_interval_seconds: 60,
+                ..Config::default()
+            }
+        );
+
+        config.max_fee_ratio = f64::NAN;
+        assert_eq!(config.reset_invalid(), vec!["max_fee_ratio"]);
+        assert!(Config::default().reset_invalid().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "ssr")]
+    fn test_needs_topup() {
+        let low = TokenAmount::from_whole(5);
+        let high = TokenAmount::from_whole(500);
+        assert!(!Config::default().needs_topup(Network::Testnet, &low));
+
+        let config: Config =
+            serde_json::from_str(r#"{"calibnet_topup_threshold": "100000000000000000000"}"#)
+                .unwrap();
+        assert!(config.needs_topup(Network::Testnet, &low));
+        assert!(!config.needs_topup(Network::Testnet, &high));
+        assert!(!config.needs_topup(Network::Mainnet, &low));
+    }
+
+    #[test]
+    fn test_allowed_amounts() {
+        let config: Config = serde_json::from_str(
+            r#"{"calibnet_allowed_amounts": ["100000000000000000", "1000000000000000000"]}"#,
+        )
+        .unwrap();
+        assert!(config.is_allowed_amount(Network::Testnet, &TokenAmount::from_whole(1)));
+        assert!(config.is_allowed_amount(Network::Testnet, &TokenAmount::from_nano(100_000_000)));
+        assert!(!config.is_allowed_amount(Network::Testnet, &TokenAmount::from_whole(5)));
+        assert!(!config.is_allowed_amount(Network::Testnet, &TokenAmount::from_nano(1)));
+
+        // Without a list, only the drip amount is allowed.
+        let mainnet_drip = config.drip_amount(Network::Mainnet).clone();
+        assert_eq!(
+            config.allowed_amounts(Network::Mainnet),
+            vec![mainnet_drip.clone()]
+        );
+        assert!(config.is_allowed_amount(Network::Mainnet, &mainnet_drip));
+        assert!(!config.is_allowed_amount(Network::Mainnet, &(mainnet_drip.div_floor(2))));
+    }
+
+    #[test]
+    fn test_faucet_settings() {
+        let config: Config = serde_json::from_str(r#"{"rate_limit_seconds": 30}"#).unwrap();
+        let settings = config.faucet_settings(Network::Testnet);
+        assert_eq!(settings.unit, "tFIL");
+        assert_eq!(settings.rate_limit_seconds, 30);
+        assert_eq!(
+            settings.drip_amount,
+            *crate::constants::CALIBNET_DRIP_AMOUNT
+        );
+        assert_eq!(settings.allowed_amounts, vec![settings.drip_amount.clone()]);
+    }
+
+    #[test]
+    fn test_gas_fee_cap_ceiling() {
+        let config: Config =
+            serde_json::from_str(r#"{"calibnet_max_gas_fee_cap": "1000"}"#).unwrap();
+        let msg = |gas_fee_cap: u64| {
+            crate::message::MessageBuilder::default()
+                .from(fvm_shared::address::Address::new_id(1))
+                .to(fvm_shared::address::Address::new_id(2))
+                .gas_fee_cap(TokenAmount::from_atto(gas_fee_cap))
+                .build()
+                .unwrap()
+        };
+        assert!(!config.exceeds_gas_fee_cap(Network::Testnet, &msg(999)));
+        assert!(!config.exceeds_gas_fee_cap(Network::Testnet, &msg(1000)));
+        assert!(config.exceeds_gas_fee_cap(Network::Testnet, &msg(1001)));
+        // The other network keeps its default.
+        assert!(!config.exceeds_gas_fee_cap(Network::Mainnet, &msg(1001)));
+        assert_eq!(
+            config.max_gas_fee_cap(Network::Mainnet),
+            &*crate::constants::MAINNET_MAX_GAS_FEE_CAP
+        );
+    }
+
+    #[test]
+    fn test_drip_params() {
+        assert!(Config::default().drip_params().is_empty());
+        let config: Config = serde_json::from_str(r#"{"drip_memo": "ethdenver"}"#).unwrap();
+        let params = config.drip_params();
+        assert_eq!(params.deserialize::<String>().unwrap(), "ethdenver");
+    }
+
+    #[test]
+    fn test_allowed_callback() {
+        let config: Config =
+            serde_json::from_str(r#"{"callback_origins": ["https://example.com/"]}"#).unwrap();
+        let allowed = |url: &str| config.is_allowed_callback(&Url::parse(url).unwrap());
+        assert!(allowed("https://example.com/faucet/done?id=1"));
+        assert!(!allowed("http://example.com/faucet/done"));
+        assert!(!allowed("https://example.com.evil.io/"));
+        assert!(!allowed("https://evil.io/?https://example.com"));
+        assert!(!Config::default().is_allowed_callback(&Url::parse("https://example.com").unwrap()));
+    }
+}
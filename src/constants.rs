@@ -11,5 +11,47 @@ pub static MAINNET_DRIP_AMOUNT: LazyLock<TokenAmount> =
 /// The amount of calibnet tFIL to be dripped to the user.
 pub static CALIBNET_DRIP_AMOUNT: LazyLock<TokenAmount> =
     LazyLock::new(|| TokenAmount::from_whole(1));
+/// Default number of signing requests a worker instance handles at once. Overridden by the
+/// `SIGN_CONCURRENCY_LIMIT` secret.
+#[cfg(feature = "ssr")]
+pub const SIGN_CONCURRENCY_LIMIT: usize = 4;
+/// Mainnet drips are refused while their maximum gas fee is at least this fraction of the drip
+/// amount.
+pub const MAX_FEE_RATIO: f64 = 0.5;
+/// Highest gas fee cap, in attoFIL per unit of gas, of the mainnet messages the faucet signs.
+pub static MAINNET_MAX_GAS_FEE_CAP: LazyLock<TokenAmount> =
+    LazyLock::new(|| TokenAmount::from_nano(1));
+/// Highest gas fee cap, in attoFIL per unit of gas, of the calibnet messages the faucet signs.
+pub static CALIBNET_MAX_GAS_FEE_CAP: LazyLock<TokenAmount> =
+    LazyLock::new(|| TokenAmount::from_nano(10));
+/// Minimum time between two automatic top-up requests of the calibnet faucet.
+pub const TOPUP_INTERVAL_SECONDS: i64 = 24 * 60 * 60;
+/// Number of recent drips kept per network for the public activity feed.
+#[cfg(feature = "ssr")]
+pub const DRIP_LOG_CAPACITY: usize = 500;
+/// Number of drips per page of the activity feed.
+#[cfg(feature = "ssr")]
+pub const DRIP_LOG_PAGE_SIZE: usize = 50;
+/// Drips not found on chain this long after they were signed are left out of the activity feed.
+#[cfg(feature = "ssr")]
+pub const DRIP_CONFIRMATION_TIMEOUT_SECONDS: i64 = 10 * 60;
+/// Upper bound on the number of recipients of a single batch drip.
+pub const MAX_BATCH_RECIPIENTS: usize = 20;
+/// Explorer linked for mainnet faucet transactions unless `FAUCET_TX_URL_MAINNET` is set.
+pub const MAINNET_TX_URL: &str = "https://beryx.io/fil/mainnet/";
+/// Explorer linked for calibnet faucet transactions unless `FAUCET_TX_URL_CALIBNET` is set.
+pub const CALIBNET_TX_URL: &str = "https://beryx.io/fil/calibration/";
 pub static FIL_MAINNET_UNIT: &str = "FIL";
 pub static FIL_CALIBNET_UNIT: &str = "tFIL";
+/// How far back, in epochs, the faucet page looks on chain for earlier drips when it loads.
+#[cfg(feature = "hydrate")]
+pub const FAUCET_HISTORY_LOOKBACK: i64 = 240;
+/// Most earlier drips the faucet page restores from chain.
+#[cfg(feature = "hydrate")]
+pub const FAUCET_HISTORY_LENGTH: usize = 20;
+/// Number of epochs on top of a message's tipset after which the faucet considers it final.
+/// This matches Filecoin's expected consensus finality.
+pub const FINALITY_DEPTH: i64 = 900;
+/// Seconds between two Filecoin epochs.
+#[cfg(any(feature = "ssr", feature = "hydrate", test))]
+pub const EPOCH_DURATION_SECONDS: i64 = 30;
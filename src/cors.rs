@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::Request,
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use worker::Env;
+
+/// Comma-separated list of origins allowed to call the server functions, e.g.
+/// `https://example.com,https://docs.example.com`. When unset, only same-origin requests work.
+const CORS_ALLOWED_ORIGINS: &str = "CORS_ALLOWED_ORIGINS";
+const CORS_MAX_AGE_SECONDS: &str = "86400";
+
+/// The origins read from `CORS_ALLOWED_ORIGINS`, shared with the middlewares as an extension.
+#[derive(Clone, Debug, Default)]
+pub struct AllowedOrigins(Arc<Vec<String>>);
+
+impl AllowedOrigins {
+    pub fn from_env(env: &Env) -> Self {
+        Self(Arc::new(
+            env.secret(CORS_ALLOWED_ORIGINS)
+                .map(|v| parse_origins(&v.to_string()))
+                .unwrap_or_default(),
+        ))
+    }
+}
+
+fn parse_origins(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(|origin| origin.trim().trim_end_matches('/').to_string())
+        .filter(|origin| !origin.is_empty())
+        .collect()
+}
+
+/// Adds CORS headers for allow-listed origins and answers their preflight requests. Requests
+/// from other origins are passed through untouched, so the browser enforces same-origin.
+pub async fn cors(
+    Extension(allowed): Extension<AllowedOrigins>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let origin = req
+        .headers()
+        .get(header::ORIGIN)
+        .filter(|origin| {
+            origin
+                .to_str()
+                .is_ok_and(|origin| allowed.0.iter().any(|o| o == origin))
+        })
+        .cloned();
+    let Some(origin) = origin else {
+        return next.run(req).await;
+    };
+
+    let mut res = if req.method() == Method::OPTIONS {
+        let mut res = StatusCode::NO_CONTENT.into_response();
+        let headers = res.headers_mut();
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_METHODS,
+            HeaderValue::from_static("POST, OPTIONS"),
+        );
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_HEADERS,
+            HeaderValue::from_static("content-type, accept"),
+        );
+        headers.insert(
+            header::ACCESS_CONTROL_MAX_AGE,
+            HeaderValue::from_static(CORS_MAX_AGE_SECONDS),
+        );
+        res
+    } else {
+        next.run(req).await
+    };
+    let headers = res.headers_mut();
+    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+    headers.append(header::VARY, HeaderValue::from_static("origin"));
+    res
+}
+
+/// Only the embeddable pages may be framed, and only by the allow-listed origins.
+pub async fn frame_ancestors(
+    Extension(allowed): Extension<AllowedOrigins>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let embed = crate::app::is_embed_path(req.uri().path());
+    let mut res = next.run(req).await;
+    let policy = if embed {
+        frame_ancestors_policy(&allowed.0)
+    } else {
+        frame_ancestors_policy(&[])
+    };
+    if let Ok(policy) = HeaderValue::from_str(&policy) {
+        res.headers_mut()
+            .insert(header::CONTENT_SECURITY_POLICY, policy);
+    }
+    res
+}
+
+fn frame_ancestors_policy(origins: &[String]) -> String {
+    std::iter::once("frame-ancestors 'self'")
+        .chain(origins.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use futures::executor::block_on;
+    use tower_service::Service as _;
+
+    fn preflight(origin: &str) -> Response {
+        let mut router = crate::ssr_imports::api_router::<()>().layer(Extension(AllowedOrigins(
+            Arc::new(vec!["https://a.com".to_string()]),
+        )));
+        let req = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/api/sign_with_secret_key")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(Body::empty())
+            .unwrap();
+        block_on(router.call(req)).unwrap()
+    }
+
+    #[test]
+    fn test_preflight() {
+        let res = preflight("https://a.com");
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        assert_eq!(
+            res.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://a.com"
+        );
+        assert_eq!(
+            res.headers()[header::ACCESS_CONTROL_ALLOW_METHODS],
+            "POST, OPTIONS"
+        );
+
+        let res = preflight("https://b.com");
+        assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert!(!res
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+
+    #[test]
+    fn test_parse_origins() {
+        assert_eq!(
+            parse_origins("https://a.com, https://b.com/ ,,"),
+            vec!["https://a.com", "https://b.com"]
+        );
+        assert!(parse_origins("").is_empty());
+    }
+
+    #[test]
+    fn test_frame_ancestors_policy() {
+        assert_eq!(frame_ancestors_policy(&[]), "frame-ancestors 'self'");
+        assert_eq!(
+            frame_ancestors_policy(&["https://a.com".to_string(), "https://b.com".to_string()]),
+            "frame-ancestors 'self' https://a.com https://b.com"
+        );
+    }
+}
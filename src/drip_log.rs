@@ -0,0 +1,231 @@
+use std::collections::HashSet;
+
+use cid::Cid;
+use fvm_shared::address::Network;
+use serde::{Deserialize, Serialize};
+use worker::*;
+
+use crate::constants::{
+    DRIP_CONFIRMATION_TIMEOUT_SECONDS, DRIP_LOG_CAPACITY, DRIP_LOG_PAGE_SIZE,
+    EPOCH_DURATION_SECONDS,
+};
+use crate::faucet::utils::DripRecord;
+use crate::rpc_context::Provider;
+use crate::store::Store;
+
+/// Time between two checks for pending drips on chain.
+const CONFIRMATION_CHECK_INTERVAL: std::time::Duration =
+    std::time::Duration::from_secs(EPOCH_DURATION_SECONDS as u64);
+
+#[derive(Serialize, Deserialize)]
+pub enum DripLogRequest {
+    /// Adds drips, in the order they were signed, once they're on chain.
+    Record {
+        is_mainnet: bool,
+        drips: Vec<DripRecord>,
+    },
+    /// Lists a page of the most recent drips, newest first.
+    List { page: usize },
+}
+
+/// Keeps the most recent drips of a faucet, for the public activity feed. One instance exists
+/// per network. Signed drips stay pending until the alarm finds them on chain, so drips that were
+/// never pushed don't show up.
+#[durable_object]
+pub struct DripLog {
+    state: State,
+}
+
+#[durable_object]
+impl DurableObject for DripLog {
+    fn new(state: State, _env: Env) -> Self {
+        Self { state }
+    }
+
+    async fn fetch(&mut self, mut req: Request) -> Result<Response> {
+        match req.json().await? {
+            DripLogRequest::Record { is_mainnet, drips } => {
+                add_pending(&mut self.state.storage(), is_mainnet, drips).await?;
+                Response::from_json(&true)
+            }
+            DripLogRequest::List { page } => {
+                Response::from_json(&list(&self.state.storage(), page, DRIP_LOG_PAGE_SIZE).await)
+            }
+        }
+    }
+
+    async fn alarm(&mut self) -> Result<Response> {
+        let mut storage = self.state.storage();
+        let pending = storage
+            .get::<Vec<DripRecord>>("pending")
+            .await
+            .unwrap_or_default();
+        let network = if storage.get::<bool>("is_mainnet").await.unwrap_or_default() {
+            Network::Mainnet
+        } else {
+            Network::Testnet
+        };
+        let on_chain = on_chain_cids(network, &pending).await;
+        let (confirmed, pending) = settle(pending, &on_chain, chrono::Utc::now().timestamp());
+        record(&mut storage, confirmed, DRIP_LOG_CAPACITY).await?;
+        if !pending.is_empty() {
+            storage.set_alarm(CONFIRMATION_CHECK_INTERVAL).await?;
+        }
+        storage.put("pending", pending).await?;
+        Response::ok("OK")
+    }
+}
+
+/// Queues `drips` until they're found on chain, checking for them on the next alarm.
+async fn add_pending(
+    store: &mut impl Store,
+    is_mainnet: bool,
+    drips: Vec<DripRecord>,
+) -> Result<()> {
+    let mut pending = store
+        .get::<Vec<DripRecord>>("pending")
+        .await
+        .unwrap_or_default();
+    // Rescheduling a pending alarm would postpone it for as long as drips keep coming in.
+    let schedule = pending.is_empty();
+    pending.extend(drips);
+    store.put("pending", pending).await?;
+    store.put("is_mainnet", is_mainnet).await?;
+    if schedule {
+        store.set_alarm(CONFIRMATION_CHECK_INTERVAL).await?;
+    }
+    Ok(())
+}
+
+/// The CIDs of the `pending` drips that are on chain. None are when the node can't be reached.
+async fn on_chain_cids(network: Network, pending: &[DripRecord]) -> HashSet<String> {
+    let cids = pending
+        .iter()
+        .filter_map(|drip| drip.cid.parse::<Cid>().ok())
+        .collect::<Vec<_>>();
+    match Provider::from_network(network)
+        .state_search_msgs(&cids)
+        .await
+    {
+        Ok(lookups) => cids
+            .iter()
+            .zip(lookups)
+            .filter(|(_, lookup)| matches!(lookup, Ok(Some(_))))
+            .map(|(cid, _)| cid.to_string())
+            .collect(),
+        Err(e) => {
+            console_log!("Failed to look up pending drips: {e}");
+            HashSet::new()
+        }
+    }
+}
+
+/// Splits the `pending` drips into those found `on_chain` and those still worth waiting for.
+/// Drips signed more than [`DRIP_CONFIRMATION_TIMEOUT_SECONDS`] before `now` are given up on,
+/// e.g. because they were never pushed.
+fn settle(
+    pending: Vec<DripRecord>,
+    on_chain: &HashSet<String>,
+    now: i64,
+) -> (Vec<DripRecord>, Vec<DripRecord>) {
+    let (confirmed, pending): (Vec<_>, Vec<_>) = pending
+        .into_iter()
+        .partition(|drip| on_chain.contains(&drip.cid));
+    let pending = pending
+        .into_iter()
+        .filter(|drip| now - drip.timestamp < DRIP_CONFIRMATION_TIMEOUT_SECONDS)
+        .collect();
+    (confirmed, pending)
+}
+
+/// Adds `drips` to the log, dropping the oldest ones beyond `capacity`.
+async fn record(store: &mut impl Store, drips: Vec<DripRecord>, capacity: usize) -> Result<()> {
+    let mut log = store
+        .get::<Vec<DripRecord>>("drips")
+        .await
+        .unwrap_or_default();
+    log.splice(0..0, drips.into_iter().rev());
+    log.truncate(capacity);
+    store.put("drips", log).await
+}
+
+/// The `page`th page of `page_size` drips, newest first. Empty past the end of the log.
+async fn list(store: &impl Store, page: usize, page_size: usize) -> Vec<DripRecord> {
+    store
+        .get::<Vec<DripRecord>>("drips")
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .skip(page.saturating_mul(page_size))
+        .take(page_size)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+    use futures::executor::block_on;
+    use fvm_shared::econ::TokenAmount;
+
+    fn drip(timestamp: i64) -> DripRecord {
+        DripRecord {
+            cid: format!("bafy{timestamp}"),
+            to: "t01234".to_string(),
+            amount: TokenAmount::from_whole(1),
+            timestamp,
+        }
+    }
+
+    fn timestamps(drips: Vec<DripRecord>) -> Vec<i64> {
+        drips.into_iter().map(|drip| drip.timestamp).collect()
+    }
+
+    #[test]
+    fn test_drip_log() {
+        let mut store = MemoryStore::default();
+        assert!(block_on(list(&store, 0, 2)).is_empty());
+
+        block_on(record(&mut store, vec![drip(1), drip(2)], 4)).unwrap();
+        block_on(record(&mut store, vec![drip(3)], 4)).unwrap();
+        assert_eq!(timestamps(block_on(list(&store, 0, 2))), vec![3, 2]);
+        assert_eq!(timestamps(block_on(list(&store, 1, 2))), vec![1]);
+        assert!(block_on(list(&store, 2, 2)).is_empty());
+
+        // The oldest drips are dropped once the log is full.
+        block_on(record(&mut store, vec![drip(4), drip(5)], 4)).unwrap();
+        assert_eq!(timestamps(block_on(list(&store, 0, 10))), vec![5, 4, 3, 2]);
+    }
+
+    #[test]
+    fn test_add_pending() {
+        let mut store = MemoryStore::default();
+        block_on(add_pending(&mut store, true, vec![drip(1)])).unwrap();
+        assert_eq!(store.alarm, Some(CONFIRMATION_CHECK_INTERVAL));
+        // Pending drips aren't listed.
+        assert!(block_on(list(&store, 0, 10)).is_empty());
+
+        // The alarm isn't postponed by later drips.
+        store.alarm = None;
+        block_on(add_pending(&mut store, true, vec![drip(2)])).unwrap();
+        assert_eq!(store.alarm, None);
+        let pending = block_on(store.get::<Vec<DripRecord>>("pending")).unwrap();
+        assert_eq!(timestamps(pending), vec![1, 2]);
+        assert_eq!(block_on(store.get::<bool>("is_mainnet")), Some(true));
+    }
+
+    #[test]
+    fn test_settle() {
+        let now = 10_000;
+        let pending = vec![
+            drip(now - 10),
+            drip(now - 20),
+            drip(now - DRIP_CONFIRMATION_TIMEOUT_SECONDS),
+        ];
+        let on_chain = HashSet::from([drip(now - 20).cid]);
+        let (confirmed, pending) = settle(pending, &on_chain, now);
+        assert_eq!(timestamps(confirmed), vec![now - 20]);
+        // The oldest drip is given up on.
+        assert_eq!(timestamps(pending), vec![now - 10]);
+    }
+}
@@ -0,0 +1,65 @@
+use fvm_shared::address::Network;
+use serde_json::json;
+use url::Url;
+use wasm_bindgen::prelude::*;
+
+use super::model::SentMessage;
+use crate::address::network_name;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "parent"], js_name = postMessage)]
+    fn post_to_parent(message: JsValue, target_origin: &str);
+
+    #[wasm_bindgen(js_namespace = ["window", "location"], js_name = assign)]
+    fn navigate_to(url: &str);
+}
+
+/// Tells the embedding page that `sent` was confirmed, then follows the `callback` URL if there
+/// is one. The message only carries public chain data, so it's posted to any origin.
+pub fn notify_confirmed(network: Network, sent: &SentMessage, callback: Option<&Url>) {
+    let message = json!({
+        "type": "faucet-drip-confirmed",
+        "network": network_name(network),
+        "cid": sent.cid.to_string(),
+        "amount": sent.amount.atto().to_string(),
+    });
+    match js_sys::JSON::parse(&message.to_string()) {
+        Ok(message) => post_to_parent(message, "*"),
+        Err(e) => log::error!("Failed to notify the embedding page: {e:?}"),
+    }
+    if let Some(callback) = callback {
+        navigate_to(callback_url(callback, network, sent).as_str());
+    }
+}
+
+/// `callback` with the network, CID and amount (in attoFIL) of the drip added to its query.
+fn callback_url(callback: &Url, network: Network, sent: &SentMessage) -> Url {
+    let mut url = callback.clone();
+    url.query_pairs_mut()
+        .append_pair("network", network_name(network))
+        .append_pair("cid", &sent.cid.to_string())
+        .append_pair("amount", &sent.amount.atto().to_string());
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cid::Cid;
+    use fvm_shared::econ::TokenAmount;
+
+    #[test]
+    fn test_callback_url() {
+        let sent = SentMessage::new(Cid::default(), TokenAmount::from_atto(5));
+        let callback = Url::parse("https://example.com/done?session=1").unwrap();
+        let url = callback_url(&callback, Network::Testnet, &sent);
+        assert_eq!(
+            url.as_str(),
+            format!(
+                "https://example.com/done?session=1&network=calibnet&cid={}&amount=5",
+                Cid::default()
+            )
+        );
+    }
+}
@@ -1,40 +1,111 @@
-use super::{model::FaucetModel, utils::sign_with_secret_key};
-use cid::Cid;
-use fvm_shared::{address::Network, econ::TokenAmount};
+use super::{
+    callback::notify_confirmed,
+    error::{FaucetError, FaucetErrorCode},
+    model::{
+        merge_sent_messages, FaucetModel, InFlight, InFlightGuard, SentMessage, SimulatedDrip,
+    },
+    signer::{external_address, sign_external, Signer},
+    tabs::{DripAnnouncement, TabMessage, Tabs},
+    utils::{sign_batch_with_secret_key, sign_with_secret_key},
+};
+use fvm_shared::{
+    address::{Address, Network},
+    clock::ChainEpoch,
+    econ::TokenAmount,
+    message::Message,
+};
 use leptos::prelude::*;
 use leptos::task::spawn_local;
+use url::Url;
 use uuid::Uuid;
 
 use crate::{
-    address::parse_address, lotus_json::LotusJson, message::message_transfer,
-    rpc_context::Provider, utils::catch_all,
+    actor::ActorKind,
+    address::{
+        f4_to_eth, format_address, lookup_id, network_name, parse_address, parse_address_typed,
+        parse_addresses, refers_to_actor, to_filecoin_display, wrong_network, AddressProtocol,
+    },
+    blocks::TipSetKey,
+    config::{Config, ConfigContext},
+    lotus_json::LotusJson,
+    message::{MessageBuilder, SignedMessage},
+    rpc_context::{Provider, RpcError, RpcProvider},
+    utils::{catch_all, seconds_until},
 };
 
 use super::utils::faucet_address;
 
+/// How many times a drip is resubmitted with a fresh nonce after the node reports the nonce was
+/// already used, e.g. by a concurrent drip.
+const NONCE_RETRIES: usize = 1;
+
 #[derive(Clone)]
-pub(super) struct FaucetController {
+pub(super) struct FaucetController<P = Provider> {
     faucet: FaucetModel,
+    /// The node of the faucet's network.
+    rpc: P,
 }
 
 impl FaucetController {
     pub fn new(network: Network) -> Self {
+        Self::with_provider(network, Provider::from_network(network))
+    }
+}
+
+impl<P: RpcProvider + Clone + 'static> FaucetController<P> {
+    /// A controller reaching the faucet's network through `rpc`, e.g. a mock in tests.
+    pub fn with_provider(network: Network, rpc: P) -> Self {
         let is_mainnet = network == Network::Mainnet;
         let balance_trigger = Trigger::new();
         let sender_address = RwSignal::new(String::new());
         let target_address = RwSignal::new(String::new());
-        let target_balance = LocalResource::new(move || {
-            let target_address = target_address.get();
-            balance_trigger.track();
-            async move {
-                if let Ok(address) = parse_address(&target_address, network) {
-                    Provider::from_network(network)
-                        .wallet_balance(address)
+        let target_balance = LocalResource::new({
+            let rpc = rpc.clone();
+            move || {
+                let rpc = rpc.clone();
+                let target_address = target_address.get();
+                balance_trigger.track();
+                async move {
+                    if let Ok(address) = parse_address(&target_address, network) {
+                        rpc.wallet_balance(address)
+                            .await
+                            .ok()
+                            .unwrap_or(TokenAmount::from_atto(0))
+                    } else {
+                        TokenAmount::from_atto(0)
+                    }
+                }
+            }
+        });
+        let target_actor = LocalResource::new({
+            let rpc = rpc.clone();
+            move || {
+                let rpc = rpc.clone();
+                let target_address = target_address.get();
+                async move {
+                    let (address, protocol) = parse_address_typed(&target_address, network).ok()?;
+                    if protocol != AddressProtocol::Delegated {
+                        return None;
+                    }
+                    rpc.actor_kind(address)
                         .await
+                        .inspect_err(|e| log::warn!("Failed to look up target actor: {}", e))
+                        .ok()
+                        .flatten()
+                }
+            }
+        });
+        let target_resolved = LocalResource::new({
+            let rpc = rpc.clone();
+            move || {
+                let rpc = rpc.clone();
+                let target_address = target_address.get();
+                async move {
+                    let address = parse_address(&target_address, network).ok()?;
+                    rpc.resolve_address(address)
+                        .await
+                        .inspect_err(|e| log::warn!("Failed to resolve target address: {}", e))
                         .ok()
-                        .unwrap_or(TokenAmount::from_atto(0))
-                } else {
-                    TokenAmount::from_atto(0)
                 }
             }
         });
@@ -44,77 +115,241 @@ impl FaucetController {
                 .map(|LotusJson(addr)| addr)
                 .ok()
         });
-        let faucet_balance = LocalResource::new(move || {
-            balance_trigger.track();
-            async move {
-                if let Some(addr) = faucet_address.await {
-                    sender_address.set(addr.to_string());
-                    Provider::from_network(network)
-                        .wallet_balance(addr)
-                        .await
-                        .ok()
-                        .unwrap_or(TokenAmount::from_atto(0))
-                } else {
-                    TokenAmount::from_atto(0)
+        let faucet_balance = LocalResource::new({
+            let rpc = rpc.clone();
+            move || {
+                let rpc = rpc.clone();
+                balance_trigger.track();
+                async move {
+                    if let Some(addr) = faucet_address.await {
+                        sender_address.set(format_address(&addr, network));
+                        rpc.wallet_balance(addr)
+                            .await
+                            .ok()
+                            .unwrap_or(TokenAmount::from_atto(0))
+                    } else {
+                        TokenAmount::from_atto(0)
+                    }
                 }
             }
         });
+        let sent_messages = RwSignal::new(Vec::<SentMessage>::new());
+        let chain_height = RwSignal::new(None);
+        // Other tabs share their drips, and the polling tab shares what it learns about them.
+        let tabs = Tabs::new(network, move |message| match message {
+            TabMessage::Sent {
+                cid,
+                amount,
+                sender,
+            } => sent_messages.update(|messages| {
+                let mut msg = SentMessage::new(cid, amount);
+                msg.sender = sender;
+                merge_sent_messages(messages, [msg]);
+            }),
+            TabMessage::Confirmed(lookup) => {
+                sent_messages.update(|messages| {
+                    for msg in messages {
+                        if msg.cid == lookup.message && !msg.is_confirmed() {
+                            msg.lookup = Some(lookup.clone());
+                        }
+                    }
+                });
+                balance_trigger.notify();
+            }
+            TabMessage::Head(height) => chain_height.set(Some(height)),
+            TabMessage::Heartbeat | TabMessage::DripStarted | TabMessage::DripFinished => {}
+        });
         let faucet = FaucetModel {
             network,
             send_disabled: RwSignal::new(false),
-            send_limited: RwSignal::new(0),
-            sent_messages: RwSignal::new(Vec::new()),
+            in_flight: InFlight::default(),
+            tabs,
+            send_limited: RwSignal::new(None),
+            sent_messages,
             error_messages: RwSignal::new(Vec::new()),
             balance_trigger,
             target_balance,
+            target_actor,
+            target_resolved,
             faucet_balance,
             sender_address,
             target_address,
+            chain_height,
+            show_confirmations: RwSignal::new(is_mainnet),
+            simulation: RwSignal::new(None),
+            config: ConfigContext::use_context(),
+            signer: RwSignal::new(Signer::default()),
+            amount_in_atto: RwSignal::new(false),
+            amount: RwSignal::new(None),
+            callback: RwSignal::new(None),
+            last_signed: RwSignal::new(None),
         };
-        Self { faucet }
+        Self { faucet, rpc }
     }
 
     #[allow(dead_code)]
     pub fn refetch_balances(&self) {
         use leptos::prelude::GetUntracked;
 
+        if !self.faucet.tabs.heartbeat() {
+            // Another tab polls, and shares what it finds.
+            return;
+        }
         log::info!("Checking for new transactions");
         self.faucet.balance_trigger.notify();
-        let pending = self
-            .faucet
-            .sent_messages
-            .get_untracked()
-            .into_iter()
-            .filter_map(|(cid, sent)| if !sent { Some(cid) } else { None })
+        let sent_messages = self.faucet.sent_messages.get_untracked();
+        let pending = sent_messages
+            .iter()
+            .filter(|msg| !msg.is_confirmed())
+            .map(|msg| msg.cid)
             .collect::<Vec<_>>();
+        let chain_height = self.faucet.chain_height;
+        let awaiting_finality = sent_messages
+            .iter()
+            .any(|msg| !msg.is_final(chain_height.get_untracked()));
 
         let network = self.faucet.network;
         let messages = self.faucet.sent_messages;
+        let tabs = self.faucet.tabs.clone();
+        let config = self.faucet.config.get_untracked();
+        let callback = self.faucet.callback.get_untracked().filter(|url| {
+            let allowed = config.is_allowed_callback(url);
+            if !allowed {
+                log::warn!("Ignoring callback to {url}, its origin isn't allowed");
+            }
+            allowed
+        });
+        let rpc = self.rpc.clone();
         spawn_local(catch_all(self.faucet.error_messages, async move {
-            for cid in pending {
-                if let Some(lookup) = Provider::from_network(network)
-                    .state_search_msg(cid)
-                    .await?
-                {
+            if awaiting_finality {
+                let height = rpc.chain_head().await?.height;
+                chain_height.set(Some(height));
+                tabs.post(TabMessage::Head(height));
+            }
+            let mut error = None;
+            for lookup in rpc.state_search_msgs(&pending).await? {
+                let lookup = match lookup {
+                    Ok(lookup) => lookup,
+                    Err(e) => {
+                        error.get_or_insert(e);
+                        continue;
+                    }
+                };
+                if let Some(lookup) = lookup {
+                    tabs.post(TabMessage::Confirmed(lookup.clone()));
+                    let mut confirmed = None;
                     messages.update(|messages| {
-                        for (cid, sent) in messages {
-                            if cid == &lookup.message {
-                                *sent = true;
+                        for msg in messages {
+                            if msg.cid == lookup.message && !msg.is_confirmed() {
+                                msg.lookup = Some(lookup.clone());
+                                confirmed = Some(msg.clone());
                             }
                         }
                     });
+                    // Reverted drips didn't deliver any funds.
+                    if let Some(sent) = confirmed.filter(|sent| sent.failed_exit_code().is_none()) {
+                        notify_confirmed(network, &sent, callback.as_ref());
+                    }
                 }
             }
-            Ok(())
+            error.map_or(Ok(()), Err)
         }));
     }
-    pub fn get_target_balance(&self) -> TokenAmount {
-        self.faucet
-            .target_balance
+    /// Restores the faucet's drips of the last [`FAUCET_HISTORY_LOOKBACK`] epochs from chain, so
+    /// the list of transactions survives a page reload.
+    #[cfg(feature = "hydrate")]
+    pub fn load_history(&self) {
+        use crate::constants::{FAUCET_HISTORY_LENGTH, FAUCET_HISTORY_LOOKBACK};
+        use fvm_shared::METHOD_SEND;
+
+        let is_mainnet = self.faucet.network == Network::Mainnet;
+        let messages = self.faucet.sent_messages;
+        let rpc = self.rpc.clone();
+        spawn_local(async move {
+            let history = async {
+                let from = sender_address(Signer::Faucet, is_mainnet).await?;
+                let head = rpc.chain_head().await?.height;
+                let cids = rpc
+                    .state_list_messages(from, (head - FAUCET_HISTORY_LOOKBACK).max(0))
+                    .await?
+                    .into_iter()
+                    .take(FAUCET_HISTORY_LENGTH)
+                    .collect::<Vec<_>>();
+                let msgs = rpc.chain_get_messages(&cids).await?;
+                let lookups = rpc.state_search_msgs(&cids).await?;
+                anyhow::Ok(
+                    cids.into_iter()
+                        .zip(msgs)
+                        .zip(lookups)
+                        .filter_map(|((cid, msg), lookup)| {
+                            let msg = msg.ok().filter(|msg| msg.method_num == METHOD_SEND)?;
+                            Some(
+                                SentMessage::from_chain(cid, msg.value, lookup.ok()??, head)
+                                    .with_sender(msg.from, msg.sequence),
+                            )
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            };
+            match history.await {
+                Ok(history) => messages.update(|messages| merge_sent_messages(messages, history)),
+                Err(e) => log::warn!("Failed to load the faucet's recent drips: {}", e),
+            }
+        });
+    }
+
+    /// The target balance, or `None` until it's first loaded.
+    pub fn get_target_balance(&self) -> Option<TokenAmount> {
+        self.faucet.target_balance.get().as_deref().cloned()
+    }
+
+    /// The forms of the target address other than the one entered, formatted for the network.
+    /// `f410` addresses are also shown in their `0x` form.
+    pub fn get_target_aliases(&self) -> Vec<String> {
+        let network = self.faucet.network;
+        let target = self.faucet.target_address.get();
+        let entered = parse_address(&target, network).ok();
+        let eth = entered
+            .as_ref()
+            .filter(|_| !target.trim().to_lowercase().starts_with("0x"))
+            .and_then(f4_to_eth);
+        let resolved = self
+            .faucet
+            .target_resolved
             .get()
             .as_deref()
             .cloned()
-            .unwrap_or_default()
+            .flatten()
+            .map(|resolved| {
+                [resolved.id, resolved.key]
+                    .into_iter()
+                    .flatten()
+                    .filter(|addr| Some(*addr) != entered)
+                    .map(|addr| format_address(&addr, network))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        eth.into_iter().chain(resolved).collect()
+    }
+
+    /// The Filecoin address funds go to if the target was entered as a `0x` address.
+    pub fn get_target_filecoin_address(&self) -> Option<String> {
+        let network = self.faucet.network;
+        let target = self.faucet.target_address.get();
+        let (addr, protocol) = parse_address_typed(&target, network).ok()?;
+        let entered_eth = target.trim().to_lowercase().starts_with("0x");
+        (protocol == AddressProtocol::Delegated && entered_eth)
+            .then(|| to_filecoin_display(&addr, network))
+    }
+
+    /// Whether the target address belongs to a smart contract rather than an account.
+    pub fn is_target_contract(&self) -> bool {
+        self.faucet
+            .target_actor
+            .get()
+            .as_deref()
+            .flatten()
+            .is_some_and(ActorKind::is_contract)
     }
 
     pub fn get_sender_address(&self) -> String {
@@ -133,6 +368,51 @@ impl FaucetController {
         .to_string()
     }
 
+    pub fn get_allowed_amounts(&self) -> Vec<TokenAmount> {
+        self.faucet
+            .config
+            .get()
+            .allowed_amounts(self.faucet.network)
+    }
+
+    /// The amount to drip: the one picked by the user if it's still allowed, otherwise the first
+    /// allowed amount.
+    pub fn get_amount(&self) -> TokenAmount {
+        let config = self.faucet.config.get();
+        let network = self.faucet.network;
+        self.faucet
+            .amount
+            .get()
+            .filter(|amount| config.is_allowed_amount(network, amount))
+            .unwrap_or_else(|| config.allowed_amounts(network)[0].clone())
+    }
+
+    pub fn set_amount(&self, amount: TokenAmount) {
+        self.faucet.amount.set(Some(amount));
+    }
+
+    pub fn get_amount_in_atto(&self) -> bool {
+        self.faucet.amount_in_atto.get()
+    }
+
+    pub fn set_amount_in_atto(&self, in_atto: bool) {
+        self.faucet.amount_in_atto.set(in_atto);
+    }
+
+    /// Picks the amount entered as a number of attoFIL, reporting amounts that aren't allowed.
+    pub fn set_amount_atto(&self, raw: &str) {
+        match parse_atto_amount(raw, &self.get_allowed_amounts()) {
+            Ok(amount) => self.set_amount(amount),
+            Err(e) => self.add_error_message(e.to_string()),
+        }
+    }
+
+    /// Sets the URL to redirect to once a drip is confirmed. It's only followed if its origin is
+    /// allow-listed in the runtime configuration.
+    pub fn set_callback(&self, callback: Option<Url>) {
+        self.faucet.callback.set(callback);
+    }
+
     pub fn set_target_address(&self, address: String) {
         self.faucet.target_address.set(address);
     }
@@ -162,75 +442,617 @@ impl FaucetController {
         });
     }
 
-    pub fn get_sent_messages(&self) -> Vec<(Cid, bool)> {
+    pub fn get_last_signed(&self) -> Option<SignedMessage> {
+        self.faucet.last_signed.get()
+    }
+
+    pub fn get_sent_messages(&self) -> Vec<SentMessage> {
         self.faucet.sent_messages.get().clone()
     }
 
+    pub fn get_chain_height(&self) -> Option<ChainEpoch> {
+        self.faucet.chain_height.get()
+    }
+
+    pub fn get_simulation(&self) -> Option<Vec<SimulatedDrip>> {
+        self.faucet.simulation.get()
+    }
+
+    pub fn get_show_confirmations(&self) -> bool {
+        self.faucet.show_confirmations.get()
+    }
+
+    pub fn set_show_confirmations(&self, show: bool) {
+        self.faucet.show_confirmations.set(show);
+    }
+
+    pub fn get_signer(&self) -> Signer {
+        self.faucet.signer.get()
+    }
+
+    pub fn set_signer(&self, signer: Signer) {
+        self.faucet.signer.set(signer);
+    }
+
+    /// Marks a drip as being sent, as [`FaucetController::drip`] does once it reaches the node.
+    #[cfg(test)]
+    pub fn set_send_disabled(&self, disabled: bool) {
+        self.faucet.send_disabled.set(disabled);
+    }
+
     pub fn is_send_disabled(&self) -> bool {
-        self.faucet.send_disabled.get()
+        self.faucet.send_disabled.get() || !self.faucet.config.is_loaded()
     }
 
     pub fn get_send_rate_limit_remaining(&self) -> i32 {
-        self.faucet.send_limited.get()
+        self.faucet.send_limited.get().map_or(0, |deadline| {
+            seconds_until(deadline, chrono::Utc::now()) as i32
+        })
     }
 
-    #[allow(dead_code)]
-    pub fn set_send_rate_limit_remaining(&self, remaining: i32) {
-        self.faucet.send_limited.set(remaining);
+    /// When the next drip is allowed, for [`crate::components::Countdown`], which clears it once
+    /// it's reached.
+    pub fn send_rate_limit(&self) -> RwSignal<Option<chrono::DateTime<chrono::Utc>>> {
+        self.faucet.send_limited
     }
 
-    pub fn drip(&self) {
+    /// Drips to the target addresses. A dry run only simulates the drips and reports what would
+    /// happen, without signing or sending anything.
+    pub fn drip(&self, dry_run: bool) {
+        if dry_run {
+            return self.simulate_drip();
+        }
+        self.faucet.simulation.set(None);
+        if self.faucet.tabs.peer_dripping() {
+            self.add_error_message("A drip is already in progress in another tab".to_string());
+            return;
+        }
+        let Some(in_flight) = self.faucet.in_flight.try_start() else {
+            log::warn!("Ignoring drip request, a previous one is still in flight");
+            return;
+        };
+        let announcement = self.faucet.tabs.start_drip();
         let is_mainnet = self.faucet.network == Network::Mainnet;
         let faucet = self.faucet.clone();
-        match parse_address(&self.faucet.target_address.get(), self.faucet.network) {
-            Ok(addr) => {
+        let rpc = self.rpc.clone();
+        let max_recipients = self.faucet.config.get_untracked().max_batch_recipients;
+        let amount = self.get_amount();
+        match parse_addresses(&self.faucet.target_address.get(), self.faucet.network) {
+            Ok(addrs) if addrs.len() > max_recipients => {
+                self.add_error_message(format!(
+                    "Too many recipients - at most {max_recipients} per request"
+                ));
+            }
+            Ok(addrs) if addrs.len() > 1 => self.drip_batch(addrs, in_flight, announcement),
+            Ok(addrs) => {
+                let addr = addrs[0];
                 spawn_local(async move {
                     catch_all(faucet.error_messages, async move {
-                        let rpc = Provider::from_network(faucet.network);
-                        let LotusJson(from) = faucet_address(is_mainnet)
-                            .await
-                            .map_err(|e| anyhow::anyhow!("Error getting faucet address: {}", e))?;
+                        let signer = faucet.signer.get_untracked();
+                        let from = sender_address(signer, is_mainnet).await?;
+                        ensure_not_self_send(&rpc, &[addr], from).await?;
                         faucet.send_disabled.set(true);
-                        let nonce = rpc.mpool_get_nonce(from).await?;
-                        let mut msg = message_transfer(
-                            from,
-                            addr,
-                            if is_mainnet {
-                                crate::constants::MAINNET_DRIP_AMOUNT.clone()
-                            } else {
-                                crate::constants::CALIBNET_DRIP_AMOUNT.clone()
-                            },
-                        );
-                        msg.sequence = nonce;
-                        let msg = rpc.estimate_gas(msg).await?;
-                        match sign_with_secret_key(LotusJson(msg.clone()), is_mainnet).await {
-                            Ok(LotusJson(smsg)) => {
-                                let cid = rpc.mpool_push(smsg).await?;
-                                faucet.sent_messages.update(|messages| {
-                                    messages.push((cid, false));
-                                });
-                                log::info!("Sent message: {:?}", cid);
-                            }
-                            Err(e) => {
-                                log::error!("Failed to sign message: {}", e);
-                                faucet
-                                    .send_limited
-                                    .set(crate::constants::RATE_LIMIT_SECONDS as i32);
+                        let config = faucet.config.get_untracked();
+                        let mut attempt = 0;
+                        loop {
+                            let nonce = rpc.next_nonce(from).await?;
+                            let tsk = head_tipset_key(&rpc).await;
+                            let msg =
+                                build_drips(&rpc, from, &[addr], &amount, &config, nonce, tsk)
+                                    .await?
+                                    .remove(0);
+                            let smsg = match signer {
+                                Signer::Faucet => {
+                                    ensure_gas_fee_cap(&config, faucet.network, &msg)?;
+                                    match sign_with_secret_key(LotusJson(msg.clone()), is_mainnet)
+                                        .await
+                                    {
+                                        Ok(LotusJson(smsg)) => {
+                                            faucet.last_signed.set(Some(smsg.clone()));
+                                            smsg
+                                        }
+                                        Err(e) => {
+                                            return handle_sign_error(
+                                                faucet.send_limited,
+                                                &config,
+                                                e,
+                                            )
+                                        }
+                                    }
+                                }
+                                Signer::External => sign_external(msg).await?,
+                            };
+                            let sender = (smsg.message.from, smsg.message.sequence);
+                            match rpc.mpool_push(smsg).await {
+                                Ok(cid) => {
+                                    faucet.sent_messages.update(|messages| {
+                                        let sent = SentMessage::new(cid, amount.clone())
+                                            .with_sender(sender.0, sender.1);
+                                        merge_sent_messages(messages, [sent]);
+                                    });
+                                    faucet.tabs.post(TabMessage::Sent {
+                                        cid,
+                                        amount: amount.clone(),
+                                        sender: Some(sender),
+                                    });
+                                    log::info!("Sent message: {:?}", cid);
+                                    return Ok(());
+                                }
+                                Err(e)
+                                    if attempt < NONCE_RETRIES
+                                        && e.downcast_ref::<RpcError>()
+                                            .is_some_and(RpcError::is_nonce_too_low) =>
+                                {
+                                    log::warn!("Retrying with a fresh nonce: {}", e);
+                                    attempt += 1;
+                                }
+                                Err(e) => return Err(e),
                             }
                         }
-                        Ok(())
                     })
                     .await;
                     faucet.send_disabled.set(false);
+                    drop(in_flight);
+                    drop(announcement);
                 });
             }
             Err(e) => {
-                self.add_error_message(format!(
-                    "Invalid address: {}",
-                    &self.faucet.target_address.get()
-                ));
+                let target = self.faucet.target_address.get();
+                let hint = wrong_network(&target, self.faucet.network)
+                    .map(|other| {
+                        let name = network_name(other);
+                        format!(" - this looks like a {name} address; switch to the {name} faucet?")
+                    })
+                    .unwrap_or_default();
+                self.add_error_message(format!("Invalid address: {target}{hint}"));
                 log::error!("Error parsing address: {}", e);
             }
         }
     }
+
+    /// Builds the drips to the target addresses like [`FaucetController::drip`] does and simulates
+    /// them on the current head.
+    fn simulate_drip(&self) {
+        let is_mainnet = self.faucet.network == Network::Mainnet;
+        let faucet = self.faucet.clone();
+        let amount = self.get_amount();
+        let target = self.faucet.target_address.get();
+        let addrs = match parse_addresses(&target, self.faucet.network) {
+            Ok(addrs) => addrs,
+            Err(e) => {
+                self.add_error_message(format!("Invalid address: {target}"));
+                log::error!("Error parsing address: {}", e);
+                return;
+            }
+        };
+        faucet.simulation.set(None);
+        let rpc = self.rpc.clone();
+        spawn_local(async move {
+            catch_all(faucet.error_messages, async move {
+                let from = sender_address(faucet.signer.get_untracked(), is_mainnet).await?;
+                let config = faucet.config.get_untracked();
+                let nonce = rpc.next_nonce(from).await?;
+                let tsk = head_tipset_key(&rpc).await;
+                let msgs =
+                    build_drips(&rpc, from, &addrs, &amount, &config, nonce, tsk.clone()).await?;
+                faucet
+                    .simulation
+                    .set(Some(simulate_drips(&rpc, &msgs, tsk).await?));
+                Ok(())
+            })
+            .await;
+        });
+    }
+
+    /// Drips to each of `addrs`, signing all messages in a single request.
+    fn drip_batch(
+        &self,
+        addrs: Vec<Address>,
+        in_flight: InFlightGuard,
+        announcement: DripAnnouncement,
+    ) {
+        let is_mainnet = self.faucet.network == Network::Mainnet;
+        let amount = self.get_amount();
+        let faucet = self.faucet.clone();
+        let rpc = self.rpc.clone();
+        spawn_local(async move {
+            catch_all(faucet.error_messages, async move {
+                let signer = faucet.signer.get_untracked();
+                let from = sender_address(signer, is_mainnet).await?;
+                ensure_not_self_send(&rpc, &addrs, from).await?;
+                faucet.send_disabled.set(true);
+                let config = faucet.config.get_untracked();
+                let nonce = rpc.next_nonce(from).await?;
+                let tsk = head_tipset_key(&rpc).await;
+                let msgs = build_drips(&rpc, from, &addrs, &amount, &config, nonce, tsk).await?;
+                let smsgs = match signer {
+                    Signer::Faucet => {
+                        for msg in &msgs {
+                            ensure_gas_fee_cap(&config, faucet.network, msg)?;
+                        }
+                        let msgs = msgs.into_iter().map(LotusJson).collect();
+                        match sign_batch_with_secret_key(msgs, is_mainnet).await {
+                            Ok(smsgs) => {
+                                let smsgs: Vec<_> =
+                                    smsgs.into_iter().map(LotusJson::into_inner).collect();
+                                faucet.last_signed.set(smsgs.last().cloned());
+                                smsgs
+                            }
+                            Err(e) => return handle_sign_error(faucet.send_limited, &config, e),
+                        }
+                    }
+                    Signer::External => {
+                        let mut smsgs = Vec::with_capacity(msgs.len());
+                        for msg in msgs {
+                            smsgs.push(sign_external(msg).await?);
+                        }
+                        smsgs
+                    }
+                };
+                for smsg in smsgs {
+                    let sender = (smsg.message.from, smsg.message.sequence);
+                    let cid = rpc.mpool_push(smsg).await?;
+                    faucet.sent_messages.update(|messages| {
+                        let sent =
+                            SentMessage::new(cid, amount.clone()).with_sender(sender.0, sender.1);
+                        merge_sent_messages(messages, [sent]);
+                    });
+                    faucet.tabs.post(TabMessage::Sent {
+                        cid,
+                        amount: amount.clone(),
+                        sender: Some(sender),
+                    });
+                    log::info!("Sent message: {:?}", cid);
+                }
+                Ok(())
+            })
+            .await;
+            faucet.send_disabled.set(false);
+            drop(in_flight);
+            drop(announcement);
+        });
+    }
+}
+
+#[cfg(test)]
+impl FaucetController<crate::rpc_context::mock::MockProvider> {
+    /// A controller on `rpc` with the default config, outside of any view.
+    pub fn mocked(network: Network, rpc: crate::rpc_context::mock::MockProvider) -> Self {
+        crate::utils::init_test_executor();
+        let owner = Owner::new();
+        owner.set();
+        ConfigContext::provide_loaded(Config::default());
+        // Signals are disposed of with their owner, so it's kept for the rest of the test.
+        std::mem::forget(owner);
+        Self::with_provider(network, rpc)
+    }
+}
+
+/// Starts the countdown if the faucet refused to sign because of rate limiting, and reports any
+/// other failure.
+fn handle_sign_error(
+    send_limited: RwSignal<Option<chrono::DateTime<chrono::Utc>>>,
+    config: &Config,
+    e: ServerFnError<FaucetError>,
+) -> anyhow::Result<()> {
+    log::error!("Failed to sign message: {}", e);
+    if let Some(seconds) = rate_limit_countdown(&e, config.rate_limit_seconds) {
+        send_limited.set(Some(
+            chrono::Utc::now() + chrono::Duration::seconds(seconds),
+        ));
+        return Ok(());
+    }
+    match e {
+        ServerFnError::WrappedServerError(e) => Err(anyhow::anyhow!(e.message)),
+        e => Err(anyhow::anyhow!("Failed to sign message: {}", e)),
+    }
+}
+
+/// Seconds until the faucet signs again if `e` is a rate limit error. Servers that don't report
+/// the remaining time block for the full `rate_limit_seconds`.
+fn rate_limit_countdown(e: &ServerFnError<FaucetError>, rate_limit_seconds: i64) -> Option<i64> {
+    match e {
+        ServerFnError::WrappedServerError(FaucetError {
+            code: FaucetErrorCode::RateLimited,
+            retry_after,
+            ..
+        }) => Some(retry_after.unwrap_or(rate_limit_seconds)),
+        _ => None,
+    }
+}
+
+/// Refuses to ask the faucet to sign `msg` if the server would reject its gas fee cap anyway.
+fn ensure_gas_fee_cap(config: &Config, network: Network, msg: &Message) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        !config.exceeds_gas_fee_cap(network, msg),
+        "Network fees are too high right now - try again later"
+    );
+    Ok(())
+}
+
+/// The address drips are sent from with the given signer.
+async fn sender_address(signer: Signer, is_mainnet: bool) -> anyhow::Result<Address> {
+    match signer {
+        Signer::Faucet => Ok(faucet_address(is_mainnet)
+            .await
+            .map_err(|e| anyhow::anyhow!("Error getting faucet address: {}", e))?
+            .0),
+        Signer::External => external_address().await,
+    }
+}
+
+/// Refuses drips back to the sender, which would only burn gas. Addresses are resolved to their
+/// IDs, so aliases of the sender are caught as well.
+async fn ensure_not_self_send(
+    rpc: &impl RpcProvider,
+    targets: &[Address],
+    sender: Address,
+) -> anyhow::Result<()> {
+    let sender_id = lookup_id(&sender, rpc).await;
+    for target in targets {
+        anyhow::ensure!(
+            !refers_to_actor(target, &sender, sender_id.as_ref(), rpc).await,
+            "Can't send funds to the faucet's own address"
+        );
+    }
+    Ok(())
+}
+
+/// Key of the current head, to pin gas estimates to a known tipset so they don't shift if the
+/// head moves mid-request. `None` estimates against whatever the node's head is.
+async fn head_tipset_key(rpc: &impl RpcProvider) -> Option<TipSetKey> {
+    rpc.chain_head()
+        .await
+        .inspect_err(|e| log::warn!("Estimating gas against the node's head: {}", e))
+        .ok()
+        .map(|head| head.key)
+}
+
+/// Unsigned drips of `amount` from `from` to each of `addrs`, with consecutive nonces starting
+/// at `nonce` and gas estimated against `tsk`.
+async fn build_drips(
+    rpc: &impl RpcProvider,
+    from: Address,
+    addrs: &[Address],
+    amount: &TokenAmount,
+    config: &Config,
+    nonce: u64,
+    tsk: Option<TipSetKey>,
+) -> anyhow::Result<Vec<Message>> {
+    let mut msgs = Vec::with_capacity(addrs.len());
+    for (i, addr) in addrs.iter().enumerate() {
+        let msg = MessageBuilder::default()
+            .from(from)
+            .to(*addr)
+            .value(amount.clone())
+            .params(config.drip_params())
+            .nonce(nonce + i as u64)
+            .build()?;
+        let msg = rpc.estimate_gas(msg, tsk.clone()).await?;
+        ensure_valid_estimate(&msg)?;
+        msgs.push(msg);
+    }
+    Ok(msgs)
+}
+
+/// Predicts the outcome of each of `msgs` by simulating it on top of the tipset `tsk`.
+async fn simulate_drips(
+    rpc: &impl RpcProvider,
+    msgs: &[Message],
+    tsk: Option<TipSetKey>,
+) -> anyhow::Result<Vec<SimulatedDrip>> {
+    let mut simulated = Vec::with_capacity(msgs.len());
+    for msg in msgs {
+        let result = rpc.state_call(msg.clone(), tsk.clone()).await?;
+        simulated.push(SimulatedDrip::new(msg, &result));
+    }
+    Ok(simulated)
+}
+
+/// Parses an exact amount of attoFIL, which must be one of the `allowed` amounts.
+fn parse_atto_amount(raw: &str, allowed: &[TokenAmount]) -> anyhow::Result<TokenAmount> {
+    let raw = raw.trim();
+    anyhow::ensure!(
+        !raw.is_empty() && raw.bytes().all(|b| b.is_ascii_digit()),
+        "Invalid amount: {raw} - enter a whole number of attoFIL"
+    );
+    let amount = TokenAmount::from_atto(raw.parse::<fvm_shared::bigint::BigInt>()?);
+    anyhow::ensure!(
+        allowed.contains(&amount),
+        "This faucet doesn't drip {raw} attoFIL - allowed amounts are {}",
+        allowed
+            .iter()
+            .map(|amount| amount.atto().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    Ok(amount)
+}
+
+/// Refuses to sign messages whose gas estimate is obviously broken, e.g. because estimation
+/// failed silently on the node. They'd only fail on chain after using up the rate limit.
+fn ensure_valid_estimate(msg: &Message) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        msg.gas_limit > 0 && msg.gas_fee_cap.is_positive(),
+        "The node returned an invalid gas estimate - try again later"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_context::mock::{MockProvider, CID};
+    use crate::utils::run_until_stalled;
+    use cid::Cid;
+    use futures::executor::block_on;
+    use leptos::server_fn::error::ServerFnErrorSerde as _;
+    use std::str::FromStr as _;
+
+    #[test]
+    fn test_build_drips() {
+        let tsk = TipSetKey(vec![Cid::from_str(CID).unwrap()]);
+        let rpc = MockProvider {
+            head: Some(tsk.clone()),
+            ..Default::default()
+        };
+        let from = Address::new_id(1);
+        let addrs = [Address::new_id(2), Address::new_id(3)];
+        let amount = TokenAmount::from_whole(5);
+        let nonce = 7;
+        let msgs = block_on(async {
+            let head = head_tipset_key(&rpc).await;
+            build_drips(&rpc, from, &addrs, &amount, &Config::default(), nonce, head).await
+        })
+        .unwrap();
+        assert_eq!(msgs.len(), 2);
+        for (i, msg) in msgs.iter().enumerate() {
+            assert_eq!(msg.from, from);
+            assert_eq!(msg.to, addrs[i]);
+            assert_eq!(msg.value, amount);
+            assert_eq!(msg.sequence, 7 + i as u64);
+            assert_eq!(msg.gas_limit, 1_000_000);
+        }
+        assert_eq!(
+            *rpc.estimated_at.lock().unwrap(),
+            vec![Some(tsk.clone()), Some(tsk)]
+        );
+
+        // Without a head, gas is estimated against whatever the node's head is.
+        let rpc = MockProvider::default();
+        assert_eq!(block_on(head_tipset_key(&rpc)), None);
+    }
+
+    #[test]
+    fn test_simulate_drips() {
+        let rpc = MockProvider::default();
+        let from = Address::new_id(1);
+        let addrs = [Address::new_id(2), Address::new_id(0)];
+        let simulated = block_on(async {
+            let config = Config::default();
+            let amount = TokenAmount::from_whole(1);
+            let msgs = build_drips(&rpc, from, &addrs, &amount, &config, 0, None).await?;
+            simulate_drips(&rpc, &msgs, None).await
+        })
+        .unwrap();
+        assert_eq!(simulated.len(), 2);
+        assert_eq!(simulated[0].to, addrs[0]);
+        assert_eq!(simulated[0].failure, None);
+        assert_eq!(
+            simulated[0].gas.max_fee,
+            TokenAmount::from_atto(100_000_000)
+        );
+        assert_eq!(simulated[1].failure.as_deref(), Some("exit 16"));
+    }
+
+    #[test]
+    fn test_refetch_balances() {
+        let cid = Cid::from_str(CID).unwrap();
+        let rpc = MockProvider {
+            head: Some(TipSetKey(vec![cid])),
+            ..Default::default()
+        };
+        let faucet = FaucetController::mocked(Network::Testnet, rpc);
+        faucet
+            .faucet
+            .sent_messages
+            .set(vec![SentMessage::new(cid, TokenAmount::from_whole(1))]);
+        faucet.refetch_balances();
+        run_until_stalled();
+        assert_eq!(faucet.get_chain_height(), Some(100));
+        assert!(!faucet.get_sent_messages()[0].is_confirmed());
+        assert!(faucet.get_error_messages().is_empty());
+
+        // Failures to reach the node are reported.
+        let faucet = FaucetController::mocked(Network::Mainnet, MockProvider::default());
+        faucet
+            .faucet
+            .sent_messages
+            .set(vec![SentMessage::new(cid, TokenAmount::from_whole(1))]);
+        faucet.refetch_balances();
+        run_until_stalled();
+        assert_eq!(faucet.get_chain_height(), None);
+        assert_eq!(faucet.get_error_messages()[0].1, "node unreachable");
+    }
+
+    #[test]
+    fn test_parse_atto_amount() {
+        let allowed = [TokenAmount::from_atto(100), TokenAmount::from_whole(1)];
+        assert_eq!(
+            parse_atto_amount(" 100 ", &allowed).unwrap(),
+            TokenAmount::from_atto(100)
+        );
+        assert_eq!(
+            parse_atto_amount("1000000000000000000", &allowed).unwrap(),
+            TokenAmount::from_whole(1)
+        );
+        for invalid in ["", "1.5", "-100", "1e18", "0", "1000000000000000001", "99"] {
+            assert!(parse_atto_amount(invalid, &allowed).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_ensure_valid_estimate() {
+        let msg = MessageBuilder::default()
+            .from(Address::new_id(1))
+            .to(Address::new_id(2))
+            .build()
+            .unwrap();
+        assert!(ensure_valid_estimate(&msg).is_err());
+
+        let rpc = MockProvider::default();
+        let estimated = block_on(rpc.estimate_gas(msg.clone(), None)).unwrap();
+        assert!(ensure_valid_estimate(&estimated).is_ok());
+        let no_fee_cap = Message {
+            gas_fee_cap: TokenAmount::default(),
+            ..estimated.clone()
+        };
+        assert!(ensure_valid_estimate(&no_fee_cap).is_err());
+        let no_gas = Message {
+            gas_limit: 0,
+            ..estimated
+        };
+        assert!(ensure_valid_estimate(&no_gas).is_err());
+    }
+
+    #[test]
+    fn test_ensure_not_self_send() {
+        let sender = Address::new_secp256k1(&[1; 65]).unwrap();
+        let rpc = MockProvider {
+            ids: vec![(sender, Address::new_id(1234))],
+            ..Default::default()
+        };
+        let others = [Address::new_id(1), Address::new_id(2)];
+        assert!(block_on(ensure_not_self_send(&rpc, &others, sender)).is_ok());
+        let alias = [Address::new_id(1), Address::new_id(1234)];
+        assert!(block_on(ensure_not_self_send(&rpc, &alias, sender)).is_err());
+    }
+
+    #[test]
+    fn test_rate_limit_countdown() {
+        // As received from the server function.
+        let e = ServerFnError::WrappedServerError(FaucetError {
+            code: FaucetErrorCode::RateLimited,
+            message: "Rate limit exceeded - wait 42 seconds".to_string(),
+            retry_after: Some(42),
+        });
+        let e = ServerFnError::<FaucetError>::de(&e.ser().unwrap());
+        assert_eq!(rate_limit_countdown(&e, 600), Some(42));
+
+        let e = ServerFnError::WrappedServerError(FaucetError::new(
+            FaucetErrorCode::RateLimited,
+            "Rate limit exceeded",
+        ));
+        assert_eq!(rate_limit_countdown(&e, 600), Some(600));
+
+        let e = ServerFnError::WrappedServerError(FaucetError::new(
+            FaucetErrorCode::AmountExceeded,
+            "Amount not allowed",
+        ));
+        assert_eq!(rate_limit_countdown(&e, 600), None);
+        assert_eq!(
+            rate_limit_countdown(&ServerFnError::ServerError("boom".into()), 600),
+            None
+        );
+    }
 }
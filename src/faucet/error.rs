@@ -0,0 +1,146 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// Stable identifiers for the reasons a drip is refused, for programmatic consumers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaucetErrorCode {
+    RateLimited,
+    AmountExceeded,
+    TooManyRecipients,
+    FeeTooHigh,
+    /// The message isn't a plain drip, e.g. it calls a method or carries unexpected params.
+    InvalidMessage,
+    Busy,
+    Internal,
+}
+
+impl FaucetErrorCode {
+    #[cfg(feature = "ssr")]
+    fn status(self) -> axum::http::StatusCode {
+        use axum::http::StatusCode;
+        match self {
+            FaucetErrorCode::RateLimited | FaucetErrorCode::Busy => StatusCode::TOO_MANY_REQUESTS,
+            FaucetErrorCode::AmountExceeded
+            | FaucetErrorCode::TooManyRecipients
+            | FaucetErrorCode::InvalidMessage => StatusCode::BAD_REQUEST,
+            FaucetErrorCode::FeeTooHigh => StatusCode::SERVICE_UNAVAILABLE,
+            FaucetErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Error returned by the faucet server functions. Its JSON form, e.g.
+/// `{"code":"rate_limited","message":"Rate limit exceeded - wait 600 seconds","retry_after":600}`,
+/// travels after the `WrappedServerFn|` prefix of server function errors.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FaucetError {
+    pub code: FaucetErrorCode,
+    pub message: String,
+    /// Seconds after which the request may succeed, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<i64>,
+}
+
+impl FaucetError {
+    #[cfg(any(feature = "ssr", test))]
+    pub fn new(code: FaucetErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            retry_after: None,
+        }
+    }
+
+    #[cfg(feature = "ssr")]
+    pub fn internal(e: impl fmt::Display) -> Self {
+        Self::new(FaucetErrorCode::Internal, e.to_string())
+    }
+
+    #[cfg(feature = "ssr")]
+    pub fn with_retry_after(mut self, seconds: i64) -> Self {
+        self.retry_after = Some(seconds);
+        self
+    }
+
+    /// Reflects the error in the HTTP response: its status, an `X-Faucet-Error-Code` header and
+    /// `Retry-After` when known.
+    #[cfg(feature = "ssr")]
+    pub fn respond(self) -> Self {
+        use axum::http::{header, HeaderName, HeaderValue};
+        use leptos_axum::ResponseOptions;
+
+        if let Some(res) = leptos::prelude::use_context::<ResponseOptions>() {
+            res.set_status(self.code.status());
+            if let Ok(code) = serde_json::to_value(self.code) {
+                if let Some(Ok(code)) = code.as_str().map(HeaderValue::from_str) {
+                    res.insert_header(HeaderName::from_static("x-faucet-error-code"), code);
+                }
+            }
+            if let Some(seconds) = self.retry_after {
+                res.insert_header(header::RETRY_AFTER, HeaderValue::from(seconds));
+            }
+        }
+        self
+    }
+}
+
+impl fmt::Display for FaucetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(self).map_err(|_| fmt::Error)?)
+    }
+}
+
+impl FromStr for FaucetError {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_faucet_error_round_trip() {
+        let err = FaucetError {
+            code: FaucetErrorCode::RateLimited,
+            message: "Rate limit exceeded - wait 600 seconds".to_string(),
+            retry_after: Some(600),
+        };
+        let s = err.to_string();
+        assert_eq!(
+            s,
+            r#"{"code":"rate_limited","message":"Rate limit exceeded - wait 600 seconds","retry_after":600}"#
+        );
+        assert_eq!(s.parse::<FaucetError>().unwrap(), err);
+
+        let err = FaucetError::new(FaucetErrorCode::AmountExceeded, "Amount limit exceeded");
+        assert_eq!(
+            err.to_string(),
+            r#"{"code":"amount_exceeded","message":"Amount limit exceeded"}"#
+        );
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn test_error_response_body() {
+        use axum::{body::Body, http::Response};
+        use leptos::{prelude::ServerFnError, server_fn::response::Res};
+
+        let err = FaucetError::new(FaucetErrorCode::Busy, "Busy").with_retry_after(1);
+        let res = <Response<Body> as Res<FaucetError>>::error_response(
+            "/api/sign_with_secret_key",
+            &ServerFnError::WrappedServerError(err),
+        );
+        let body =
+            futures::executor::block_on(axum::body::to_bytes(res.into_body(), usize::MAX)).unwrap();
+        assert_eq!(
+            body,
+            r#"WrappedServerFn|{"code":"busy","message":"Busy","retry_after":1}"#
+        );
+    }
+}
@@ -1,4 +1,8 @@
+mod callback;
 mod controller;
+pub mod error;
 mod model;
+mod signer;
+mod tabs;
 pub mod utils;
 pub mod views;
@@ -1,18 +1,449 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use chrono::{DateTime, Utc};
 use cid::Cid;
-use fvm_shared::{address::Network, econ::TokenAmount};
+use fvm_shared::{
+    address::{Address, Network},
+    clock::ChainEpoch,
+    econ::TokenAmount,
+    error::ExitCode,
+};
 use leptos::prelude::{LocalResource, RwSignal, Trigger};
+use url::Url;
 use uuid::Uuid;
 
+use super::signer::Signer;
+use super::tabs::Tabs;
+use crate::{
+    actor::ActorKind,
+    config::ConfigContext,
+    lotus_json::{InvocResult, MessageLookup},
+    message::{EstimatedGas, Message, SignedMessage},
+    rpc_context::ResolvedAddress,
+};
+
+/// A message pushed by the faucet, during this session or found on chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SentMessage {
+    pub cid: Cid,
+    pub amount: TokenAmount,
+    pub sent_at: DateTime<Utc>,
+    /// Set once the message has been found on chain.
+    pub lookup: Option<MessageLookup>,
+    /// Sender and nonce of the message, when known. Messages with the same ones replace each
+    /// other, so at most one of them lands on chain.
+    pub sender: Option<(Address, u64)>,
+    /// Messages with the same sender and nonce that this one replaced in the list.
+    pub replaces: Vec<Cid>,
+}
+
+impl SentMessage {
+    pub fn new(cid: Cid, amount: TokenAmount) -> Self {
+        Self {
+            cid,
+            amount,
+            sent_at: Utc::now(),
+            lookup: None,
+            sender: None,
+            replaces: Vec::new(),
+        }
+    }
+
+    pub fn with_sender(mut self, from: Address, nonce: u64) -> Self {
+        self.sender = Some((from, nonce));
+        self
+    }
+
+    /// A message found on chain, already confirmed by `lookup`. It's dated back from the chain
+    /// `head` by the epochs since its tipset.
+    #[cfg(any(feature = "hydrate", test))]
+    pub fn from_chain(
+        cid: Cid,
+        amount: TokenAmount,
+        lookup: MessageLookup,
+        head: ChainEpoch,
+    ) -> Self {
+        let epochs = (head - lookup.height).max(0);
+        Self {
+            cid,
+            amount,
+            sent_at: Utc::now()
+                - chrono::Duration::seconds(epochs * crate::constants::EPOCH_DURATION_SECONDS),
+            lookup: Some(lookup),
+            sender: None,
+            replaces: Vec::new(),
+        }
+    }
+
+    pub fn is_confirmed(&self) -> bool {
+        self.lookup.is_some()
+    }
+
+    /// The exit code of a message which landed on chain but failed to execute, e.g. a reverted
+    /// transfer.
+    pub fn failed_exit_code(&self) -> Option<ExitCode> {
+        self.lookup
+            .as_ref()
+            .map(|lookup| lookup.receipt.exit_code)
+            .filter(|exit_code| !exit_code.is_success())
+    }
+
+    /// Number of epochs on top of the tipset that included the message, as of the chain `head`.
+    pub fn confirmations(&self, head: ChainEpoch) -> Option<i64> {
+        self.lookup
+            .as_ref()
+            .map(|lookup| (head - lookup.height).max(0))
+    }
+
+    /// Progress towards [`crate::constants::FINALITY_DEPTH`], from `0.0` (pending) to `1.0`
+    /// (final).
+    pub fn finality_progress(&self, head: Option<ChainEpoch>) -> f64 {
+        match head.and_then(|head| self.confirmations(head)) {
+            Some(confirmations) => {
+                (confirmations as f64 / crate::constants::FINALITY_DEPTH as f64).min(1.0)
+            }
+            None => 0.0,
+        }
+    }
+
+    pub fn is_final(&self, head: Option<ChainEpoch>) -> bool {
+        self.finality_progress(head) >= 1.0
+    }
+
+    /// Whether the message is pending or confirmed, with the number of confirmations as of the
+    /// chain `head` if `show_confirmations` is set.
+    pub fn status_label(&self, head: Option<ChainEpoch>, show_confirmations: bool) -> String {
+        match head.and_then(|head| self.confirmations(head)) {
+            Some(confirmations) if show_confirmations => {
+                format!("(confirmed, {confirmations} confs)")
+            }
+            _ if self.is_confirmed() => "(confirmed)".to_string(),
+            _ => "(pending)".to_string(),
+        }
+    }
+}
+
+/// Adds the `new` messages which aren't in `messages` yet, keeping them ordered by the time they
+/// were sent. Of two messages with the same sender and nonce, only the one on chain, or else the
+/// newer one, is kept, and it records the other as replaced.
+pub fn merge_sent_messages(
+    messages: &mut Vec<SentMessage>,
+    new: impl IntoIterator<Item = SentMessage>,
+) {
+    for mut msg in new {
+        if messages
+            .iter()
+            .any(|known| known.cid == msg.cid || known.replaces.contains(&msg.cid))
+        {
+            continue;
+        }
+        let same_nonce = msg.sender.and_then(|sender| {
+            messages
+                .iter_mut()
+                .find(|known| known.sender == Some(sender))
+        });
+        match same_nonce {
+            Some(known)
+                if known.is_confirmed() || (!msg.is_confirmed() && known.sent_at > msg.sent_at) =>
+            {
+                known.replaces.push(msg.cid);
+                known.replaces.append(&mut msg.replaces);
+            }
+            Some(known) => {
+                msg.replaces.push(known.cid);
+                msg.replaces.append(&mut known.replaces);
+                *known = msg;
+            }
+            None => messages.push(msg),
+        }
+    }
+    messages.sort_by_key(|msg| msg.sent_at);
+}
+
+/// What a drip would do, as predicted by a dry run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulatedDrip {
+    pub to: Address,
+    /// The estimated gas settings, and the most the drip could cost in gas.
+    pub gas: EstimatedGas,
+    /// Why the drip would fail, or `None` if it would succeed.
+    pub failure: Option<String>,
+}
+
+impl SimulatedDrip {
+    /// The prediction for `msg`, given the result of simulating it with `Filecoin.StateCall`.
+    pub fn new(msg: &Message, result: &InvocResult) -> Self {
+        let failure = if !result.error.is_empty() {
+            Some(result.error.clone())
+        } else {
+            match result.exit_code() {
+                Some(exit_code) if exit_code.is_success() => None,
+                Some(exit_code) => Some(format!("exit {}", exit_code.value())),
+                None => Some("message couldn't be applied".to_string()),
+            }
+        };
+        Self {
+            to: msg.to,
+            gas: EstimatedGas::from(msg),
+            failure,
+        }
+    }
+}
+
+/// Tracks whether a drip is running. Unlike `send_disabled`, it's updated synchronously, so a
+/// double-click can't start a second drip before the view catches up.
+#[derive(Clone, Default)]
+pub struct InFlight(Arc<AtomicBool>);
+
+impl InFlight {
+    /// Marks a drip as running, or returns `None` if one already is. The drip is over once the
+    /// returned guard is dropped.
+    pub fn try_start(&self) -> Option<InFlightGuard> {
+        self.0
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| InFlightGuard(self.0.clone()))
+    }
+}
+
+pub struct InFlightGuard(Arc<AtomicBool>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
 #[derive(Clone)]
 pub(super) struct FaucetModel {
     pub network: Network,
     pub send_disabled: RwSignal<bool>,
-    pub send_limited: RwSignal<i32>,
-    pub sent_messages: RwSignal<Vec<(Cid, bool)>>,
+    pub in_flight: InFlight,
+    /// Coordination with faucet pages open in other tabs.
+    pub tabs: Tabs,
+    /// When the next drip is allowed, while the faucet is rate limited.
+    pub send_limited: RwSignal<Option<DateTime<Utc>>>,
+    pub sent_messages: RwSignal<Vec<SentMessage>>,
     pub error_messages: RwSignal<Vec<(Uuid, String)>>,
     pub balance_trigger: Trigger,
     pub faucet_balance: LocalResource<TokenAmount>,
     pub target_balance: LocalResource<TokenAmount>,
+    /// The actor behind an Ethereum-style target address, if there is one.
+    pub target_actor: LocalResource<Option<ActorKind>>,
+    /// Other forms of the target address, e.g. the key address behind an ID address.
+    pub target_resolved: LocalResource<Option<ResolvedAddress>>,
     pub sender_address: RwSignal<String>,
     pub target_address: RwSignal<String>,
+    pub chain_height: RwSignal<Option<ChainEpoch>>,
+    /// Results of the last dry run, if any.
+    pub simulation: RwSignal<Option<Vec<SimulatedDrip>>>,
+    /// Whether confirmed drips show their number of confirmations. On by default on mainnet.
+    pub show_confirmations: RwSignal<bool>,
+    pub config: ConfigContext,
+    pub signer: RwSignal<Signer>,
+    /// Whether the amount is entered as an exact number of attoFIL.
+    pub amount_in_atto: RwSignal<bool>,
+    /// The amount picked by the user, if they picked one of the allowed amounts.
+    pub amount: RwSignal<Option<TokenAmount>>,
+    /// Where to send the user once a drip is confirmed, as requested by the embedding page.
+    pub callback: RwSignal<Option<Url>>,
+    /// The last message signed by the faucet, for the signature check in debug builds.
+    pub last_signed: RwSignal<Option<SignedMessage>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A successful lookup of a message in the tipset at `height`.
+    fn confirmed_lookup(height: ChainEpoch) -> MessageLookup {
+        MessageLookup {
+            height,
+            message: Cid::default(),
+            receipt: fvm_shared::receipt::Receipt {
+                exit_code: ExitCode::OK,
+                return_data: Default::default(),
+                gas_used: 0,
+                events_root: None,
+            },
+            tipset: Default::default(),
+        }
+    }
+
+    /// A CID distinct for each `data`.
+    fn test_cid(data: &[u8]) -> Cid {
+        use multihash_codetable::{Code, MultihashDigest as _};
+        Cid::new_v1(0x55, Code::Blake2b256.digest(data))
+    }
+
+    #[test]
+    fn test_finality_progress() {
+        let mut msg = SentMessage::new(Cid::default(), TokenAmount::from_whole(1));
+        assert_eq!(msg.finality_progress(Some(100)), 0.0);
+
+        msg.lookup = Some(confirmed_lookup(100));
+        assert_eq!(msg.finality_progress(None), 0.0);
+        assert_eq!(msg.finality_progress(Some(100)), 0.0);
+        assert_eq!(msg.finality_progress(Some(550)), 0.5);
+        assert!(!msg.is_final(Some(550)));
+        assert_eq!(msg.finality_progress(Some(2000)), 1.0);
+        assert!(msg.is_final(Some(2000)));
+    }
+
+    #[test]
+    fn test_merge_sent_messages() {
+        let amount = TokenAmount::from_whole(1);
+        let mut messages = vec![SentMessage::new(test_cid(b"session"), amount.clone())];
+        merge_sent_messages(
+            &mut messages,
+            [
+                SentMessage::from_chain(
+                    test_cid(b"session"),
+                    amount.clone(),
+                    confirmed_lookup(100),
+                    100,
+                ),
+                SentMessage::from_chain(
+                    test_cid(b"newer"),
+                    amount.clone(),
+                    confirmed_lookup(90),
+                    100,
+                ),
+                SentMessage::from_chain(
+                    test_cid(b"older"),
+                    amount.clone(),
+                    confirmed_lookup(80),
+                    100,
+                ),
+            ],
+        );
+        let cids: Vec<_> = messages.iter().map(|msg| msg.cid).collect();
+        assert_eq!(
+            cids,
+            vec![test_cid(b"older"), test_cid(b"newer"), test_cid(b"session")]
+        );
+        // The session's own copy is kept.
+        assert!(!messages[2].is_confirmed());
+        assert!(messages[0].sent_at < messages[1].sent_at);
+    }
+
+    #[test]
+    fn test_merge_replaced_messages() {
+        let from = Address::new_id(1);
+        let amount = TokenAmount::from_whole(1);
+        let mut messages = vec![
+            SentMessage::new(test_cid(b"first"), amount.clone()).with_sender(from, 5),
+            SentMessage::new(test_cid(b"other"), amount.clone()).with_sender(from, 6),
+        ];
+
+        // A newer message with the same nonce replaces the pending one.
+        let mut retry = SentMessage::new(test_cid(b"retry"), amount.clone()).with_sender(from, 5);
+        retry.sent_at += chrono::Duration::seconds(1);
+        merge_sent_messages(&mut messages, [retry]);
+        let cids: Vec<_> = messages.iter().map(|msg| msg.cid).collect();
+        assert_eq!(cids, vec![test_cid(b"other"), test_cid(b"retry")]);
+        assert_eq!(messages[1].replaces, vec![test_cid(b"first")]);
+
+        // Replaced messages aren't added back.
+        merge_sent_messages(
+            &mut messages,
+            [SentMessage::new(test_cid(b"first"), amount.clone()).with_sender(from, 5)],
+        );
+        assert_eq!(messages.len(), 2);
+
+        // Messages on chain win over newer pending ones.
+        messages[0].lookup = Some(confirmed_lookup(100));
+        merge_sent_messages(
+            &mut messages,
+            [SentMessage::new(test_cid(b"late"), amount).with_sender(from, 6)],
+        );
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].cid, test_cid(b"other"));
+        assert_eq!(messages[0].replaces, vec![test_cid(b"late")]);
+    }
+
+    #[test]
+    fn test_status_label() {
+        let mut msg = SentMessage::new(Cid::default(), TokenAmount::from_whole(1));
+        assert_eq!(msg.status_label(Some(100), true), "(pending)");
+
+        msg.lookup = Some(confirmed_lookup(100));
+        assert_eq!(msg.status_label(Some(105), true), "(confirmed, 5 confs)");
+        assert_eq!(msg.status_label(Some(105), false), "(confirmed)");
+        assert_eq!(msg.status_label(None, true), "(confirmed)");
+    }
+
+    #[test]
+    fn test_failed_exit_code() {
+        let mut msg = SentMessage::new(Cid::default(), TokenAmount::from_whole(1));
+        assert_eq!(msg.failed_exit_code(), None);
+
+        let lookup = |exit_code: u32| {
+            serde_json::from_value::<MessageLookup>(serde_json::json!({
+                "Message": { "/": "baeaaaaa" },
+                "Receipt": { "ExitCode": exit_code, "Return": null, "GasUsed": 488_500, "EventsRoot": null },
+                "ReturnDec": null,
+                "TipSet": [{ "/": "baeaaaaa" }],
+                "Height": 100
+            }))
+            .unwrap()
+        };
+        msg.lookup = Some(lookup(0));
+        assert_eq!(msg.failed_exit_code(), None);
+        msg.lookup = Some(lookup(33));
+        assert_eq!(msg.failed_exit_code(), Some(ExitCode::new(33)));
+    }
+
+    #[test]
+    fn test_simulated_drip() {
+        let msg = crate::message::MessageBuilder::default()
+            .from(Address::new_id(1))
+            .to(Address::new_id(1234))
+            .gas_limit(1_000_000)
+            .gas_fee_cap(TokenAmount::from_atto(100))
+            .build()
+            .unwrap();
+        let result = |json| serde_json::from_value::<InvocResult>(json).unwrap();
+        let ok = SimulatedDrip::new(
+            &msg,
+            &result(serde_json::json!({
+                "MsgCid": { "/": "baeaaaaa" },
+                "MsgRct": { "ExitCode": 0, "Return": null, "GasUsed": 488_500, "EventsRoot": null },
+            })),
+        );
+        assert_eq!(ok.to, Address::new_id(1234));
+        assert_eq!(ok.gas.max_fee, TokenAmount::from_atto(100_000_000));
+        assert_eq!(ok.failure, None);
+
+        let reverted = SimulatedDrip::new(
+            &msg,
+            &result(serde_json::json!({
+                "MsgCid": { "/": "baeaaaaa" },
+                "MsgRct": { "ExitCode": 6, "Return": null, "GasUsed": 488_500, "EventsRoot": null },
+            })),
+        );
+        assert_eq!(reverted.failure.as_deref(), Some("exit 6"));
+
+        let unapplied = SimulatedDrip::new(
+            &msg,
+            &result(serde_json::json!({
+                "MsgCid": { "/": "baeaaaaa" },
+                "Error": "insufficient funds",
+            })),
+        );
+        assert_eq!(unapplied.failure.as_deref(), Some("insufficient funds"));
+    }
+
+    #[test]
+    fn test_in_flight_rejects_concurrent_drips() {
+        let in_flight = InFlight::default();
+        let first = in_flight.try_start();
+        assert!(first.is_some());
+        assert!(in_flight.clone().try_start().is_none());
+        drop(first);
+        assert!(in_flight.try_start().is_some());
+    }
 }
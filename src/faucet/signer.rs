@@ -0,0 +1,79 @@
+use anyhow::{anyhow, Context as _};
+use fvm_shared::{address::Address, message::Message};
+use wasm_bindgen::prelude::*;
+
+use crate::{lotus_json::LotusJson, message::SignedMessage};
+
+/// Who signs the messages sent from the faucet page.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Signer {
+    /// The faucet's own key, held by the server and subject to rate limiting.
+    #[default]
+    Faucet,
+    /// The user's wallet, injected into the page as `window.filecoinSigner`. Funds are sent from
+    /// the wallet's address rather than the faucet's.
+    External,
+}
+
+impl Signer {
+    /// Whether this deployment offers signing with an external wallet.
+    pub fn external_enabled() -> bool {
+        option_env!("FAUCET_EXTERNAL_SIGNING").is_some()
+    }
+}
+
+// The external signer interface. Messages and signed messages are passed as lotus JSON objects:
+//
+// ```js
+// window.filecoinSigner = {
+//   getAddress: async () => "t1...",
+//   signMessage: async (message) => ({ Message: message, Signature: { Type: 1, Data: "..." } }),
+// };
+// ```
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = filecoinSigner, js_name = getAddress)]
+    async fn get_address() -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = filecoinSigner, js_name = signMessage)]
+    async fn sign_message(message: JsValue) -> Result<JsValue, JsValue>;
+}
+
+fn js_error(e: JsValue) -> anyhow::Error {
+    anyhow!(
+        "External signer failed: {}",
+        e.as_string()
+            .or_else(|| js_sys::JSON::stringify(&e).ok().map(String::from))
+            .unwrap_or_default()
+    )
+}
+
+fn to_js<T: serde::Serialize>(value: &T) -> anyhow::Result<JsValue> {
+    js_sys::JSON::parse(&serde_json::to_string(value)?).map_err(js_error)
+}
+
+fn from_js<T: serde::de::DeserializeOwned>(value: &JsValue) -> anyhow::Result<T> {
+    let json = js_sys::JSON::stringify(value).map_err(js_error)?;
+    Ok(serde_json::from_str(&String::from(json))?)
+}
+
+/// The address of the external wallet.
+pub async fn external_address() -> anyhow::Result<Address> {
+    let LotusJson(address) = from_js(&get_address().await.map_err(js_error)?)
+        .context("External signer returned an invalid address")?;
+    Ok(address)
+}
+
+/// Hands `msg` over to the external wallet for signing.
+pub async fn sign_external(msg: Message) -> anyhow::Result<SignedMessage> {
+    let signed = sign_message(to_js(&LotusJson(msg.clone()))?)
+        .await
+        .map_err(js_error)?;
+    let LotusJson(smsg): LotusJson<SignedMessage> =
+        from_js(&signed).context("External signer returned an invalid signed message")?;
+    anyhow::ensure!(
+        smsg.message == msg,
+        "External signer modified the message before signing"
+    );
+    Ok(smsg)
+}
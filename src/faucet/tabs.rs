@@ -0,0 +1,269 @@
+//! Coordination between faucet pages open in several tabs of the same browser. Tabs talk over a
+//! `BroadcastChannel` per network: they share the drips they send and their confirmations, refuse
+//! to drip while another tab is dripping, and only the tab with the lowest id polls the node.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+use cid::Cid;
+use fvm_shared::{
+    address::{Address, Network},
+    clock::ChainEpoch,
+    econ::TokenAmount,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::lotus_json::MessageLookup;
+
+/// Tabs that haven't sent a heartbeat for this long are considered closed.
+const HEARTBEAT_TIMEOUT: Duration = Duration::seconds(12);
+/// A drip announced by a tab that never finished it, e.g. because the tab was closed, stops
+/// blocking other tabs after this long.
+const DRIP_TIMEOUT: Duration = Duration::seconds(60);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TabMessage {
+    /// Sent by every tab on each poll, so tabs know who else is open.
+    Heartbeat,
+    DripStarted,
+    DripFinished,
+    Sent {
+        #[serde(with = "crate::lotus_json")]
+        cid: Cid,
+        #[serde(with = "crate::lotus_json")]
+        amount: TokenAmount,
+        /// Sender and nonce of the message, see [`super::model::SentMessage::sender`].
+        #[serde(with = "crate::lotus_json", default)]
+        sender: Option<(Address, u64)>,
+    },
+    Confirmed(MessageLookup),
+    Head(ChainEpoch),
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    from: Uuid,
+    message: TabMessage,
+}
+
+/// What a tab knows about the other tabs.
+#[derive(Default)]
+struct Peers {
+    /// When each tab was last heard from.
+    seen: HashMap<Uuid, DateTime<Utc>>,
+    /// When each tab started the drip it's running.
+    dripping: HashMap<Uuid, DateTime<Utc>>,
+}
+
+impl Peers {
+    #[cfg(any(feature = "hydrate", test))]
+    fn receive(&mut self, from: Uuid, message: &TabMessage, now: DateTime<Utc>) {
+        self.seen.insert(from, now);
+        match message {
+            TabMessage::DripStarted => {
+                self.dripping.insert(from, now);
+            }
+            TabMessage::DripFinished => {
+                self.dripping.remove(&from);
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether `me` has the lowest id of the open tabs.
+    fn is_leader(&self, me: Uuid, now: DateTime<Utc>) -> bool {
+        !self
+            .seen
+            .iter()
+            .any(|(id, seen)| *id < me && now - *seen < HEARTBEAT_TIMEOUT)
+    }
+
+    fn is_dripping(&self, now: DateTime<Utc>) -> bool {
+        self.dripping
+            .values()
+            .any(|started| now - *started < DRIP_TIMEOUT)
+    }
+}
+
+#[derive(Clone)]
+pub struct Tabs {
+    id: Uuid,
+    peers: Arc<Mutex<Peers>>,
+    #[cfg(feature = "hydrate")]
+    channel: send_wrapper::SendWrapper<Option<std::rc::Rc<hydrate::Channel>>>,
+}
+
+impl Tabs {
+    /// Joins the channel of `network` until the current reactive owner is cleaned up.
+    /// `on_message` is called with the messages of other tabs.
+    #[cfg_attr(not(feature = "hydrate"), allow(unused_variables))]
+    pub fn new(network: Network, on_message: impl Fn(TabMessage) + 'static) -> Self {
+        let id = Uuid::new_v4();
+        let peers = Arc::new(Mutex::new(Peers::default()));
+        #[cfg(feature = "hydrate")]
+        {
+            let channel =
+                hydrate::join(network, id, peers.clone(), on_message).map(std::rc::Rc::new);
+            if let Some(channel) = &channel {
+                let channel = send_wrapper::SendWrapper::new(channel.clone());
+                leptos::prelude::on_cleanup(move || channel.close());
+            }
+            Self {
+                id,
+                peers,
+                channel: send_wrapper::SendWrapper::new(channel),
+            }
+        }
+        #[cfg(not(feature = "hydrate"))]
+        Self { id, peers }
+    }
+
+    #[cfg_attr(not(feature = "hydrate"), allow(unused_variables))]
+    pub fn post(&self, message: TabMessage) {
+        #[cfg(feature = "hydrate")]
+        if let Some(channel) = self.channel.as_ref() {
+            let channel = &channel.channel;
+            let envelope = Envelope {
+                from: self.id,
+                message,
+            };
+            match serde_json::to_string(&envelope) {
+                Ok(json) => {
+                    if let Err(e) = channel.post_message(&json.into()) {
+                        log::warn!("Failed to notify other tabs: {:?}", e);
+                    }
+                }
+                Err(e) => log::warn!("Failed to notify other tabs: {}", e),
+            }
+        }
+    }
+
+    /// Whether this tab should poll the node. Also lets the other tabs know it's still open.
+    pub fn heartbeat(&self) -> bool {
+        self.post(TabMessage::Heartbeat);
+        self.peers.lock().unwrap().is_leader(self.id, Utc::now())
+    }
+
+    /// Whether another tab is dripping.
+    pub fn peer_dripping(&self) -> bool {
+        self.peers.lock().unwrap().is_dripping(Utc::now())
+    }
+
+    /// Lets the other tabs know a drip started. They're told it's over once the returned guard is
+    /// dropped.
+    pub fn start_drip(&self) -> DripAnnouncement {
+        self.post(TabMessage::DripStarted);
+        DripAnnouncement(self.clone())
+    }
+}
+
+pub struct DripAnnouncement(Tabs);
+
+impl Drop for DripAnnouncement {
+    fn drop(&mut self) {
+        self.0.post(TabMessage::DripFinished);
+    }
+}
+
+#[cfg(feature = "hydrate")]
+mod hydrate {
+    use super::*;
+    use crate::address::network_name;
+    use leptos::web_sys::{BroadcastChannel, MessageEvent};
+    use wasm_bindgen::prelude::*;
+
+    /// A joined channel, with the handler of its messages.
+    pub(super) struct Channel {
+        pub(super) channel: BroadcastChannel,
+        _onmessage: Closure<dyn Fn(MessageEvent)>,
+    }
+
+    impl Channel {
+        pub(super) fn close(&self) {
+            self.channel.set_onmessage(None);
+            self.channel.close();
+        }
+    }
+
+    pub(super) fn join(
+        network: Network,
+        id: Uuid,
+        peers: Arc<Mutex<Peers>>,
+        on_message: impl Fn(TabMessage) + 'static,
+    ) -> Option<Channel> {
+        let channel = BroadcastChannel::new(&format!("forest-faucet-{}", network_name(network)))
+            .inspect_err(|e| log::warn!("Tabs can't coordinate: {:?}", e))
+            .ok()?;
+        let onmessage = Closure::<dyn Fn(MessageEvent)>::new(move |event: MessageEvent| {
+            let Some(Ok(envelope)) = event
+                .data()
+                .as_string()
+                .map(|json| serde_json::from_str::<Envelope>(&json))
+            else {
+                return;
+            };
+            if envelope.from == id {
+                return;
+            }
+            peers
+                .lock()
+                .unwrap()
+                .receive(envelope.from, &envelope.message, Utc::now());
+            on_message(envelope.message);
+        });
+        channel.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+        Some(Channel {
+            channel,
+            _onmessage: onmessage,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peers() {
+        let now = Utc::now();
+        let (low, me, high) = {
+            let mut ids = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+            ids.sort();
+            (ids[0], ids[1], ids[2])
+        };
+        let mut peers = Peers::default();
+        assert!(peers.is_leader(me, now));
+
+        peers.receive(high, &TabMessage::Heartbeat, now);
+        assert!(peers.is_leader(me, now));
+        peers.receive(low, &TabMessage::Heartbeat, now);
+        assert!(!peers.is_leader(me, now));
+        // The lower tab was closed.
+        assert!(peers.is_leader(me, now + HEARTBEAT_TIMEOUT));
+
+        assert!(!peers.is_dripping(now));
+        peers.receive(high, &TabMessage::DripStarted, now);
+        assert!(peers.is_dripping(now));
+        assert!(!peers.is_dripping(now + DRIP_TIMEOUT));
+        peers.receive(high, &TabMessage::DripFinished, now);
+        assert!(!peers.is_dripping(now));
+    }
+
+    #[test]
+    fn test_tab_message_roundtrip() {
+        let envelope = Envelope {
+            from: Uuid::new_v4(),
+            message: TabMessage::Sent {
+                cid: Cid::default(),
+                amount: TokenAmount::from_whole(1),
+                sender: Some((Address::new_id(1), 5)),
+            },
+        };
+        let json = serde_json::to_string(&envelope).unwrap();
+        let parsed: Envelope = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.from, envelope.from);
+        assert_eq!(parsed.message, envelope.message);
+    }
+}
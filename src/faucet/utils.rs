@@ -1,11 +1,21 @@
+use super::error::FaucetError;
+#[cfg(feature = "ssr")]
+use super::error::FaucetErrorCode;
+#[cfg(feature = "ssr")]
+use crate::config::Config;
 #[cfg(feature = "ssr")]
 use crate::key::{sign, Key};
 use crate::{lotus_json::LotusJson, message::SignedMessage};
 use anyhow::{anyhow, Result};
-#[cfg(feature = "ssr")]
-use fvm_shared::address::Network;
-use fvm_shared::{address::Address, econ::TokenAmount, message::Message};
+use fvm_shared::{
+    address::{Address, Network},
+    bigint::BigInt,
+    econ::TokenAmount,
+    message::Message,
+};
 use leptos::{prelude::ServerFnError, server};
+use num_traits::{Signed as _, Zero as _};
+use serde::{Deserialize, Serialize};
 use url::Url;
 
 #[server]
@@ -23,58 +33,415 @@ pub async fn faucet_address(is_mainnet: bool) -> Result<LotusJson<Address>, Serv
 pub async fn sign_with_secret_key(
     msg: LotusJson<Message>,
     is_mainnet: bool,
-) -> Result<LotusJson<SignedMessage>, ServerFnError> {
-    use crate::message::message_cid;
-    use leptos::server_fn::error::NoCustomError;
+) -> Result<LotusJson<SignedMessage>, ServerFnError<FaucetError>> {
     use send_wrapper::SendWrapper;
     let LotusJson(msg) = msg;
-    let cid = message_cid(&msg);
-    let amount_limit = match is_mainnet {
-        true => crate::constants::MAINNET_DRIP_AMOUNT.clone(),
-        false => crate::constants::CALIBNET_DRIP_AMOUNT.clone(),
+    let mut signed = SendWrapper::new(sign_messages(vec![msg], is_mainnet))
+        .await
+        .map_err(FaucetError::respond)?;
+    Ok(LotusJson(signed.remove(0)))
+}
+
+/// Health of a faucet, for monitoring. Each check is independent, so a failing one leaves the
+/// others intact; `None` means the value couldn't be determined.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FaucetHealth {
+    #[serde(with = "crate::lotus_json")]
+    pub address: Option<Address>,
+    #[serde(with = "crate::lotus_json")]
+    pub balance: Option<TokenAmount>,
+    /// Whether the balance can no longer cover a single drip.
+    pub below_drip_amount: Option<bool>,
+    /// `None` when the rate limiter is disabled.
+    pub rate_limiter_reachable: Option<bool>,
+    pub rpc_reachable: bool,
+    /// Reasons for the failed checks.
+    pub errors: Vec<String>,
+}
+
+/// Aggregated health of the mainnet or calibnet faucet, e.g. for uptime checks. Served at a
+/// stable path so monitoring doesn't depend on the build.
+#[server(endpoint = "faucet_status")]
+pub async fn faucet_status(is_mainnet: bool) -> Result<FaucetHealth, ServerFnError> {
+    use axum::Extension;
+    use leptos_axum::extract;
+    use send_wrapper::SendWrapper;
+    use std::sync::Arc;
+    use worker::Env;
+
+    let network = if is_mainnet {
+        Network::Mainnet
+    } else {
+        Network::Testnet
+    };
+    let Extension(env): Extension<Arc<Env>> = extract().await?;
+    Ok(SendWrapper::new(check_faucet_status(&env, network)).await)
+}
+
+#[cfg(feature = "ssr")]
+async fn check_faucet_status(env: &worker::Env, network: Network) -> FaucetHealth {
+    use crate::rpc_context::Provider;
+
+    let mut errors = Vec::new();
+    // Report whether the node answers now, rather than after a round of retries. Its answers
+    // are a few bytes, anything much larger doesn't come from a healthy node.
+    let provider = Provider::from_network(network)
+        .with_retries(0, std::time::Duration::ZERO)
+        .with_max_response_size(64 * 1024);
+    let rpc_reachable = provider
+        .network_name()
+        .await
+        .inspect_err(|e| errors.push(format!("RPC: {e}")))
+        .is_ok();
+    let address = secret_key(network)
+        .await
+        .inspect_err(|e| errors.push(format!("Key: {e}")))
+        .ok()
+        .map(|key| key.address);
+    let balance = match address {
+        Some(address) if rpc_reachable => provider
+            .wallet_balance(address)
+            .await
+            .inspect_err(|e| errors.push(format!("Balance: {e}")))
+            .ok(),
+        _ => None,
+    };
+    let drip_amount = Config::from_env(env).drip_amount(network).clone();
+    let below_drip_amount = balance.as_ref().map(|balance| balance < &drip_amount);
+    let rate_limiter_reachable = if rate_limiter_disabled(env) {
+        None
+    } else {
+        Some(
+            ping_rate_limiter(env)
+                .await
+                .inspect_err(|e| errors.push(format!("Rate limiter: {e}")))
+                .is_ok(),
+        )
     };
-    if msg.value > amount_limit {
-        return Err(ServerFnError::ServerError(
-            "Amount limit exceeded".to_string(),
+    FaucetHealth {
+        address,
+        balance,
+        below_drip_amount,
+        rate_limiter_reachable,
+        rpc_reachable,
+        errors,
+    }
+}
+
+/// A drip signed by the faucet, as listed by [`faucet_transactions`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DripRecord {
+    pub cid: String,
+    pub to: String,
+    #[serde(with = "crate::lotus_json")]
+    pub amount: TokenAmount,
+    /// Unix time at which the drip was signed.
+    pub timestamp: i64,
+}
+
+/// The most recent on-chain drips of the mainnet or calibnet faucet across all users, newest
+/// first and [`crate::constants::DRIP_LOG_PAGE_SIZE`] per page. Only the last
+/// [`crate::constants::DRIP_LOG_CAPACITY`] drips are kept.
+#[server(endpoint = "faucet_transactions")]
+pub async fn faucet_transactions(
+    is_mainnet: bool,
+    page: usize,
+) -> Result<Vec<DripRecord>, ServerFnError> {
+    use crate::drip_log::DripLogRequest;
+    use axum::Extension;
+    use leptos_axum::extract;
+    use send_wrapper::SendWrapper;
+    use std::sync::Arc;
+    use worker::Env;
+
+    let network = if is_mainnet {
+        Network::Mainnet
+    } else {
+        Network::Testnet
+    };
+    let Extension(env): Extension<Arc<Env>> = extract().await?;
+    let drips = SendWrapper::new(query_drip_log(
+        &env,
+        network,
+        &DripLogRequest::List { page },
+    ))
+    .await?;
+    Ok(drips)
+}
+
+/// Signs drips to several recipients at once. Each recipient is charged to the rate limiter, so a
+/// batch of `n` drips blocks signing for `n` rate limit windows.
+#[server]
+pub async fn sign_batch_with_secret_key(
+    msgs: Vec<LotusJson<Message>>,
+    is_mainnet: bool,
+) -> Result<Vec<LotusJson<SignedMessage>>, ServerFnError<FaucetError>> {
+    use send_wrapper::SendWrapper;
+    let msgs = msgs.into_iter().map(LotusJson::into_inner).collect();
+    let signed = SendWrapper::new(sign_messages(msgs, is_mainnet))
+        .await
+        .map_err(FaucetError::respond)?;
+    Ok(signed.into_iter().map(LotusJson).collect())
+}
+
+#[cfg(feature = "ssr")]
+async fn sign_messages(
+    msgs: Vec<Message>,
+    is_mainnet: bool,
+) -> Result<Vec<SignedMessage>, FaucetError> {
+    use crate::config::Config;
+    use crate::message::{fee_exceeds_ratio, message_cid};
+    use crate::rate_limiter::RateLimit;
+    use axum::Extension;
+    use fvm_shared::METHOD_SEND;
+    use leptos_axum::extract;
+    use std::sync::Arc;
+    use worker::{Context, Env};
+
+    let Extension(env): Extension<Arc<Env>> = extract().await.map_err(FaucetError::internal)?;
+    let Extension(ctx): Extension<Arc<Context>> = extract().await.map_err(FaucetError::internal)?;
+    let _permit = SignPermit::acquire(&env)?;
+    let network = if is_mainnet {
+        Network::Mainnet
+    } else {
+        Network::Testnet
+    };
+    let config = Config::from_env(&env);
+    if msgs.len() > config.max_batch_recipients {
+        return Err(FaucetError::new(
+            FaucetErrorCode::TooManyRecipients,
+            format!(
+                "Too many recipients - at most {} per request",
+                config.max_batch_recipients
+            ),
         ));
     }
-    SendWrapper::new(async move {
-        use axum::Extension;
-        use leptos_axum::extract;
-        use std::sync::Arc;
-        use worker::Env;
-        let Extension(env): Extension<Arc<Env>> = extract().await?;
-        let rate_limiter_disabled = env
-            .secret("RATE_LIMITER_DISABLED")
-            .map(|v| v.to_string().to_lowercase() == "true")
-            .unwrap_or(false);
-        let may_sign = rate_limiter_disabled || query_rate_limiter().await?;
-
-        if !may_sign {
-            return Err(ServerFnError::ServerError(format!(
-                "Rate limit exceeded - wait {} seconds",
-                crate::constants::RATE_LIMIT_SECONDS
-            )));
-        }
+    if msgs
+        .iter()
+        .any(|msg| !config.is_allowed_amount(network, &msg.value))
+    {
+        return Err(FaucetError::new(
+            FaucetErrorCode::AmountExceeded,
+            "Amount not allowed",
+        ));
+    }
+    if has_duplicate_recipients(&msgs) {
+        return Err(FaucetError::new(
+            FaucetErrorCode::InvalidMessage,
+            "Each recipient may only appear once per request",
+        ));
+    }
+    let params = config.drip_params();
+    if msgs
+        .iter()
+        .any(|msg| msg.method_num != METHOD_SEND || msg.params != params)
+    {
+        return Err(FaucetError::new(
+            FaucetErrorCode::InvalidMessage,
+            "Only plain transfers carrying the configured memo are signed",
+        ));
+    }
+    let key = secret_key(network).await.map_err(FaucetError::internal)?;
+    if msgs.iter().any(|msg| msg.from != key.address) {
+        return Err(FaucetError::new(
+            FaucetErrorCode::InvalidMessage,
+            "Messages must be sent from the faucet address",
+        ));
+    }
+    if msgs
+        .iter()
+        .any(|msg| config.exceeds_gas_fee_cap(network, msg))
+    {
+        return Err(FaucetError::new(
+            FaucetErrorCode::FeeTooHigh,
+            format!(
+                "Gas fee cap exceeds the faucet's ceiling of {} attoFIL per gas unit - try again later",
+                config.max_gas_fee_cap(network).atto()
+            ),
+        ));
+    }
+    // Calibnet funds are worthless, so only mainnet drips are protected from fee spikes.
+    if network == Network::Mainnet
+        && msgs
+            .iter()
+            .any(|msg| fee_exceeds_ratio(msg, config.max_fee_ratio))
+    {
+        return Err(FaucetError::new(
+            FaucetErrorCode::FeeTooHigh,
+            "Network fees are too high right now - try again later",
+        ));
+    }
+    let limit = if rate_limiter_disabled(&env) {
+        RateLimit::Allowed
+    } else {
+        apply_limiter_failure_policy(
+            query_rate_limiter(msgs.len()).await,
+            config.limiter_failure_policy(network),
+        )?
+    };
+    if let RateLimit::Blocked { retry_after } = limit {
+        return Err(FaucetError::new(
+            FaucetErrorCode::RateLimited,
+            format!("Rate limit exceeded - wait {retry_after} seconds"),
+        )
+        .with_retry_after(retry_after));
+    }
 
-        let network = if is_mainnet {
-            Network::Mainnet
-        } else {
-            Network::Testnet
-        };
-        let key = secret_key(network).await?;
+    let mut signed = Vec::with_capacity(msgs.len());
+    for mut msg in msgs {
+        if let Some(nonce) = allocate_nonce(&env, network, key.address)
+            .await
+            .map_err(FaucetError::internal)?
+        {
+            msg.sequence = nonce;
+        }
+        let cid = message_cid(&msg);
         let sig = sign(
             key.key_info.r#type,
             &key.key_info.private_key,
             cid.to_bytes().as_slice(),
         )
-        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
-        Ok(LotusJson(SignedMessage {
+        .map_err(FaucetError::internal)?;
+        let smsg = SignedMessage {
             message: msg,
             signature: sig,
+        };
+        // Never hand out a drip the network would reject.
+        smsg.verify().map_err(FaucetError::internal)?;
+        signed.push(smsg);
+    }
+    // Neither recording the drips nor checking the balance holds up the response.
+    ctx.wait_until({
+        let env = env.clone();
+        let signed = signed.clone();
+        async move {
+            if let Err(e) = record_drips(&env, network, &signed).await {
+                log::warn!("Failed to record drips: {e}");
+            }
+        }
+    });
+    let address = key.address;
+    ctx.wait_until(async move {
+        if let Err(e) = request_topup_if_low(&env, &config, network, address).await {
+            log::warn!("Automatic top-up request failed: {e}");
+        }
+    });
+    Ok(signed)
+}
+
+/// Posts a top-up request to `FAUCET_TOPUP_REQ_URL` if the faucet's balance fell below the
+/// configured threshold. Requests go out at most once per `topup_interval_seconds`, tracked by a
+/// dedicated rate limiter instance; without the rate limiter, none are sent.
+#[cfg(feature = "ssr")]
+async fn request_topup_if_low(
+    env: &worker::Env,
+    config: &Config,
+    network: Network,
+    address: Address,
+) -> Result<()> {
+    use crate::rate_limiter::RateLimit;
+    use crate::rpc_context::Provider;
+
+    let Some(url) = option_env!("FAUCET_TOPUP_REQ_URL") else {
+        return Ok(());
+    };
+    if config.topup_threshold(network).is_none() {
+        return Ok(());
+    }
+    let balance = Provider::from_network(network)
+        .wallet_balance(address)
+        .await?;
+    if !config.needs_topup(network, &balance) {
+        return Ok(());
+    }
+    let limit = acquire_topup_slot(env)
+        .await
+        .map_err(|e| anyhow!("Rate limiter: {e}"))?;
+    if limit != RateLimit::Allowed {
+        return Ok(());
+    }
+    log::info!("Faucet balance {balance} is low, requesting a top-up");
+    reqwest::Client::new()
+        .post(url)
+        .json(&serde_json::json!({
+            "network": crate::address::network_name(network),
+            "address": address.to_string(),
+            "balance": balance.atto().to_string(),
         }))
-    })
-    .await
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Whether several of `msgs` go to the same address.
+#[cfg(feature = "ssr")]
+fn has_duplicate_recipients(msgs: &[Message]) -> bool {
+    let mut seen = std::collections::HashSet::new();
+    !msgs.iter().all(|msg| seen.insert(msg.to))
+}
+
+/// Decides whether to sign when the rate limiter failed to answer, according to `policy`.
+#[cfg(feature = "ssr")]
+fn apply_limiter_failure_policy(
+    limit: Result<crate::rate_limiter::RateLimit, ServerFnError>,
+    policy: crate::config::LimiterFailurePolicy,
+) -> Result<crate::rate_limiter::RateLimit, FaucetError> {
+    use crate::config::LimiterFailurePolicy;
+    use crate::rate_limiter::RateLimit;
+
+    match (limit, policy) {
+        (Ok(limit), _) => Ok(limit),
+        (Err(e), LimiterFailurePolicy::Open) => {
+            log::warn!("Rate limiter unavailable, signing anyway: {e}");
+            Ok(RateLimit::Allowed)
+        }
+        (Err(e), LimiterFailurePolicy::Closed) => Err(FaucetError::internal(format!(
+            "Rate limiter unavailable: {e}"
+        ))),
+    }
+}
+
+/// Number of signing requests currently being handled by this worker instance.
+#[cfg(feature = "ssr")]
+static SIGNS_IN_FLIGHT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+/// A slot among the `SIGN_CONCURRENCY_LIMIT` signing requests allowed at once, released on drop.
+#[cfg(feature = "ssr")]
+struct SignPermit;
+
+#[cfg(feature = "ssr")]
+impl SignPermit {
+    /// Takes a slot, or rejects the request with `429 Too Many Requests` if all are taken.
+    fn acquire(env: &worker::Env) -> Result<Self, FaucetError> {
+        use std::sync::atomic::Ordering;
+
+        let limit = env
+            .secret("SIGN_CONCURRENCY_LIMIT")
+            .ok()
+            .and_then(|v| v.to_string().parse().ok())
+            .unwrap_or(crate::constants::SIGN_CONCURRENCY_LIMIT);
+        let acquired = SIGNS_IN_FLIGHT
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (n < limit).then_some(n + 1)
+            })
+            .is_ok();
+        if acquired {
+            return Ok(Self);
+        }
+        Err(FaucetError::new(
+            FaucetErrorCode::Busy,
+            "The faucet is busy - try again in a moment",
+        )
+        .with_retry_after(1))
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl Drop for SignPermit {
+    fn drop(&mut self) {
+        SIGNS_IN_FLIGHT.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+    }
 }
 
 #[cfg(feature = "ssr")]
@@ -98,7 +465,110 @@ pub async fn secret_key(network: Network) -> Result<Key, ServerFnError> {
 }
 
 #[cfg(feature = "ssr")]
-pub async fn query_rate_limiter() -> Result<bool, ServerFnError> {
+fn rate_limiter_disabled(env: &worker::Env) -> bool {
+    env.secret("RATE_LIMITER_DISABLED")
+        .map(|v| v.to_string().to_lowercase() == "true")
+        .unwrap_or(false)
+}
+
+/// Asks the rate limiter whether a top-up request may be sent now. Top-ups use their own
+/// instance, so they don't block drips.
+#[cfg(feature = "ssr")]
+async fn acquire_topup_slot(env: &worker::Env) -> worker::Result<crate::rate_limiter::RateLimit> {
+    use worker::{Method, Request};
+
+    env.durable_object("RATE_LIMITER")?
+        .id_from_name("TOPUP")?
+        .get_stub()?
+        .fetch_with_request(Request::new(
+            &format!("http://do{}", crate::rate_limiter::TOPUP_PATH),
+            Method::Get,
+        )?)
+        .await?
+        .json()
+        .await
+}
+
+/// Hands signed drips to the network's `DRIP_LOG` durable object, which lists them once they're
+/// on chain. Does nothing when the binding is not configured.
+#[cfg(feature = "ssr")]
+async fn record_drips(
+    env: &worker::Env,
+    network: Network,
+    signed: &[SignedMessage],
+) -> worker::Result<()> {
+    use crate::address::format_address;
+    use crate::drip_log::DripLogRequest;
+
+    if env.durable_object("DRIP_LOG").is_err() {
+        return Ok(());
+    }
+    let timestamp = chrono::Utc::now().timestamp();
+    let drips = signed
+        .iter()
+        .map(|smsg| DripRecord {
+            cid: smsg.cid().to_string(),
+            to: format_address(&smsg.message.to, network),
+            amount: smsg.message.value.clone(),
+            timestamp,
+        })
+        .collect();
+    let request = DripLogRequest::Record {
+        is_mainnet: network == Network::Mainnet,
+        drips,
+    };
+    query_drip_log::<bool>(env, network, &request).await?;
+    Ok(())
+}
+
+/// Sends `request` to the network's instance of the `DRIP_LOG` durable object.
+#[cfg(feature = "ssr")]
+async fn query_drip_log<T: serde::de::DeserializeOwned>(
+    env: &worker::Env,
+    network: Network,
+    request: &crate::drip_log::DripLogRequest,
+) -> worker::Result<T> {
+    use crate::address::network_name;
+    use worker::{Method, Request, RequestInit};
+
+    let body = serde_json::to_string(request)?;
+    let req = Request::new_with_init(
+        "http://do/drip_log",
+        RequestInit::new()
+            .with_method(Method::Post)
+            .with_body(Some(body.into())),
+    )?;
+    env.durable_object("DRIP_LOG")?
+        .id_from_name(network_name(network))?
+        .get_stub()?
+        .fetch_with_request(req)
+        .await?
+        .json()
+        .await
+}
+
+/// Checks that the rate limiter responds, without taking up a request.
+#[cfg(feature = "ssr")]
+async fn ping_rate_limiter(env: &worker::Env) -> worker::Result<()> {
+    use worker::{Method, Request};
+
+    env.durable_object("RATE_LIMITER")?
+        .id_from_name("RATE_LIMITER")?
+        .get_stub()?
+        .fetch_with_request(Request::new(
+            &format!("http://do{}", crate::rate_limiter::STATUS_PATH),
+            Method::Get,
+        )?)
+        .await?
+        .json::<bool>()
+        .await?;
+    Ok(())
+}
+
+#[cfg(feature = "ssr")]
+pub async fn query_rate_limiter(
+    recipients: usize,
+) -> Result<crate::rate_limiter::RateLimit, ServerFnError> {
     use axum::Extension;
     use leptos_axum::extract;
     use std::sync::Arc;
@@ -110,18 +580,71 @@ pub async fn query_rate_limiter() -> Result<bool, ServerFnError> {
         .id_from_name("RATE_LIMITER")?
         .get_stub()?;
     Ok(rate_limiter
-        .fetch_with_request(Request::new("http://do/rate_limiter", Method::Get)?)
+        .fetch_with_request(Request::new(
+            &format!(
+                "http://do/rate_limiter?{}={recipients}",
+                crate::rate_limiter::RECIPIENTS_PARAM
+            ),
+            Method::Get,
+        )?)
         .await?
-        .json::<bool>()
+        .json()
         .await?)
 }
 
-/// Formats FIL balance to a human-readable string with two decimal places and a unit.
+/// Allocates the next nonce for the faucet key from the `NONCE_MANAGER` durable object, seeded
+/// with the nonce reported by the node. Returns `None` when the binding is not configured, in which
+/// case the nonce chosen by the client is kept.
+#[cfg(feature = "ssr")]
+async fn allocate_nonce(
+    env: &worker::Env,
+    network: Network,
+    address: Address,
+) -> Result<Option<u64>, ServerFnError> {
+    use crate::nonce_manager::NonceRequest;
+    use crate::rpc_context::Provider;
+    use leptos::server_fn::error::NoCustomError;
+    use worker::{Method, Request, RequestInit};
+
+    let Ok(namespace) = env.durable_object("NONCE_MANAGER") else {
+        return Ok(None);
+    };
+    let chain_nonce = Provider::from_network(network)
+        .next_nonce(address)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    let body = serde_json::to_string(&NonceRequest { chain_nonce })?;
+    let req = Request::new_with_init(
+        "http://do/nonce_manager",
+        RequestInit::new()
+            .with_method(Method::Post)
+            .with_body(Some(body.into())),
+    )?;
+    let nonce = namespace
+        .id_from_name(&address.to_string())?
+        .get_stub()?
+        .fetch_with_request(req)
+        .await?
+        .json::<u64>()
+        .await?;
+    Ok(Some(nonce))
+}
+
+/// Formats FIL balance to a human-readable string with two decimal places and a unit. The
+/// rounding is done on the exact attoFIL value, so arbitrarily large balances are shown in full.
 pub fn format_balance(balance: &TokenAmount, unit: &str) -> String {
-    format!(
-        "{:.2} {unit}",
-        balance.to_string().parse::<f32>().unwrap_or_default(),
-    )
+    // attoFIL per hundredth of a FIL.
+    let hundredth = BigInt::from(10u64.pow(16));
+    let atto = balance.atto();
+    let hundredths: BigInt = (atto.abs() + &hundredth / 2) / &hundredth;
+    let sign = if atto.is_negative() && !hundredths.is_zero() {
+        "-"
+    } else {
+        ""
+    };
+    let whole = &hundredths / 100;
+    let fraction = &hundredths % 100;
+    format!("{sign}{whole}.{fraction:0>2} {unit}")
 }
 
 /// Types of search paths in Filecoin explorer.
@@ -148,6 +671,36 @@ pub fn format_url(base_url: &Url, path: SearchPath, identifier: &str) -> Result<
         .map_err(|e| anyhow!("Failed to join URL: {}", e))
 }
 
+/// Base URL of the external explorer linked for confirmed faucet transactions. The
+/// `FAUCET_TX_URL_*` build variables override the built-in explorer.
+pub fn tx_base_url(network: Network) -> Url {
+    let (configured, default) = match network {
+        Network::Mainnet => (
+            option_env!("FAUCET_TX_URL_MAINNET"),
+            crate::constants::MAINNET_TX_URL,
+        ),
+        Network::Testnet => (
+            option_env!("FAUCET_TX_URL_CALIBNET"),
+            crate::constants::CALIBNET_TX_URL,
+        ),
+    };
+    configured
+        .and_then(parse_base_url)
+        .or_else(|| parse_base_url(default))
+        .expect("default explorer URLs are valid")
+}
+
+/// Parses an explorer base URL. A trailing slash is added if missing, so joining paths onto it
+/// keeps its last segment, e.g. the network in `https://beryx.io/fil/mainnet`.
+fn parse_base_url(url: &str) -> Option<Url> {
+    let url = if url.ends_with('/') {
+        url.to_string()
+    } else {
+        format!("{url}/")
+    };
+    Url::parse(&url).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,10 +713,75 @@ mod tests {
             (TokenAmount::from_whole(0), "0.00 FIL"),
             (TokenAmount::from_nano(10e6 as i64), "0.01 FIL"),
             (TokenAmount::from_nano(999_999_999), "1.00 FIL"),
+            (TokenAmount::from_nano(4_999_999), "0.00 FIL"),
+            (TokenAmount::from_nano(5_000_000), "0.01 FIL"),
+            (TokenAmount::from_whole(-2), "-2.00 FIL"),
+            (TokenAmount::from_atto(-1), "0.00 FIL"),
+            // Well beyond f64 range.
+            (
+                TokenAmount::from_atto(BigInt::from(10u8).pow(400) * 12345),
+                &format!("12345{}.00 FIL", "0".repeat(382)),
+            ),
         ];
         for (balance, expected) in cases.iter() {
-            assert_eq!(format_balance(balance, "FIL"), *expected);
+            assert_eq!(format_balance(balance, "FIL"), **expected);
+        }
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn test_has_duplicate_recipients() {
+        let drip = |id| {
+            crate::message::message_transfer(
+                Address::new_id(0),
+                Address::new_id(id),
+                TokenAmount::from_whole(1),
+            )
+        };
+        assert!(!has_duplicate_recipients(&[]));
+        assert!(!has_duplicate_recipients(&[drip(1), drip(2)]));
+        assert!(has_duplicate_recipients(&[drip(1), drip(2), drip(1)]));
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn test_limiter_failure_policy() {
+        use crate::config::LimiterFailurePolicy;
+        use crate::rate_limiter::RateLimit;
+
+        let blocked = RateLimit::Blocked { retry_after: 5 };
+        let failed = || Err(ServerFnError::ServerError("no such binding".into()));
+        for policy in [LimiterFailurePolicy::Open, LimiterFailurePolicy::Closed] {
+            assert_eq!(
+                apply_limiter_failure_policy(Ok(blocked), policy).unwrap(),
+                blocked
+            );
+        }
+        assert_eq!(
+            apply_limiter_failure_policy(failed(), LimiterFailurePolicy::Open).unwrap(),
+            RateLimit::Allowed
+        );
+        let err = apply_limiter_failure_policy(failed(), LimiterFailurePolicy::Closed).unwrap_err();
+        assert_eq!(err.code, FaucetErrorCode::Internal);
+    }
+
+    #[test]
+    fn test_tx_base_url() {
+        for network in [Network::Mainnet, Network::Testnet] {
+            let base = tx_base_url(network);
+            let url = format_url(&base, SearchPath::Transaction, "bafy2bzace").unwrap();
+            assert!(url.as_str().starts_with(base.as_str()));
+            assert!(url.as_str().ends_with("/txs/bafy2bzace"));
         }
+        // The last segment of a base without a trailing slash is kept.
+        let base = parse_base_url("https://beryx.io/fil/mainnet").unwrap();
+        assert_eq!(
+            format_url(&base, SearchPath::Address, "f01234")
+                .unwrap()
+                .as_str(),
+            "https://beryx.io/fil/mainnet/address/f01234"
+        );
+        assert!(parse_base_url("not a url").is_none());
     }
 
     #[test]
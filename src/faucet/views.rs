@@ -1,62 +1,152 @@
 use std::collections::HashSet;
 use std::time::Duration;
 
+use chrono::Utc;
 use fvm_shared::address::Network;
 use leptos::prelude::*;
 use leptos::task::spawn_local;
-use leptos::{component, leptos_dom::helpers::event_target_value, view, IntoView};
+use leptos::{
+    component,
+    leptos_dom::helpers::{event_target_checked, event_target_value},
+    view, IntoView,
+};
 use leptos_meta::{Meta, Title};
+use leptos_router::hooks::use_query_map;
 #[cfg(feature = "hydrate")]
 use leptos_use::*;
 use url::Url;
 
+use crate::address::{
+    describe_address, format_address, network_name, parse_address_any, protocol_label,
+    wrong_network,
+};
+use crate::components::{CopyButton, Countdown, TransactionLink};
+use crate::config::{ConfigContext, FaucetSettings};
 use crate::faucet::controller::FaucetController;
+use crate::faucet::signer::Signer;
 use crate::faucet::utils::SearchPath;
-use crate::faucet::utils::{format_balance, format_url};
-use crate::rpc_context::{Provider, RpcContext};
+use crate::faucet::utils::{format_balance, format_url, tx_base_url};
+use crate::key::{recover_signer, verify};
+use crate::message::message_cid;
+use crate::rpc_context::{Provider, RpcContext, RpcProvider};
+use crate::storage::{use_stored, StorageKind};
+use crate::utils::format_time_ago;
 
 const MESSAGE_FADE_AFTER: Duration = Duration::new(3, 0);
 const MESSAGE_REMOVAL_AFTER: Duration = Duration::new(3, 500_000_000);
 
+/// What a faucet's send button offers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SendButton {
+    Sending,
+    RateLimited,
+    /// The faucet can't cover the drip, so operators are asked for a top-up instead.
+    TopUp,
+    Ready,
+}
+
+impl SendButton {
+    fn of<P: RpcProvider + Clone + 'static>(faucet: &FaucetController<P>) -> Self {
+        // Users paying from their own wallet don't depend on the faucet's balance.
+        let needs_topup = faucet.get_signer() == Signer::Faucet
+            && faucet.get_faucet_balance() < faucet.get_amount();
+        Self::new(
+            faucet.is_send_disabled(),
+            faucet.get_send_rate_limit_remaining() > 0,
+            needs_topup,
+        )
+    }
+
+    fn new(sending: bool, rate_limited: bool, needs_topup: bool) -> Self {
+        if sending {
+            SendButton::Sending
+        } else if rate_limited {
+            SendButton::RateLimited
+        } else if needs_topup {
+            SendButton::TopUp
+        } else {
+            SendButton::Ready
+        }
+    }
+}
+
+/// The faucet's send button, or what's offered instead while it can't send.
 #[component]
-pub fn Faucet(target_network: Network) -> impl IntoView {
-    let faucet = RwSignal::new(FaucetController::new(target_network));
+fn SendControl<P: RpcProvider + Clone + Send + Sync + 'static>(
+    faucet: RwSignal<FaucetController<P>>,
+) -> impl IntoView {
+    let topup_req_url = option_env!("FAUCET_TOPUP_REQ_URL");
+    move || {
+        match SendButton::of(&faucet.get()) {
+        SendButton::Sending => {
+            view! {
+                <button class="bg-gray-400 text-white font-bold py-2 px-4 rounded-r" disabled=true>
+                    "Sending..."
+                </button>
+            }.into_any()
+        }
+        SendButton::RateLimited => {
+            view! {
+                <button class="bg-gray-400 text-white font-bold py-2 px-4 rounded-r" disabled=true>
+                    "Rate-limited! " <Countdown deadline=faucet.get().send_rate_limit() />
+                </button>
+            }.into_any()
+        }
+        SendButton::TopUp => {
+            view! {
+                <a href={topup_req_url} target="_blank" class="bg-blue-500 hover:bg-blue-700 text-white font-bold py-2 px-4 rounded-r">
+                    "Request Faucet Top-up"
+                </a>
+            }.into_any()
+        }
+        SendButton::Ready => {
+            view! {
+                <button
+                    class="bg-green-500 hover:bg-green-600 text-white font-bold py-2 px-4 rounded-r"
+                    on:click=move |_| {
+                        faucet.get().drip(false);
+                    }
+                >
+                    Send
+                </button>
+            }.into_any()
+        }
+    }
+    }
+}
 
-    #[cfg(feature = "hydrate")]
-    let _ = use_interval_fn(
-        move || {
-            let duration = faucet.get().get_send_rate_limit_remaining();
-            if duration > 0 {
-                faucet.get().set_send_rate_limit_remaining(duration - 1);
-            }
-        },
-        1000,
+#[component]
+pub fn Faucet(
+    target_network: Network,
+    /// Renders only the widget, without links to the rest of the site.
+    #[prop(optional)]
+    embed: bool,
+) -> impl IntoView {
+    let faucet = RwSignal::new(FaucetController::new(target_network));
+    let now = RwSignal::new(Utc::now());
+    let query = use_query_map();
+    faucet.get_untracked().set_callback(
+        query
+            .get_untracked()
+            .get("callback")
+            .and_then(|url| Url::parse(&url).ok()),
     );
 
+    #[cfg(feature = "hydrate")]
+    faucet.get_untracked().load_history();
     #[cfg(feature = "hydrate")]
     let _ = use_interval_fn(
         move || {
-            faucet.get().refetch_balances();
+            if let Some(faucet) = faucet.try_get_untracked() {
+                faucet.refetch_balances();
+            }
+            now.try_set(Utc::now());
         },
         5000,
     );
 
     let (fading_messages, set_fading_messages) = signal(HashSet::new());
-    let (drip_amount, faucet_tx_base_url) = match target_network {
-        Network::Mainnet => (
-            crate::constants::MAINNET_DRIP_AMOUNT.clone(),
-            RwSignal::new(
-                option_env!("FAUCET_TX_URL_MAINNET").and_then(|url| Url::parse(url).ok()),
-            ),
-        ),
-        Network::Testnet => (
-            crate::constants::CALIBNET_DRIP_AMOUNT.clone(),
-            RwSignal::new(
-                option_env!("FAUCET_TX_URL_CALIBNET").and_then(|url| Url::parse(url).ok()),
-            ),
-        ),
-    };
-    let topup_req_url = option_env!("FAUCET_TOPUP_REQ_URL");
+    let faucet_tx_base_url = RwSignal::new(tx_base_url(target_network));
     view! {
         {move || {
             let errors = faucet.get().get_error_messages();
@@ -129,62 +219,177 @@ pub fn Faucet(target_network: Network) -> impl IntoView {
             <div class="my-4 flex">
                 <input
                     type="text"
-                    placeholder="Enter target address (Filecoin or Ethereum style)"
+                    placeholder="Enter target address (Filecoin or Ethereum style), or several separated by commas"
+                    data-shortcut-focus
                     prop:value=faucet.get().get_target_address()
                     on:input=move |ev| { faucet.get().set_target_address(event_target_value(&ev)) }
                     on:keydown=move |ev| {
                         if ev.key() == "Enter" && !faucet.get().is_send_disabled() && faucet.get().get_send_rate_limit_remaining() <= 0 {
-                            faucet.get().drip();
+                            faucet.get().drip(false);
                         }
                     }
                     class="flex-grow border border-gray-300 p-2 rounded-l"
                 />
                 {move || {
-                    if faucet.get().is_send_disabled() {
-                        view! {
-                            <button class="bg-gray-400 text-white font-bold py-2 px-4 rounded-r" disabled=true>
-                                "Sending..."
-                            </button>
-                        }.into_any()
-                    } else if faucet.get().get_send_rate_limit_remaining() > 0 {
-                        let duration = faucet.get().get_send_rate_limit_remaining();
+                    parse_address_any(&faucet.get().get_target_address()).ok().map(|(addr, network)| {
+                        let network = network_name(network.unwrap_or(target_network));
                         view! {
-                            <button class="bg-gray-400 text-white font-bold py-2 px-4 rounded-r" disabled=true>
-                                {format!("Rate-limited! {duration}s")}
-                            </button>
-                        }.into_any()
-                    } else if faucet.get().get_faucet_balance() < drip_amount {
-                        view! {
-                            <a href={topup_req_url} target="_blank" class="bg-blue-500 hover:bg-blue-700 text-white font-bold py-2 px-4 rounded-r">
-                                "Request Faucet Top-up"
+                            <span class="self-center whitespace-nowrap bg-gray-100 text-gray-700 text-xs px-2 py-1 mx-2 rounded-full">
+                                {format!("{} · {network}", protocol_label(&addr))}
+                            </span>
+                        }
+                    })
+                }}
+                <details class="relative self-center mx-2">
+                    <summary class="list-none cursor-pointer select-none w-5 h-5 text-xs text-center leading-5 border border-gray-400 text-gray-500 rounded-full" title="Which addresses are accepted?">
+                        "i"
+                    </summary>
+                    <div class="absolute right-0 z-10 w-72 mt-2 p-3 bg-white border border-gray-300 rounded shadow text-sm text-gray-700">
+                        <p class="mb-2">"Accepted address formats:"</p>
+                        <ul class="list-disc pl-5 mb-2">
+                            <li>"f1/t1 and f3/t3: regular Filecoin wallets"</li>
+                            <li>"f410/t410 or 0x…: Ethereum-compatible wallets, e.g. MetaMask"</li>
+                            <li>"f0/t0 and f2/t2: existing actors"</li>
+                        </ul>
+                        {move || {
+                            parse_address_any(&faucet.get().get_target_address())
+                                .ok()
+                                .map(|(addr, _)| view! { <p class="font-semibold">{describe_address(&addr)}</p> })
+                        }}
+                    </div>
+                </details>
+                <SendControl faucet />
+
+            </div>
+            {move || {
+                wrong_network(&faucet.get().get_target_address(), target_network).map(|other| {
+                    let name = network_name(other);
+                    view! {
+                        <p class="text-sm text-yellow-700 mb-4">
+                            {format!("This looks like a {name} address. ")}
+                            <a class="text-blue-600 underline" href=format!("/faucet/{name}")>
+                                {format!("Switch to the {name} faucet?")}
                             </a>
-                        }.into_any()
-                    } else {
-                        view! {
-                            <button
-                                class="bg-green-500 hover:bg-green-600 text-white font-bold py-2 px-4 rounded-r"
-                                on:click=move |_| {
-                                    faucet.get().drip();
-                                }
-                            >
-                                Send
-                            </button>
-                        }.into_any()
+                        </p>
                     }
+                })
+            }}
+            {move || {
+                faucet.get().get_target_filecoin_address().map(|addr| view! {
+                    <p class="text-sm text-gray-600 mb-4 break-all">"Funds go to " {addr}</p>
+                })
+            }}
+            <div class="mb-4 text-sm">
+                <button
+                    class="text-blue-600 hover:underline"
+                    title="Estimate and simulate the drip without signing or sending it"
+                    on:click=move |_| faucet.get().drip(true)
+                >
+                    "Dry run"
+                </button>
+                {move || {
+                    faucet.get().get_simulation().map(|simulated| view! {
+                        <ul class="list-disc pl-5 text-gray-700">
+                            {simulated.into_iter().map(|drip| {
+                                let to = format_address(&drip.to, target_network);
+                                let fee = format_balance(&drip.gas.max_fee, &faucet.get().get_fil_unit());
+                                match drip.failure {
+                                    None => view! {
+                                        <li class="break-all">{format!("{to}: would succeed, fee at most {fee}")}</li>
+                                    }.into_any(),
+                                    Some(failure) => view! {
+                                        <li class="break-all text-red-600">{format!("{to}: would fail ({failure})")}</li>
+                                    }.into_any(),
+                                }
+                            }).collect::<Vec<_>>()}
+                        </ul>
+                    })
                 }}
-
             </div>
+            <Show when=move || { faucet.get().get_allowed_amounts().len() > 1 }>
+                <div class="flex items-center space-x-2 mb-4">
+                    <span class="text-sm text-gray-700">"Amount:"</span>
+                    {move || {
+                        let selected = faucet.get().get_amount();
+                        faucet.get().get_allowed_amounts().into_iter().map(|amount| {
+                            let class = if amount == selected {
+                                "bg-blue-500 text-white text-sm py-1 px-3 rounded-full"
+                            } else {
+                                "bg-gray-200 hover:bg-gray-300 text-gray-800 text-sm py-1 px-3 rounded-full"
+                            };
+                            let label = format_balance(&amount, &faucet.get().get_fil_unit());
+                            view! {
+                                <button class=class on:click=move |_| faucet.get().set_amount(amount.clone())>
+                                    {label}
+                                </button>
+                            }
+                        }).collect::<Vec<_>>()
+                    }}
+                </div>
+                <label class="flex items-center text-sm text-gray-700 mb-2">
+                    <input
+                        type="checkbox"
+                        class="mr-2"
+                        prop:checked=move || faucet.get().get_amount_in_atto()
+                        on:change=move |ev| faucet.get().set_amount_in_atto(event_target_checked(&ev))
+                    />
+                    "Enter the exact amount in attoFIL"
+                </label>
+                <Show when=move || faucet.get().get_amount_in_atto()>
+                    <input
+                        type="text"
+                        inputmode="numeric"
+                        class="w-full border border-gray-300 p-2 rounded mb-4 font-mono text-sm"
+                        prop:value=move || faucet.get().get_amount().atto().to_string()
+                        on:change=move |ev| faucet.get().set_amount_atto(&event_target_value(&ev))
+                    />
+                </Show>
+            </Show>
+            <Show when=move || faucet.get().is_target_contract()>
+                <p class="bg-yellow-100 border border-yellow-400 text-yellow-800 text-sm px-4 py-2 mb-2 rounded">
+                    "This address belongs to a smart contract, not a wallet. Funds sent to it can only be moved by the contract's own logic."
+                </p>
+            </Show>
+            <Show when=Signer::external_enabled>
+                <label class="flex items-center text-sm text-gray-700">
+                    <input
+                        type="checkbox"
+                        class="mr-2"
+                        prop:checked=move || faucet.get().get_signer() == Signer::External
+                        on:change=move |ev| {
+                            faucet.get().set_signer(if event_target_checked(&ev) { Signer::External } else { Signer::Faucet })
+                        }
+                    />
+                    "Sign with my own wallet instead of the faucet's key"
+                </label>
+            </Show>
             <div class="flex justify-between my-4">
                 <div>
                     <h3 class="text-lg font-semibold">Faucet Balance:</h3>
                     <Transition fallback={move || view!{ <p>Loading faucet balance...</p> }}>
                         <p class="text-xl">{ move || format_balance(&faucet.get().get_faucet_balance(), &faucet.get().get_fil_unit()) }</p>
                     </Transition>
+                    <p class="text-sm text-gray-600 break-all">
+                        { move || faucet.get().get_sender_address() }
+                        <CopyButton text=Signal::derive(move || faucet.get().get_sender_address()) />
+                    </p>
                 </div>
                 <div>
                     <h3 class="text-lg font-semibold">Target Balance:</h3>
                     <Transition fallback={move || view!{ <p>Loading target balance...</p> }}>
-                        <p class="text-xl">{ move || format_balance(&faucet.get().get_target_balance(), &faucet.get().get_fil_unit()) }</p>
+                        {move || match faucet.get().get_target_balance() {
+                            Some(balance) => view! {
+                                <p class="text-xl">{format_balance(&balance, &faucet.get().get_fil_unit())}</p>
+                            }.into_any(),
+                            None => view! {
+                                <p class="h-7 w-24 bg-gray-200 rounded animate-pulse" aria-label="Loading target balance"></p>
+                            }.into_any(),
+                        }}
+                        {move || {
+                            faucet.get().get_target_aliases().into_iter().map(|alias| view! {
+                                <p class="text-sm text-gray-600 break-all">"Also known as " {alias}</p>
+                            }).collect::<Vec<_>>()
+                        }}
                     </Transition>
                 </div>
             </div>
@@ -194,32 +399,69 @@ pub fn Faucet(target_network: Network) -> impl IntoView {
                 if !messages.is_empty() {
                     view! {
                         <div class="mt-4">
-                            <h3 class="text-lg font-semibold">Transactions:</h3>
+                            <h3 class="text-lg font-semibold">
+                                "Transactions:"
+                                <CopyButton
+                                    label="Copy pending CIDs"
+                                    text=Signal::derive(move || {
+                                        faucet
+                                            .get()
+                                            .get_sent_messages()
+                                            .iter()
+                                            .filter(|sent| !sent.is_confirmed())
+                                            .map(|sent| sent.cid.to_string())
+                                            .collect::<Vec<_>>()
+                                            .join("\n")
+                                    })
+                                />
+                            </h3>
+                            <label class="flex items-center text-sm text-gray-700">
+                                <input
+                                    type="checkbox"
+                                    class="mr-2"
+                                    prop:checked=move || faucet.get().get_show_confirmations()
+                                    on:change=move |ev| faucet.get().set_show_confirmations(event_target_checked(&ev))
+                                />
+                                "Show confirmations"
+                            </label>
                             <ul class="list-disc pl-5">
                                 {messages
                                     .into_iter()
-                                    .map(|(msg, sent)| {
-                                        let (cid, status) = if sent {
-                                            let cid = faucet_tx_base_url.get()
-                                                .as_ref()
-                                                .and_then(|base_url| format_url(base_url, SearchPath::Transaction ,&msg.to_string()).ok())
-                                                .map(|tx_url| {
-                                                    view! {
-                                                        <a href=tx_url.to_string() target="_blank" class="text-blue-600 hover:underline">
-                                                            {msg.to_string()}
-                                                        </a>
-                                                    }.into_any()
-                                                })
-                                                .unwrap_or_else(|| view! {{msg.to_string()}}.into_any());
-
-                                            (cid, "(confirmed)")
-                                        } else {
-                                            let cid = view! {{msg.to_string()}}.into_any();
-                                            (cid, "(pending)")
+                                    .map(|sent| {
+                                        let cid = sent.cid;
+                                        let sent_at = sent.sent_at;
+                                        let base = sent.is_confirmed().then(|| faucet_tx_base_url.get());
+                                        let status = {
+                                            let sent = sent.clone();
+                                            move || {
+                                                if let Some(exit_code) = sent.failed_exit_code() {
+                                                    return view! {
+                                                        <span class="text-red-600">{format!("failed (exit {})", exit_code.value())}</span>
+                                                    }
+                                                    .into_any();
+                                                }
+                                                let faucet = faucet.get();
+                                                sent.status_label(faucet.get_chain_height(), faucet.get_show_confirmations()).into_any()
+                                            }
+                                        };
+                                        let replaced = (!sent.replaces.is_empty()).then(|| {
+                                            sent.replaces.iter().map(|cid| cid.to_string()).collect::<Vec<_>>().join(", ")
+                                        });
+                                        let progress = move || {
+                                            format!("width: {:.1}%", sent.finality_progress(faucet.get().get_chain_height()) * 100.0)
                                         };
                                         view! {
                                             <li>
-                                                "CID:" {cid} {status}
+                                                "CID:" <TransactionLink cid=cid base=base network=target_network /> {status}
+                                                {replaced.map(|replaced| view! {
+                                                    <span class="text-sm text-gray-500 ml-2" title=replaced>
+                                                        "(replaced an earlier message)"
+                                                    </span>
+                                                })}
+                                                <span class="text-sm text-gray-500 ml-2">{move || format_time_ago(now.get() - sent_at)}</span>
+                                                <div class="w-full bg-gray-200 rounded-full h-1.5 mb-2" title="Progress towards finality">
+                                                    <div class="bg-green-500 h-1.5 rounded-full transition-all duration-1000 ease-out" style=progress></div>
+                                                </div>
                                             </li>
                                         }
                                     })
@@ -232,35 +474,69 @@ pub fn Faucet(target_network: Network) -> impl IntoView {
                     ().into_any()
                 }
             }}
+            <Show when=move || cfg!(debug_assertions)>
+                <SignatureCheck faucet=faucet />
+            </Show>
         </div>
         <div class="flex justify-center space-x-4">
         {move || {
-            match faucet_tx_base_url.get() {
-                Some(ref base_url) => match format_url(base_url, SearchPath::Address, &faucet.get().get_sender_address()) {
-                    Ok(addr_url) => view! {
-                        <button class="bg-blue-500 hover:bg-blue-700 text-white font-bold py-1 px-2 rounded-full">
-                            <a
-                                href={addr_url.to_string()}
-                                target="_blank"
-                                rel="noopener noreferrer"
-                            >
-                                "Transaction History"
-                            </a>
-                        </button>
-                    }
-                    .into_any(),
-                    Err(_) => ().into_any(),
-                },
-                None => ().into_any(),
+            match format_url(&faucet_tx_base_url.get(), SearchPath::Address, &faucet.get().get_sender_address()) {
+                Ok(addr_url) => view! {
+                    <button class="bg-blue-500 hover:bg-blue-700 text-white font-bold py-1 px-2 rounded-full">
+                        <a
+                            href={addr_url.to_string()}
+                            target="_blank"
+                            rel="noopener noreferrer"
+                        >
+                            "Transaction History"
+                        </a>
+                    </button>
+                }
+                .into_any(),
+                Err(_) => ().into_any(),
             }
         }}
-        <button class="bg-blue-500 hover:bg-blue-700 text-white font-bold py-1 px-2 rounded-full">
-            <a href="/faucet">Back to faucet list</a>
-        </button>
+        <Show when=move || !embed>
+            <button class="bg-blue-500 hover:bg-blue-700 text-white font-bold py-1 px-2 rounded-full">
+                <a href="/faucet">Back to faucet list</a>
+            </button>
+        </Show>
         </div>
     }
 }
 
+/// Banner telling which network the faucet pays out on. Dismissing it lasts until the browser
+/// session ends.
+#[component]
+fn NetworkBanner(network: Network) -> impl IntoView {
+    let (dismissed, set_dismissed) = use_stored(
+        StorageKind::Session,
+        format!("network-banner-dismissed-{}", network_name(network)),
+        false,
+    );
+    let (class, text) = match network {
+        Network::Testnet => (
+            "bg-yellow-100 border-yellow-400 text-yellow-800",
+            "Test network — tFIL has no value",
+        ),
+        Network::Mainnet => (
+            "bg-blue-100 border-blue-400 text-blue-800",
+            "Mainnet — this faucet sends real FIL",
+        ),
+    };
+
+    view! {
+        <Show when=move || !dismissed.get()>
+            <div class=format!("flex justify-between items-center max-w-2xl mx-auto mb-4 px-4 py-2 border rounded font-semibold {class}") role="status">
+                <span>{text}</span>
+                <button class="ml-4" title="Dismiss" on:click=move |_| set_dismissed.run(true)>
+                    "×"
+                </button>
+            </div>
+        </Show>
+    }
+}
+
 #[component]
 pub fn Faucets() -> impl IntoView {
     view! {
@@ -271,11 +547,125 @@ pub fn Faucets() -> impl IntoView {
                 <a class="text-blue-600" href="/faucet/calibnet">Calibration Network Faucet</a><br />
                 <a class="text-blue-600" href="/faucet/mainnet">Mainnet Network Faucet</a>
         </div>
+        <MultiNetworkDrip />
+    }
+}
+
+/// Checks the signature of the last message signed by the faucet against its sender. Only shown
+/// in debug builds, while working on the signing path.
+#[component]
+fn SignatureCheck(faucet: RwSignal<FaucetController>) -> impl IntoView {
+    move || {
+        faucet.get().get_last_signed().map(|smsg| {
+            let cid = message_cid(&smsg.message).to_bytes();
+            let from = smsg.message.from;
+            let signer = recover_signer(&smsg.signature, &cid)
+                .map(|signer| signer.to_string())
+                .unwrap_or_else(|e| format!("unknown ({e})"));
+            let check = match verify(&smsg.signature, &cid, &from) {
+                Ok(()) => format!("Signature is valid for the sender {from}"),
+                Err(e) => format!("Signature is NOT valid for the sender {from}: {e}"),
+            };
+            view! {
+                <div class="mt-4 p-2 border border-dashed border-gray-400 text-xs text-gray-600 break-all">
+                    <p class="font-semibold">"Signature check (debug build)"</p>
+                    <p>"Recovered signer: " {signer}</p>
+                    <p>{check}</p>
+                </div>
+            }
+        })
+    }
+}
+
+/// Drips to a single `0x` address on every network at once. Each network goes through its own
+/// faucet, so its amount and rate limit apply, and reports its own outcome. Networks that are
+/// busy or rate limited are skipped, so they don't hold back the others.
+#[component]
+fn MultiNetworkDrip() -> impl IntoView {
+    let target = RwSignal::new(String::new());
+    let faucets = [Network::Testnet, Network::Mainnet]
+        .map(|network| (network, RwSignal::new(FaucetController::new(network))));
+    let is_eth_address = move || matches!(parse_address_any(&target.get()), Ok((_, None)));
+    let is_available = |faucet: &FaucetController| {
+        !faucet.is_send_disabled() && faucet.get_send_rate_limit_remaining() <= 0
+    };
+
+    view! {
+        <div class="max-w-2xl mx-auto mt-8">
+            <h2 class="text-2xl font-bold mb-2">"Drip on all networks"</h2>
+            <div class="flex">
+                <input
+                    type="text"
+                    placeholder="0x..."
+                    data-shortcut-focus
+                    class="flex-grow border border-gray-300 p-2 rounded-l"
+                    prop:value=target
+                    on:input=move |ev| target.set(event_target_value(&ev))
+                />
+                <button
+                    class="bg-green-500 hover:bg-green-600 disabled:bg-gray-400 text-white font-bold py-2 px-4 rounded-r"
+                    disabled=move || {
+                        !is_eth_address() || !faucets.iter().any(|(_, faucet)| is_available(&faucet.get()))
+                    }
+                    on:click=move |_| {
+                        for (_, faucet) in faucets {
+                            let faucet = faucet.get();
+                            if !is_available(&faucet) {
+                                continue;
+                            }
+                            faucet.set_target_address(target.get());
+                            faucet.drip(false);
+                        }
+                    }
+                >
+                    "Send"
+                </button>
+            </div>
+            <ul class="mt-2">
+                {faucets
+                    .into_iter()
+                    .map(|(network, faucet)| {
+                        view! {
+                            <li>
+                                <span class="font-semibold">{network_name(network)} ": "</span>
+                                {move || {
+                                    let faucet = faucet.get();
+                                    if faucet.get_send_rate_limit_remaining() > 0 {
+                                        view! {
+                                            <span>"Rate limited, try again in " <Countdown deadline=faucet.send_rate_limit() /></span>
+                                        }
+                                        .into_any()
+                                    } else if faucet.is_send_disabled() {
+                                        view! { <span>"Sending..."</span> }.into_any()
+                                    } else if let Some((_, error)) = faucet.get_error_messages().pop() {
+                                        view! { <span class="text-red-600">{error}</span> }.into_any()
+                                    } else if let Some(sent) = faucet.get_sent_messages().pop() {
+                                        view! {
+                                            <span>"Sent " <TransactionLink cid=sent.cid base=Some(tx_base_url(network)) network /></span>
+                                        }
+                                        .into_any()
+                                    } else {
+                                        view! { <span class="text-gray-500">"Not requested"</span> }.into_any()
+                                    }
+                                }}
+                            </li>
+                        }
+                    })
+                    .collect_view()}
+            </ul>
+        </div>
     }
 }
 
+/// The settings of `network`'s faucet, from the runtime config.
+fn use_faucet_settings(network: Network) -> Signal<FaucetSettings> {
+    let config = ConfigContext::use_context();
+    Signal::derive(move || config.get().faucet_settings(network))
+}
+
 #[component]
 pub fn Faucet_Calibnet() -> impl IntoView {
+    let config = use_faucet_settings(Network::Testnet);
     let rpc_context = RpcContext::use_context();
     // Set rpc context to calibnet url
     rpc_context.set(Provider::get_network_url(Network::Testnet));
@@ -285,16 +675,18 @@ pub fn Faucet_Calibnet() -> impl IntoView {
         <Meta name="description" content="Filecoin Calibration Network Faucet dispensing tokens for testing purposes." />
         <div>
             <h1 class="text-4xl font-bold mb-6 text-center">Filecoin Calibnet Faucet</h1>
+            <NetworkBanner network=Network::Testnet />
             <Faucet target_network=Network::Testnet />
         </div>
         <div class="text-center mt-4">
-            "This faucet distributes " { format_balance(&crate::constants::CALIBNET_DRIP_AMOUNT, crate::constants::FIL_CALIBNET_UNIT) } " per request. It is rate-limited to 1 request per " {crate::constants::RATE_LIMIT_SECONDS} " seconds. Farming is discouraged and will result in more stringent rate limiting in the future and/or permanent bans."
+            "This faucet distributes " { move || config.with(|config| format_balance(&config.drip_amount, &config.unit)) } " per request. It is rate-limited to 1 request per " { move || config.get().rate_limit_seconds } " seconds. Farming is discouraged and will result in more stringent rate limiting in the future and/or permanent bans."
         </div>
     }
 }
 
 #[component]
 pub fn Faucet_Mainnet() -> impl IntoView {
+    let config = use_faucet_settings(Network::Mainnet);
     let rpc_context = RpcContext::use_context();
     // Set rpc context to mainnet url
     rpc_context.set(Provider::get_network_url(Network::Mainnet));
@@ -304,10 +696,66 @@ pub fn Faucet_Mainnet() -> impl IntoView {
         <Meta name="description" content="Filecoin Mainnet Faucet dispensing tokens for testing purposes." />
         <div>
             <h1 class="text-4xl font-bold mb-6 text-center">Filecoin Mainnet Faucet</h1>
+            <NetworkBanner network=Network::Mainnet />
             <Faucet target_network=Network::Mainnet />
         <div class="text-center mt-4">
-            "This faucet distributes " { format_balance(&crate::constants::MAINNET_DRIP_AMOUNT, crate::constants::FIL_MAINNET_UNIT) } " per request. It is rate-limited to 1 request per " {crate::constants::RATE_LIMIT_SECONDS} " seconds. Farming is discouraged and will result in more stringent rate limiting in the future and/or permanent bans or service termination. Faucet funds are limited and may run out. They are replenished periodically."
+            "This faucet distributes " { move || config.with(|config| format_balance(&config.drip_amount, &config.unit)) } " per request. It is rate-limited to 1 request per " { move || config.get().rate_limit_seconds } " seconds. Farming is discouraged and will result in more stringent rate limiting in the future and/or permanent bans or service termination. Faucet funds are limited and may run out. They are replenished periodically."
         </div>
         </div>
     }
 }
+
+/// Stripped-down faucet for embedding in an iframe, e.g. `/faucet/calibnet/embed`.
+#[component]
+pub fn Faucet_Embed(target_network: Network) -> impl IntoView {
+    let rpc_context = RpcContext::use_context();
+    rpc_context.set(Provider::get_network_url(target_network));
+
+    view! { <Faucet target_network=target_network embed=true /> }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_context::mock::MockProvider;
+
+    /// Checks that the send control shows `label`. Views only render to HTML with the `ssr`
+    /// feature.
+    fn assert_renders(faucet: &FaucetController<MockProvider>, label: &str) {
+        #[cfg(feature = "ssr")]
+        {
+            let html = view! { <SendControl faucet=RwSignal::new(faucet.clone()) /> }.to_html();
+            assert!(html.contains(label), "{label} not in {html}");
+        }
+        #[cfg(not(feature = "ssr"))]
+        let _ = (faucet, label);
+    }
+
+    #[test]
+    fn test_send_control() {
+        for network in [Network::Testnet, Network::Mainnet] {
+            let faucet = FaucetController::mocked(network, MockProvider::default());
+
+            // The faucet's balance never loads without a server, so it can't cover a drip.
+            assert_eq!(SendButton::of(&faucet), SendButton::TopUp);
+            assert_renders(&faucet, "Request Faucet Top-up");
+
+            faucet
+                .send_rate_limit()
+                .set(Some(Utc::now() + chrono::Duration::seconds(60)));
+            assert_eq!(SendButton::of(&faucet), SendButton::RateLimited);
+            assert_renders(&faucet, "Rate-limited!");
+
+            faucet.set_send_disabled(true);
+            assert_eq!(SendButton::of(&faucet), SendButton::Sending);
+            assert_renders(&faucet, "Sending...");
+
+            // Drips paid from the user's own wallet don't depend on the faucet's balance.
+            faucet.set_send_disabled(false);
+            faucet.send_rate_limit().set(None);
+            faucet.set_signer(Signer::External);
+            assert_eq!(SendButton::of(&faucet), SendButton::Ready);
+            assert_renders(&faucet, ">Send</button>");
+        }
+    }
+}
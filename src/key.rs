@@ -1,10 +1,15 @@
-use anyhow::{Context as _, Result};
-use bls_signatures::{PrivateKey as BlsPrivate, Serialize as _};
+use anyhow::{bail, ensure, Context as _, Result};
+use bls_signatures::{
+    PrivateKey as BlsPrivate, PublicKey as BlsPublic, Serialize as _, Signature as BlsSignature,
+};
 use libsecp256k1::{PublicKey as SecpPublic, SecretKey as SecpPrivate};
 use serde::{Deserialize, Serialize};
 use std::{convert::TryFrom, str::FromStr};
 
-use fvm_shared::{address::Address, crypto::signature::SignatureType};
+use fvm_shared::{
+    address::{Address, Payload},
+    crypto::signature::{Signature, SignatureType},
+};
 
 /// Return the public key for a given private key and `SignatureType`
 pub fn to_public(sig_type: SignatureType, private_key: &[u8]) -> Result<Vec<u8>> {
@@ -78,6 +83,12 @@ impl FromStr for KeyInfo {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Tolerate keys copied with a `0x` prefix or a trailing newline.
+        let s = s.trim();
+        let s = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
         let decoded_key = hex::decode(s).context("Key must be hex encoded")?;
 
         let key_str = std::str::from_utf8(&decoded_key)?;
@@ -87,7 +98,6 @@ impl FromStr for KeyInfo {
     }
 }
 
-#[cfg(feature = "ssr")]
 /// Generates BLAKE2b hash of fixed 32 bytes size.
 pub fn blake2b_256(ingest: &[u8]) -> [u8; 32] {
     use blake2b_simd::Params;
@@ -106,12 +116,7 @@ pub fn blake2b_256(ingest: &[u8]) -> [u8; 32] {
 #[cfg(feature = "ssr")]
 /// Sign takes in `SignatureType`, private key and message. Returns a Signature
 /// for that message
-pub fn sign(
-    sig_type: SignatureType,
-    private_key: &[u8],
-    msg: &[u8],
-) -> Result<fvm_shared::crypto::signature::Signature> {
-    use fvm_shared::crypto::signature::Signature;
+pub fn sign(sig_type: SignatureType, private_key: &[u8], msg: &[u8]) -> Result<Signature> {
     use libsecp256k1::Message as SecpMessage;
     match sig_type {
         SignatureType::BLS => {
@@ -136,13 +141,87 @@ pub fn sign(
     }
 }
 
+/// Address of the key which produced the Secp256k1 `signature` over `msg`. BLS signatures can't be
+/// attributed without knowing the signer, see [`verify`].
+pub fn recover_signer(signature: &Signature, msg: &[u8]) -> Result<Address> {
+    use libsecp256k1::{Message as SecpMessage, RecoveryId, Signature as SecpSignature};
+
+    ensure!(
+        signature.signature_type() == SignatureType::Secp256k1,
+        "Only Secp256k1 signatures reveal their signer"
+    );
+    let bytes = signature.bytes();
+    ensure!(bytes.len() == 65, "Invalid Secp256k1 signature length");
+    let public_key = libsecp256k1::recover(
+        &SecpMessage::parse(&blake2b_256(msg)),
+        &SecpSignature::parse_standard_slice(&bytes[..64])?,
+        &RecoveryId::parse(bytes[64])?,
+    )?;
+    new_address(SignatureType::Secp256k1, &public_key.serialize())
+}
+
+/// Checks that `signature` over `msg` was produced by the key behind `addr`.
+pub fn verify(signature: &Signature, msg: &[u8], addr: &Address) -> Result<()> {
+    match signature.signature_type() {
+        SignatureType::Secp256k1 => {
+            let signer = recover_signer(signature, msg)?;
+            ensure!(signer == *addr, "Signed by {signer}, not {addr}");
+        }
+        SignatureType::BLS => {
+            let Payload::BLS(public_key) = addr.payload() else {
+                bail!("{addr} is not a BLS address");
+            };
+            let public_key = BlsPublic::from_bytes(public_key)?;
+            let signature = BlsSignature::from_bytes(signature.bytes())?;
+            ensure!(
+                bls_signatures::verify_messages(&signature, &[msg], &[public_key]),
+                "Not signed by {addr}"
+            );
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn test_verify() {
+        for sig_type in [SignatureType::Secp256k1, SignatureType::BLS] {
+            let private_key = match sig_type {
+                SignatureType::Secp256k1 => [7; 32].to_vec(),
+                SignatureType::BLS => BlsPrivate::new([7; 32]).as_bytes(),
+            };
+            let addr = new_address(sig_type, &to_public(sig_type, &private_key).unwrap()).unwrap();
+            let signature = sign(sig_type, &private_key, b"drip").unwrap();
+            verify(&signature, b"drip", &addr).unwrap();
+            assert!(verify(&signature, b"drop", &addr).is_err());
+            assert!(verify(&signature, b"drip", &Address::new_id(1)).is_err());
+            if sig_type == SignatureType::Secp256k1 {
+                assert_eq!(recover_signer(&signature, b"drip").unwrap(), addr);
+            } else {
+                assert!(recover_signer(&signature, b"drip").is_err());
+            }
+        }
+    }
+
     #[test]
     fn test_key_info_from_str() {
         let key_info = KeyInfo::from_str("7b2254797065223a312c22507269766174654b6579223a2272744f75762f386664316d72535570313970487064645479392b67756e7376656a786e317950356b6869493d227d").unwrap();
         assert_eq!(key_info.r#type, SignatureType::Secp256k1);
     }
+
+    #[test]
+    fn test_key_info_from_str_0x_prefix() {
+        const KEY: &str = "7b2254797065223a312c22507269766174654b6579223a2272744f75762f386664316d72535570313970487064645479392b67756e7376656a786e317950356b6869493d227d";
+        let key_info = KeyInfo::from_str(KEY).unwrap();
+        for prefixed in [format!("0x{KEY}"), format!(" 0X{KEY}\n")] {
+            let prefixed = KeyInfo::from_str(&prefixed).unwrap();
+            assert_eq!(prefixed.r#type, key_info.r#type);
+            assert_eq!(prefixed.private_key, key_info.private_key);
+        }
+        assert!(KeyInfo::from_str("0x").is_err());
+    }
 }
@@ -1,15 +1,31 @@
+mod actor;
 mod app;
 mod rpc_context;
 #[cfg(feature = "hydrate")]
 use app::App;
 mod address;
+mod blocks;
+mod components;
+mod config;
 mod constants;
+#[cfg(feature = "ssr")]
+mod cors;
+#[cfg(feature = "ssr")]
+mod drip_log;
 mod faucet;
 mod key;
 mod lotus_json;
+mod market;
 mod message;
+mod miner;
+mod network_version;
+#[cfg(feature = "ssr")]
+mod nonce_manager;
 #[cfg(feature = "ssr")]
 mod rate_limiter;
+mod storage;
+#[cfg(feature = "ssr")]
+mod store;
 mod utils;
 
 #[cfg(feature = "hydrate")]
@@ -26,14 +42,23 @@ mod ssr_imports {
 
     use crate::{
         app::{shell, App},
+        cors::{cors, frame_ancestors, AllowedOrigins},
         faucet,
     };
-    use axum::{routing::post, Extension, Router};
+    use axum::{middleware, routing::post, Extension, Router};
     use leptos::prelude::*;
     use leptos_axum::{generate_route_list, LeptosRoutes};
     use worker::{event, Context, Env, HttpRequest, Result};
 
-    fn router(env: Env) -> Router {
+    /// The server functions. CORS wraps the whole route rather than its handlers, so preflight
+    /// requests reach it instead of being refused with `405 Method Not Allowed`.
+    pub(crate) fn api_router<S: Clone + Send + Sync + 'static>() -> Router<S> {
+        Router::new()
+            .route("/api/*fn_name", post(leptos_axum::handle_server_fns))
+            .layer(middleware::from_fn(cors))
+    }
+
+    fn router(env: Env, ctx: Context) -> Router {
         let leptos_options = LeptosOptions::builder()
             .output_name("client")
             .site_pkg_dir("pkg")
@@ -42,33 +67,40 @@ mod ssr_imports {
 
         // build our application with a route
         let app: axum::Router<()> = Router::new()
+            .merge(api_router())
             .leptos_routes(&leptos_options, routes, {
                 let leptos_options = leptos_options.clone();
                 move || shell(leptos_options.clone())
             })
-            .route("/api/*fn_name", post(leptos_axum::handle_server_fns))
             .with_state(leptos_options)
-            .layer(Extension(Arc::new(env)));
+            .layer(middleware::from_fn(frame_ancestors))
+            .layer(Extension(AllowedOrigins::from_env(&env)))
+            .layer(Extension(Arc::new(env)))
+            .layer(Extension(Arc::new(ctx)));
         app
     }
 
     #[event(start)]
     fn register() {
         server_fn::axum::register_explicit::<faucet::utils::SignWithSecretKey>();
+        server_fn::axum::register_explicit::<faucet::utils::SignBatchWithSecretKey>();
         server_fn::axum::register_explicit::<faucet::utils::FaucetAddress>();
+        server_fn::axum::register_explicit::<faucet::utils::FaucetStatus>();
+        server_fn::axum::register_explicit::<faucet::utils::FaucetTransactions>();
+        server_fn::axum::register_explicit::<crate::config::RuntimeConfig>();
     }
 
     #[event(fetch)]
     async fn fetch(
         req: HttpRequest,
         env: Env,
-        _ctx: Context,
+        ctx: Context,
     ) -> Result<axum::http::Response<axum::body::Body>> {
         _ = console_log::init_with_level(log::Level::Debug);
         use tower_service::Service;
 
         console_error_panic_hook::set_once();
 
-        Ok(router(env).call(req).await?)
+        Ok(router(env, ctx).call(req).await?)
     }
 }
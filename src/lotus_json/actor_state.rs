@@ -0,0 +1,88 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::*;
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::state::ActorState;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ActorStateLotusJson {
+    #[serde(with = "crate::lotus_json")]
+    code: Cid,
+    #[serde(with = "crate::lotus_json")]
+    head: Cid,
+    nonce: u64,
+    #[serde(with = "crate::lotus_json")]
+    balance: TokenAmount,
+    #[serde(with = "crate::lotus_json", default)]
+    delegated_address: Option<Address>,
+}
+
+impl HasLotusJson for ActorState {
+    type LotusJson = ActorStateLotusJson;
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        let Self {
+            code,
+            state,
+            sequence,
+            balance,
+            delegated_address,
+        } = self;
+        Self::LotusJson {
+            code,
+            head: state,
+            nonce: sequence,
+            balance,
+            delegated_address,
+        }
+    }
+
+    fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+        let Self::LotusJson {
+            code,
+            head,
+            nonce,
+            balance,
+            delegated_address,
+        } = lotus_json;
+        Self {
+            code,
+            state: head,
+            sequence: nonce,
+            balance,
+            delegated_address,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_actor_state_snapshot() {
+        let json = serde_json::json!({
+            "Code": { "/": "baeaaaaa" },
+            "Head": { "/": "baeaaaaa" },
+            "Nonce": 42,
+            "Balance": "1000",
+            "DelegatedAddress": null
+        });
+        let LotusJson(state) =
+            serde_json::from_value::<LotusJson<ActorState>>(json.clone()).unwrap();
+        assert_eq!(
+            state,
+            ActorState {
+                code: Cid::default(),
+                state: Cid::default(),
+                sequence: 42,
+                balance: TokenAmount::from_atto(1000),
+                delegated_address: None,
+            }
+        );
+        assert_eq!(serde_json::to_value(LotusJson(state)).unwrap(), json);
+    }
+}
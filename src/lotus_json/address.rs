@@ -45,3 +45,36 @@ impl<'de> Deserialize<'de> for AddressLotusJson {
             .map_err(serde::de::Error::custom)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    // The address string form depends on the global network, so tests that change it are forked.
+    use fvm_shared::address::set_current_network;
+    use rusty_fork::rusty_fork_test;
+
+    fn assert_round_trip(addr_str: &str) {
+        let addr = parse_address(addr_str).unwrap();
+        let json = serde_json::to_value(LotusJson(addr)).unwrap();
+        assert_eq!(json, serde_json::Value::String(addr_str.to_string()));
+
+        let LotusJson(deserialized) = serde_json::from_value::<LotusJson<Address>>(json).unwrap();
+        assert_eq!(deserialized, addr);
+    }
+
+    rusty_fork_test! {
+    #[test]
+    fn test_delegated_address_round_trip_mainnet() {
+        set_current_network(Network::Mainnet);
+        assert_round_trip("f410f2oekwcmo2pueydmaq53eic2i62crtbeyuzx2gmy");
+    }
+    }
+
+    rusty_fork_test! {
+    #[test]
+    fn test_delegated_address_round_trip_testnet() {
+        set_current_network(Network::Testnet);
+        assert_round_trip("t410f2oekwcmo2pueydmaq53eic2i62crtbeyuzx2gmy");
+    }
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::*;
+
+/// Fixed-size byte arrays are base64 strings, like [`Vec<u8>`], but must decode to exactly `N`
+/// bytes.
+#[derive(Clone)]
+pub struct ArrayU8LotusJson<const N: usize>([u8; N]);
+
+impl<const N: usize> Serialize for ArrayU8LotusJson<N> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        base64_standard::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for ArrayU8LotusJson<N> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let bytes = base64_standard::deserialize(deserializer)?;
+        let len = bytes.len();
+        bytes
+            .try_into()
+            .map(Self)
+            .map_err(|_| serde::de::Error::invalid_length(len, &format!("{N} bytes").as_str()))
+    }
+}
+
+impl<const N: usize> HasLotusJson for [u8; N] {
+    type LotusJson = ArrayU8LotusJson<N>;
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        ArrayU8LotusJson(self)
+    }
+
+    fn from_lotus_json(ArrayU8LotusJson(bytes): Self::LotusJson) -> Self {
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_array_u8_round_trip() {
+        let json = serde_json::json!("AQID");
+        let LotusJson(bytes) = serde_json::from_value::<LotusJson<[u8; 3]>>(json.clone()).unwrap();
+        assert_eq!(bytes, [1, 2, 3]);
+        assert_eq!(serde_json::to_value(LotusJson(bytes)).unwrap(), json);
+
+        let root = [7u8; 32];
+        let json = serde_json::to_value(LotusJson(root)).unwrap();
+        let LotusJson(decoded) = serde_json::from_value::<LotusJson<[u8; 32]>>(json).unwrap();
+        assert_eq!(decoded, root);
+    }
+
+    #[test]
+    fn test_array_u8_length_mismatch() {
+        let err =
+            serde_json::from_value::<LotusJson<[u8; 32]>>(serde_json::json!("AQID")).unwrap_err();
+        assert!(err.to_string().contains("invalid length 3"));
+        assert!(serde_json::from_value::<LotusJson<[u8; 2]>>(serde_json::json!("AQID")).is_err());
+    }
+}
@@ -11,6 +11,18 @@ pub struct BigIntLotusJson(#[serde(with = "crate::lotus_json::stringify")] BigIn
 impl HasLotusJson for BigInt {
     type LotusJson = BigIntLotusJson;
 
+    #[cfg(test)]
+    fn snapshots() -> Vec<(serde_json::Value, Self)> {
+        vec![
+            (serde_json::json!("0"), BigInt::from(0)),
+            (serde_json::json!("-1"), BigInt::from(-1)),
+            (
+                serde_json::json!("340282366920938463463374607431768211456"),
+                BigInt::from(u128::MAX) + 1,
+            ),
+        ]
+    }
+
     fn into_lotus_json(self) -> Self::LotusJson {
         BigIntLotusJson(self)
     }
@@ -19,3 +31,22 @@ impl HasLotusJson for BigInt {
         big_int
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng as _;
+
+    #[test]
+    fn test_snapshots() {
+        assert_all_snapshots::<BigInt>();
+    }
+
+    #[test]
+    fn test_unchanged_via_json() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            assert_unchanged_via_json(BigInt::from(rng.gen::<i128>()));
+        }
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::*;
+use fvm_ipld_bitfield::{iter::Ranges, BitField};
+use std::ops::Range;
+
+/// Bitfields are serialized as the lengths of their runs, starting with a run of unset bits, e.g.
+/// `[2, 4, 3, 2]` for bits 2-5 and 9-10. An empty bitfield is `[0]`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct BitFieldLotusJson(Vec<u64>);
+
+impl HasLotusJson for BitField {
+    type LotusJson = BitFieldLotusJson;
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        let mut runs = Vec::new();
+        let mut end = 0;
+        for range in self.ranges() {
+            runs.push(range.start - end);
+            runs.push(range.end - range.start);
+            end = range.end;
+        }
+        if runs.is_empty() {
+            runs.push(0);
+        }
+        BitFieldLotusJson(runs)
+    }
+
+    fn from_lotus_json(BitFieldLotusJson(runs): Self::LotusJson) -> Self {
+        let mut ranges: Vec<Range<u64>> = Vec::new();
+        let mut start = 0u64;
+        for (i, len) in runs.into_iter().enumerate() {
+            let end = start.saturating_add(len);
+            if i % 2 == 1 && len > 0 {
+                // Merge runs split by an empty run of unset bits.
+                match ranges.last_mut() {
+                    Some(last) if last.end == start => last.end = end,
+                    _ => ranges.push(start..end),
+                }
+            }
+            start = end;
+        }
+        BitField::from_ranges(Ranges::new(ranges))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bit_field_snapshot() {
+        let json = serde_json::json!([2, 4, 3, 2]);
+        let LotusJson(bit_field) =
+            serde_json::from_value::<LotusJson<BitField>>(json.clone()).unwrap();
+        assert_eq!(
+            bit_field.iter().collect::<Vec<_>>(),
+            vec![2, 3, 4, 5, 9, 10]
+        );
+        assert_eq!(serde_json::to_value(LotusJson(bit_field)).unwrap(), json);
+    }
+
+    #[test]
+    fn test_bit_field_round_trip() {
+        for bits in [vec![], vec![0], vec![0, 1, 2, 7], vec![5, 1000]] {
+            let bit_field = BitField::try_from_bits(bits.clone()).unwrap();
+            let json = serde_json::to_string(&LotusJson(bit_field)).unwrap();
+            let LotusJson(bit_field) = serde_json::from_str::<LotusJson<BitField>>(&json).unwrap();
+            assert_eq!(bit_field.iter().collect::<Vec<_>>(), bits);
+        }
+        let LotusJson(empty) = serde_json::from_str::<LotusJson<BitField>>("[0]").unwrap();
+        assert!(empty.is_empty());
+        let LotusJson(merged) = serde_json::from_str::<LotusJson<BitField>>("[1,1,0,1]").unwrap();
+        assert_eq!(merged.iter().collect::<Vec<_>>(), vec![1, 2]);
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! The lotus JSON of [`BlockHeader`](crate::blocks::BlockHeader) is derived next to its
+//! definition.
+
+#[cfg(test)]
+mod tests {
+    use crate::blocks::{BlockHeader, ElectionProof, Ticket, TipSetKey, VRFProof};
+    use crate::lotus_json::LotusJson;
+    use cid::Cid;
+    use fvm_shared::{address::Address, bigint::BigInt};
+    use std::str::FromStr as _;
+
+    const CID: &str = "bafy2bzacea3wsdh6y3a36tb3skempjoxqpuyompjbmfeyf34fi3uy6uue42v4";
+
+    #[test]
+    fn test_block_header_snapshot() {
+        let json = serde_json::json!({
+            "Miner": "f01234",
+            "Ticket": { "VRFProof": "AQI=" },
+            "ElectionProof": { "WinCount": 1, "VRFProof": "AwQ=" },
+            "Parents": [{ "/": CID }],
+            "ParentWeight": "118842335",
+            "Height": 4_000_000,
+            "Messages": { "/": CID },
+            "Timestamp": 1_720_000_000
+        });
+        let LotusJson(header) =
+            serde_json::from_value::<LotusJson<BlockHeader>>(json.clone()).unwrap();
+        let cid = Cid::from_str(CID).unwrap();
+        assert_eq!(
+            header,
+            BlockHeader {
+                miner: Address::new_id(1234),
+                ticket: Some(Ticket {
+                    vrfproof: VRFProof(vec![1, 2])
+                }),
+                election_proof: Some(ElectionProof {
+                    win_count: 1,
+                    vrfproof: VRFProof(vec![3, 4])
+                }),
+                parents: TipSetKey(vec![cid]),
+                parent_weight: BigInt::from(118_842_335),
+                height: 4_000_000,
+                messages: cid,
+                timestamp: 1_720_000_000,
+            }
+        );
+        assert_eq!(serde_json::to_value(LotusJson(header)).unwrap(), json);
+    }
+
+    #[test]
+    fn test_genesis_block_header() {
+        let json = serde_json::json!({
+            "Miner": "f00",
+            "Ticket": null,
+            "ElectionProof": null,
+            "Parents": [{ "/": CID }],
+            "ParentWeight": "0",
+            "Height": 0,
+            "Messages": { "/": CID },
+            "Timestamp": 1_598_306_400,
+            "BLSAggregate": null
+        });
+        let LotusJson(header) = serde_json::from_value::<LotusJson<BlockHeader>>(json).unwrap();
+        assert_eq!(header.ticket, None);
+        assert_eq!(header.election_proof, None);
+        assert_eq!(header.height, 0);
+    }
+}
@@ -12,6 +12,14 @@ pub struct CidLotusJson {
 impl HasLotusJson for ::cid::Cid {
     type LotusJson = CidLotusJson;
 
+    #[cfg(test)]
+    fn snapshots() -> Vec<(serde_json::Value, Self)> {
+        vec![(
+            serde_json::json!({ "/": "baeaaaaa" }),
+            ::cid::Cid::default(),
+        )]
+    }
+
     fn into_lotus_json(self) -> Self::LotusJson {
         Self::LotusJson { slash: self }
     }
@@ -21,3 +29,27 @@ impl HasLotusJson for ::cid::Cid {
         slash
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use multihash_codetable::{Code, MultihashDigest as _};
+    use rand::Rng as _;
+
+    #[test]
+    fn test_snapshots() {
+        assert_all_snapshots::<Cid>();
+    }
+
+    #[test]
+    fn test_unchanged_via_json() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let data = rng.gen::<[u8; 32]>();
+            assert_unchanged_via_json(Cid::new_v1(
+                fvm_ipld_encoding::DAG_CBOR,
+                Code::Blake2b256.digest(&data),
+            ));
+        }
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::*;
+
+use crate::market::DealProposal;
+use fvm_shared::{address::Address, clock::ChainEpoch, econ::TokenAmount, piece::PaddedPieceSize};
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DealProposalLotusJson {
+    #[serde(rename = "PieceCID", with = "crate::lotus_json")]
+    piece_cid: Cid,
+    piece_size: u64,
+    verified_deal: bool,
+    #[serde(with = "crate::lotus_json")]
+    client: Address,
+    #[serde(with = "crate::lotus_json")]
+    provider: Address,
+    #[serde(default)]
+    label: String,
+    start_epoch: ChainEpoch,
+    end_epoch: ChainEpoch,
+    #[serde(with = "crate::lotus_json")]
+    storage_price_per_epoch: TokenAmount,
+    #[serde(with = "crate::lotus_json")]
+    provider_collateral: TokenAmount,
+    #[serde(with = "crate::lotus_json")]
+    client_collateral: TokenAmount,
+}
+
+impl HasLotusJson for DealProposal {
+    type LotusJson = DealProposalLotusJson;
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        let Self {
+            piece_cid,
+            piece_size: PaddedPieceSize(piece_size),
+            verified_deal,
+            client,
+            provider,
+            label,
+            start_epoch,
+            end_epoch,
+            storage_price_per_epoch,
+            provider_collateral,
+            client_collateral,
+        } = self;
+        Self::LotusJson {
+            piece_cid,
+            piece_size,
+            verified_deal,
+            client,
+            provider,
+            label,
+            start_epoch,
+            end_epoch,
+            storage_price_per_epoch,
+            provider_collateral,
+            client_collateral,
+        }
+    }
+
+    fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+        let Self::LotusJson {
+            piece_cid,
+            piece_size,
+            verified_deal,
+            client,
+            provider,
+            label,
+            start_epoch,
+            end_epoch,
+            storage_price_per_epoch,
+            provider_collateral,
+            client_collateral,
+        } = lotus_json;
+        Self {
+            piece_cid,
+            piece_size: PaddedPieceSize(piece_size),
+            verified_deal,
+            client,
+            provider,
+            label,
+            start_epoch,
+            end_epoch,
+            storage_price_per_epoch,
+            provider_collateral,
+            client_collateral,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::{DealState, MarketDeal};
+
+    #[test]
+    fn test_deal_proposal_snapshot() {
+        // The `result` of a `Filecoin.StateMarketStorageDeal` call for a 2 KiB devnet deal.
+        let response = serde_json::json!({
+            "Proposal": {
+                "PieceCID": { "/": "baga6ea4seaqao7s73y24kcutaosvacpdjgfe5pw76ooefnyqw4ynr3d2y6x2mpq" },
+                "PieceSize": 2048,
+                "VerifiedDeal": false,
+                "Client": "f01002",
+                "Provider": "f01000",
+                "Label": "bafk2bzacedsbfhk5ddlrjwuciwz5vb2nu4uxiej5dc7bzqd4tbsamzihqe6uc",
+                "StartEpoch": 1020,
+                "EndEpoch": 519420,
+                "StoragePricePerEpoch": "976562",
+                "ProviderCollateral": "0",
+                "ClientCollateral": "0"
+            },
+            "State": {
+                "SectorNumber": 2,
+                "SectorStartEpoch": 1001,
+                "LastUpdatedEpoch": 3120,
+                "SlashEpoch": -1
+            }
+        });
+        let deal: MarketDeal = serde_json::from_value(response.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&deal).unwrap(), response);
+
+        let json = response["Proposal"].clone();
+        let proposal = deal.proposal;
+        assert_eq!(
+            proposal.piece_cid,
+            Cid::from_str("baga6ea4seaqao7s73y24kcutaosvacpdjgfe5pw76ooefnyqw4ynr3d2y6x2mpq")
+                .unwrap()
+        );
+        assert_eq!(proposal.piece_size, PaddedPieceSize(2048));
+        assert_eq!(proposal.client, Address::new_id(1002));
+        assert_eq!(proposal.provider, Address::new_id(1000));
+        assert_eq!(
+            proposal.storage_price_per_epoch,
+            TokenAmount::from_atto(976562)
+        );
+        assert_eq!(serde_json::to_value(LotusJson(proposal)).unwrap(), json);
+    }
+
+    #[test]
+    fn test_market_deal() {
+        let json = serde_json::json!({
+            "Proposal": {
+                "PieceCID": { "/": "baga6ea4seaqao7s73y24kcutaosvacpdjgfe5pw76ooefnyqw4ynr3d2y6x2mpq" },
+                "PieceSize": 2048,
+                "VerifiedDeal": true,
+                "Client": "f01002",
+                "Provider": "f01000",
+                "Label": "",
+                "StartEpoch": 1020,
+                "EndEpoch": 519420,
+                "StoragePricePerEpoch": "0",
+                "ProviderCollateral": "0",
+                "ClientCollateral": "0"
+            },
+            "State": {
+                "SectorNumber": 7,
+                "SectorStartEpoch": 1001,
+                "LastUpdatedEpoch": -1,
+                "SlashEpoch": -1
+            }
+        });
+        let deal: MarketDeal = serde_json::from_value(json).unwrap();
+        assert!(deal.proposal.verified_deal);
+        assert_eq!(
+            deal.state,
+            DealState {
+                sector_number: 7,
+                sector_start_epoch: 1001,
+                last_updated_epoch: -1,
+                slash_epoch: -1
+            }
+        );
+    }
+}
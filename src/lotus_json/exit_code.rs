@@ -0,0 +1,38 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::*;
+use fvm_shared::error::ExitCode;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ExitCodeLotusJson(u32);
+
+impl HasLotusJson for ExitCode {
+    type LotusJson = ExitCodeLotusJson;
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        ExitCodeLotusJson(self.value())
+    }
+
+    fn from_lotus_json(ExitCodeLotusJson(code): Self::LotusJson) -> Self {
+        ExitCode::new(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_code_snapshot() {
+        let LotusJson(ok) = serde_json::from_value::<LotusJson<ExitCode>>(0.into()).unwrap();
+        assert!(ok.is_success());
+        assert_eq!(ok, ExitCode::OK);
+
+        let json = serde_json::json!(18);
+        let LotusJson(code) = serde_json::from_value::<LotusJson<ExitCode>>(json.clone()).unwrap();
+        assert_eq!(code, ExitCode::USR_FORBIDDEN);
+        assert_eq!(serde_json::to_value(LotusJson(code)).unwrap(), json);
+    }
+}
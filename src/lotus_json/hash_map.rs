@@ -0,0 +1,25 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::*;
+use std::collections::HashMap;
+
+impl<V> HasLotusJson for HashMap<String, V>
+where
+    V: HasLotusJson,
+{
+    type LotusJson = HashMap<String, V::LotusJson>;
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        self.into_iter()
+            .map(|(k, v)| (k, v.into_lotus_json()))
+            .collect()
+    }
+
+    fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+        lotus_json
+            .into_iter()
+            .map(|(k, v)| (k, V::from_lotus_json(v)))
+            .collect()
+    }
+}
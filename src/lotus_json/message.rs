@@ -28,6 +28,7 @@ pub struct MessageLotusJson {
     gas_premium: TokenAmount,
     #[serde(default)]
     method: u64,
+    /// Empty params, e.g. for `Send`, are serialized as `null`, like Lotus does.
     #[serde(
         with = "crate::lotus_json",
         skip_serializing_if = "Option::is_none",
@@ -93,3 +94,60 @@ impl HasLotusJson for Message {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fvm_shared::address::{set_current_network, Network};
+    use rusty_fork::rusty_fork_test;
+
+    rusty_fork_test! {
+    #[test]
+    fn test_send_message_snapshot() {
+        set_current_network(Network::Mainnet);
+        let mut msg = crate::message::message_transfer(
+            Address::new_id(1),
+            Address::new_id(1234),
+            TokenAmount::from_atto(1000),
+        );
+        msg.sequence = 7;
+        assert_eq!(
+            serde_json::to_value(LotusJson(msg)).unwrap(),
+            serde_json::json!({
+                "Version": 0,
+                "To": "f01234",
+                "From": "f01",
+                "Nonce": 7,
+                "Value": "1000",
+                "GasLimit": 0,
+                "GasFeeCap": "0",
+                "GasPremium": "0",
+                "Method": 0,
+                "Params": null
+            })
+        );
+    }
+    }
+
+    #[test]
+    fn test_message_ignores_unknown_fields() {
+        let json = serde_json::json!({
+            "Version": 0,
+            "To": "f01234",
+            "From": "f01",
+            "Nonce": 7,
+            "Value": "1000",
+            "GasLimit": 0,
+            "GasFeeCap": "0",
+            "GasPremium": "0",
+            "Method": 0,
+            "Params": null,
+            "CID": { "/": "bafy2bzacea" },
+            "SomeFutureField": [1, 2, 3]
+        });
+        let LotusJson(message) = serde_json::from_value::<LotusJson<Message>>(json).unwrap();
+        assert_eq!(message.to, Address::new_id(1234));
+        assert_eq!(message.sequence, 7);
+        assert_eq!(message.value, TokenAmount::from_atto(1000));
+    }
+}
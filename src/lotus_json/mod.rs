@@ -88,12 +88,16 @@
 //! a call to [std::primitive::str::parse], which is unacceptable - malformed JSON could cause a crash!
 //!
 //! ### Location
-//! Prefer implementing in this module, as [`decl_and_test`] will handle `quickcheck`-ing and snapshot testing.
+//! Prefer implementing in this module, one submodule per type.
 //!
 //! If you require access to private fields, consider:
 //! - implementing an exhaustive helper method, e.g [`crate::beacon::BeaconEntry::into_parts`].
 //! - moving implementation to the module where the struct is defined, e.g [`crate::blocks::tipset::lotus_json`].
-//!   If you do this, you MUST manually add snapshot and `quickcheck` tests.
+//!
+//! ### Testing
+//! List a few JSON values and the domain values they stand for in [`HasLotusJson::snapshots`], and
+//! call [`assert_all_snapshots`] from the tests of the module. Round-trip random values through
+//! [`assert_unchanged_via_json`] to catch fields that are lost on the way.
 //!
 //! ### Compound structs
 //! - Each field of a struct should be wrapped with [`LotusJson`].
@@ -113,19 +117,29 @@
 //! # }
 //! ```
 //!
+//! ### Unknown fields
+//! Lotus and Forest add fields to their responses over time. Structs must ignore fields they
+//! don't know about, so never use `#[serde(deny_unknown_fields)]`.
+//!
 //! # API hazards
 //! - Avoid using `#[serde(with = ...)]` except for leaf types
 //! - There is a hazard if the same type can be de/serialized in multiple ways.
 //!
+//! ### Derived implementations
+//! Simple compound structs whose fields map one to one onto the lotus JSON fields can
+//! `#[derive(HasLotusJson)]` instead, see [`macro@HasLotusJson`]. The companion struct is
+//! generated next to the domain struct, so snapshot tests must be added manually.
+//!
 //! # Future work
 //! - use [`proptest`](https://docs.rs/proptest/) to test the parser pipeline
-//! - use a derive macro for simple compound structs
 
 use ::cid::Cid;
 use derive_more::From;
 use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
 use std::{fmt::Display, str::FromStr};
 
+pub use lotus_json_derive::HasLotusJson;
+
 pub trait HasLotusJson: Sized {
     /// The struct representing JSON. You should `#[derive(Deserialize, Serialize)]` on it.
     type LotusJson: Serialize + DeserializeOwned;
@@ -134,45 +148,75 @@ pub trait HasLotusJson: Sized {
     ///
     /// Serialization and de-serialization of the domain type should match the snapshot.
     ///
-    /// If using [`decl_and_test`], this test is automatically run for you, but if the test
-    /// is out-of-module, you must call [`assert_all_snapshots`] manually.
+    /// Call [`assert_all_snapshots`] from the tests of the module to check them.
+    #[cfg(test)]
+    fn snapshots() -> Vec<(serde_json::Value, Self)> {
+        Vec::new()
+    }
     fn into_lotus_json(self) -> Self::LotusJson;
     fn from_lotus_json(lotus_json: Self::LotusJson) -> Self;
 }
 
-// macro_rules! decl_and_test {
-//     ($($mod_name:ident for $domain_ty:ty),* $(,)?) => {
-//         $(
-//             mod $mod_name;
-//         )*
-//     }
-// }
-// #[cfg(doc)]
-// pub(crate) use decl_and_test;
-
-// decl_and_test!(
-//     big_int for fvm_shared::bigint::BigInt,
-//     cid for ::cid::Cid,
-//     // key_info for crate::key_management::KeyInfo,
-//     // message for crate::shim::message::Message,
-//     // signature for crate::shim::crypto::Signature,
-//     // signature_type for crate::shim::crypto::SignatureType,
-//     // signed_message for  crate::message::SignedMessage,
-//     // token_amount for crate::shim::econ::TokenAmount,
-//     vec_u8 for Vec<u8>,
-// );
+/// Checks that each of [`HasLotusJson::snapshots`] serializes to its JSON, and back.
+#[cfg(test)]
+pub fn assert_all_snapshots<T>()
+where
+    T: HasLotusJson + Clone + PartialEq + std::fmt::Debug,
+{
+    let snapshots = T::snapshots();
+    assert!(
+        !snapshots.is_empty(),
+        "{} has no snapshots",
+        std::any::type_name::<T>()
+    );
+    for (json, val) in snapshots {
+        let serialized = serde_json::to_value(val.clone().into_lotus_json()).unwrap();
+        assert_eq!(serialized, json, "serializing {val:?}");
+        let deserialized = T::from_lotus_json(serde_json::from_value(json.clone()).unwrap());
+        assert_eq!(deserialized, val, "deserializing {json}");
+    }
+}
 
+/// Checks that `val` survives a trip through its lotus JSON, and that the JSON is stable.
+#[cfg(test)]
+pub fn assert_unchanged_via_json<T>(val: T)
+where
+    T: HasLotusJson + Clone + PartialEq + std::fmt::Debug,
+{
+    let json = serde_json::to_value(val.clone().into_lotus_json()).unwrap();
+    let round_tripped = T::from_lotus_json(serde_json::from_value(json.clone()).unwrap());
+    assert_eq!(round_tripped, val);
+    assert_eq!(
+        serde_json::to_value(round_tripped.into_lotus_json()).unwrap(),
+        json
+    );
+}
+
+mod actor_state;
 mod address;
+mod array_u8;
 mod big_int;
+mod bit_field;
+mod block_header;
 mod cid;
+mod deal_proposal;
+mod exit_code;
+mod hash_map;
 mod message;
+mod multiaddr;
+mod network_version;
 mod opt;
+mod peer_id;
+mod receipt;
 mod signature;
 mod signature_type;
 mod signed_message;
+mod ticket;
+mod tipset;
 mod token_amount;
 mod vec;
 mod vec_u8;
+mod vrf_proof;
 
 // mod nonempty; // can't make snapshots of generic type
 // mod opt; // can't make snapshots of generic type
@@ -181,15 +225,57 @@ mod raw_bytes; // fvm_ipld_encoding::RawBytes: !quickcheck::Arbitrary
 
 // pub use vec::*;
 
+/// Where a message landed on chain and how it executed, as returned by `Filecoin.StateSearchMsg`
+/// and `Filecoin.StateWaitMsg`.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct MessageLookup {
     pub height: i64,
     #[serde(with = "crate::lotus_json")]
     pub message: Cid,
+    #[serde(with = "crate::lotus_json")]
+    pub receipt: fvm_shared::receipt::Receipt,
+    /// The tipset the message was executed in.
+    #[serde(rename = "TipSet", with = "crate::lotus_json")]
+    pub tipset: crate::blocks::TipSetKey,
 }
 lotus_json_with_self!(MessageLookup);
 
+/// A message together with its CID, as returned by e.g. `Filecoin.ChainGetParentMessages`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiMessage {
+    #[serde(with = "crate::lotus_json")]
+    pub cid: Cid,
+    #[serde(with = "crate::lotus_json")]
+    pub message: crate::message::Message,
+}
+lotus_json_with_self!(ApiMessage);
+
+/// Result of simulating a message with `Filecoin.StateCall`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct InvocResult {
+    #[serde(with = "crate::lotus_json")]
+    pub msg_cid: Cid,
+    #[serde(with = "crate::lotus_json", default)]
+    pub msg_rct: Option<fvm_shared::receipt::Receipt>,
+    /// The execution trace, kept as raw JSON.
+    #[serde(default)]
+    pub execution_trace: serde_json::Value,
+    /// Set when the message couldn't be applied at all.
+    #[serde(default)]
+    pub error: String,
+}
+lotus_json_with_self!(InvocResult);
+
+impl InvocResult {
+    /// Exit code the message would have, or `None` if it couldn't be applied.
+    pub fn exit_code(&self) -> Option<fvm_shared::error::ExitCode> {
+        self.msg_rct.as_ref().map(|rct| rct.exit_code)
+    }
+}
+
 /// Usage: `#[serde(with = "stringify")]`
 pub mod stringify {
     use super::*;
@@ -240,38 +326,30 @@ pub mod stringify {
 //     }
 // }
 
-// pub mod hexify_vec_bytes {
-//     use super::*;
-//     use std::fmt::Write;
+/// Usage: `#[serde(with = "hexify_vec_bytes")]`, for the `0x`-prefixed byte strings of the Eth
+/// JSON-RPC API. Empty bytes are `"0x"`.
+#[allow(dead_code)]
+pub mod hexify_vec_bytes {
+    use super::*;
 
-//     pub fn serialize<S>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error>
-//     where
-//         S: Serializer,
-//     {
-//         let mut s = String::with_capacity(2 + value.len() * 2);
-//         s.push_str("0x");
-//         for b in value {
-//             write!(s, "{:02x}", b).expect("failed to write to string");
-//         }
-//         serializer.serialize_str(&s)
-//     }
+    pub fn serialize<S>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format!("0x{}", hex::encode(value)))
+    }
 
-//     pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
-//     where
-//         D: Deserializer<'de>,
-//     {
-//         let s = String::deserialize(deserializer)?;
-//         if (s.len() >= 2 && s.len() % 2 == 0) && s.get(..2).expect("failed to get prefix") == "0x" {
-//             let result: Result<Vec<u8>, _> = (2..s.len())
-//                 .step_by(2)
-//                 .map(|i| u8::from_str_radix(s.get(i..i + 2).expect("failed to get slice"), 16))
-//                 .collect();
-//             result.map_err(serde::de::Error::custom)
-//         } else {
-//             Err(serde::de::Error::custom("Invalid hex"))
-//         }
-//     }
-// }
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        let digits = s.strip_prefix("0x").ok_or_else(|| {
+            serde::de::Error::custom(format!("expected a 0x-prefixed hex string, got {s:?}"))
+        })?;
+        hex::decode(digits).map_err(|e| serde::de::Error::custom(format!("invalid hex {s:?}: {e}")))
+    }
+}
 
 // /// Usage: `#[serde(with = "hexify")]`
 // pub mod hexify {
@@ -455,3 +533,106 @@ mod fixme {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fvm_shared::error::ExitCode;
+
+    #[derive(Clone, Debug, PartialEq, HasLotusJson)]
+    struct Derived {
+        #[lotus_json(rename = "CID")]
+        cid: Cid,
+        height: i64,
+        #[lotus_json(skip_if_none)]
+        memo: Option<String>,
+    }
+
+    #[test]
+    fn test_derived_snapshot() {
+        let json = serde_json::json!({ "CID": { "/": "baeaaaaa" }, "Height": 100 });
+        let LotusJson(derived) =
+            serde_json::from_value::<LotusJson<Derived>>(json.clone()).unwrap();
+        assert_eq!(
+            derived,
+            Derived {
+                cid: Cid::default(),
+                height: 100,
+                memo: None
+            }
+        );
+        assert_eq!(serde_json::to_value(LotusJson(derived)).unwrap(), json);
+
+        let json = serde_json::json!({ "CID": { "/": "baeaaaaa" }, "Height": 100, "Memo": "drip" });
+        let LotusJson(derived) =
+            serde_json::from_value::<LotusJson<Derived>>(json.clone()).unwrap();
+        assert_eq!(derived.memo.as_deref(), Some("drip"));
+        assert_eq!(serde_json::to_value(LotusJson(derived)).unwrap(), json);
+    }
+
+    #[test]
+    fn test_hexify_vec_bytes() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Bytes(#[serde(with = "hexify_vec_bytes")] Vec<u8>);
+
+        for (bytes, json) in [(vec![], "0x"), (vec![0x00, 0xab, 0xff], "0x00abff")] {
+            assert_eq!(
+                serde_json::to_value(Bytes(bytes.clone())).unwrap(),
+                serde_json::json!(json)
+            );
+            assert_eq!(
+                serde_json::from_value::<Bytes>(serde_json::json!(json)).unwrap(),
+                Bytes(bytes)
+            );
+        }
+        assert_eq!(
+            serde_json::from_value::<Bytes>(serde_json::json!("0xABff")).unwrap(),
+            Bytes(vec![0xab, 0xff])
+        );
+        for invalid in ["", "00ab", "0xabc", "0xzz"] {
+            assert!(serde_json::from_value::<Bytes>(serde_json::json!(invalid)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_message_lookup() {
+        let json = serde_json::json!({
+            "Message": { "/": "baeaaaaa" },
+            "Receipt": { "ExitCode": 0, "Return": null, "GasUsed": 1000, "EventsRoot": null },
+            "ReturnDec": null,
+            "TipSet": [{ "/": "baeaaaaa" }],
+            "Height": 100
+        });
+        let lookup: MessageLookup = serde_json::from_value(json).unwrap();
+        assert_eq!(lookup.height, 100);
+        assert_eq!(lookup.receipt.exit_code, ExitCode::OK);
+        assert_eq!(lookup.receipt.gas_used, 1000);
+        assert_eq!(
+            lookup.tipset,
+            crate::blocks::TipSetKey(vec![Cid::default()])
+        );
+    }
+
+    #[test]
+    fn test_invoc_result_exit_code() {
+        let json = serde_json::json!({
+            "MsgCid": { "/": "baeaaaaa" },
+            "Msg": null,
+            "MsgRct": { "ExitCode": 33, "Return": null, "GasUsed": 0, "EventsRoot": null },
+            "GasCost": null,
+            "ExecutionTrace": { "Msg": null },
+            "Error": "",
+            "Duration": 1000
+        });
+        let res: InvocResult = serde_json::from_value(json).unwrap();
+        assert_eq!(res.exit_code(), Some(ExitCode::new(33)));
+
+        let res: InvocResult = serde_json::from_value(serde_json::json!({
+            "MsgCid": { "/": "baeaaaaa" },
+            "MsgRct": null,
+            "Error": "actor not found"
+        }))
+        .unwrap();
+        assert_eq!(res.exit_code(), None);
+    }
+}
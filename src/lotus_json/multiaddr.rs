@@ -0,0 +1,22 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::*;
+use crate::miner::Multiaddr;
+
+/// Multiaddresses are serialized as their base64-encoded binary form.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MultiaddrLotusJson(#[serde(with = "base64_standard")] Vec<u8>);
+
+impl HasLotusJson for Multiaddr {
+    type LotusJson = MultiaddrLotusJson;
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        MultiaddrLotusJson(self.0)
+    }
+
+    fn from_lotus_json(MultiaddrLotusJson(bytes): Self::LotusJson) -> Self {
+        Multiaddr(bytes)
+    }
+}
@@ -0,0 +1,35 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::*;
+use crate::network_version::NetworkVersion;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NetworkVersionLotusJson(u64);
+
+impl HasLotusJson for NetworkVersion {
+    type LotusJson = NetworkVersionLotusJson;
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        NetworkVersionLotusJson(self.0)
+    }
+
+    fn from_lotus_json(NetworkVersionLotusJson(version): Self::LotusJson) -> Self {
+        NetworkVersion(version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_version_snapshot() {
+        let json = serde_json::json!(23);
+        let LotusJson(version) =
+            serde_json::from_value::<LotusJson<NetworkVersion>>(json.clone()).unwrap();
+        assert_eq!(version, NetworkVersion(23));
+        assert_eq!(serde_json::to_value(LotusJson(version)).unwrap(), json);
+    }
+}
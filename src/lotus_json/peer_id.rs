@@ -0,0 +1,21 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::*;
+use crate::miner::PeerId;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PeerIdLotusJson(String);
+
+impl HasLotusJson for PeerId {
+    type LotusJson = PeerIdLotusJson;
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        PeerIdLotusJson(self.0)
+    }
+
+    fn from_lotus_json(PeerIdLotusJson(peer_id): Self::LotusJson) -> Self {
+        PeerId(peer_id)
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::*;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+use fvm_shared::receipt::Receipt;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ReceiptLotusJson {
+    #[serde(with = "crate::lotus_json")]
+    exit_code: ExitCode,
+    #[serde(with = "crate::lotus_json")]
+    r#return: RawBytes,
+    gas_used: u64,
+    #[serde(with = "crate::lotus_json", default)]
+    events_root: Option<Cid>,
+}
+
+impl HasLotusJson for Receipt {
+    type LotusJson = ReceiptLotusJson;
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        let Self {
+            exit_code,
+            return_data,
+            gas_used,
+            events_root,
+        } = self;
+        Self::LotusJson {
+            exit_code,
+            r#return: return_data,
+            gas_used,
+            events_root,
+        }
+    }
+
+    fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+        let Self::LotusJson {
+            exit_code,
+            r#return,
+            gas_used,
+            events_root,
+        } = lotus_json;
+        Self {
+            exit_code,
+            return_data: r#return,
+            gas_used,
+            events_root,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_receipt_snapshot() {
+        let json = serde_json::json!({
+            "ExitCode": 16,
+            "Return": "AQI=",
+            "GasUsed": 1234,
+            "EventsRoot": null
+        });
+        let LotusJson(receipt) =
+            serde_json::from_value::<LotusJson<Receipt>>(json.clone()).unwrap();
+        assert_eq!(
+            receipt,
+            Receipt {
+                exit_code: ExitCode::new(16),
+                return_data: RawBytes::new(vec![1, 2]),
+                gas_used: 1234,
+                events_root: None,
+            }
+        );
+        assert_eq!(serde_json::to_value(LotusJson(receipt)).unwrap(), json);
+    }
+}
@@ -16,6 +16,20 @@ pub struct SignatureLotusJson {
 impl HasLotusJson for Signature {
     type LotusJson = SignatureLotusJson;
 
+    #[cfg(test)]
+    fn snapshots() -> Vec<(serde_json::Value, Self)> {
+        vec![
+            (
+                serde_json::json!({ "Type": 1, "Data": "AQID" }),
+                Signature::new_secp256k1(vec![1, 2, 3]),
+            ),
+            (
+                serde_json::json!({ "Type": 2, "Data": null }),
+                Signature::new_bls(Vec::new()),
+            ),
+        ]
+    }
+
     fn into_lotus_json(self) -> Self::LotusJson {
         let Self { sig_type, bytes } = self;
         Self::LotusJson {
@@ -32,3 +46,29 @@ impl HasLotusJson for Signature {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng as _;
+
+    #[test]
+    fn test_snapshots() {
+        assert_all_snapshots::<Signature>();
+    }
+
+    #[test]
+    fn test_unchanged_via_json() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let sig_type = if rng.gen() {
+                SignatureType::Secp256k1
+            } else {
+                SignatureType::BLS
+            };
+            let len = rng.gen_range(0..=96);
+            let bytes = (0..len).map(|_| rng.gen()).collect();
+            assert_unchanged_via_json(Signature { sig_type, bytes });
+        }
+    }
+}
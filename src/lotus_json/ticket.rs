@@ -0,0 +1,64 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! The lotus JSON of [`Ticket`](crate::blocks::Ticket) and
+//! [`ElectionProof`](crate::blocks::ElectionProof) is derived next to their definitions.
+
+#[cfg(test)]
+mod tests {
+    use crate::blocks::{ElectionProof, Ticket, VRFProof};
+    use crate::lotus_json::LotusJson;
+    use bls_signatures::{PrivateKey, Serialize as _, Signature};
+
+    /// Randomness drawn from the beacon, which the miner's worker key signs to produce a VRF proof.
+    const RANDOMNESS: &[u8] = b"ticket randomness for epoch 4000000";
+
+    fn worker_key() -> PrivateKey {
+        PrivateKey::new([7; 32])
+    }
+
+    /// A VRF proof as miners produce it: the worker key's BLS signature over the randomness.
+    fn proof() -> VRFProof {
+        VRFProof(worker_key().sign(RANDOMNESS).as_bytes())
+    }
+
+    fn proof_base64() -> String {
+        use base64::Engine as _;
+        base64::engine::general_purpose::STANDARD.encode(&proof().0)
+    }
+
+    /// Whether `proof` still verifies against the worker key after a trip through lotus JSON.
+    fn verifies(proof: &VRFProof) -> bool {
+        let signature = Signature::from_bytes(&proof.0).unwrap();
+        bls_signatures::verify_messages(&signature, &[RANDOMNESS], &[worker_key().public_key()])
+    }
+
+    #[test]
+    fn test_ticket_snapshot() {
+        let json = serde_json::json!({ "VRFProof": proof_base64() });
+        let LotusJson(ticket) = serde_json::from_value::<LotusJson<Ticket>>(json.clone()).unwrap();
+        assert_eq!(ticket, Ticket { vrfproof: proof() });
+        assert_eq!(ticket.vrfproof.0.len(), 96);
+        assert!(verifies(&ticket.vrfproof));
+        assert_eq!(serde_json::to_value(LotusJson(ticket)).unwrap(), json);
+    }
+
+    #[test]
+    fn test_election_proof_snapshot() {
+        let json = serde_json::json!({ "WinCount": 1, "VRFProof": proof_base64() });
+        let LotusJson(election_proof) =
+            serde_json::from_value::<LotusJson<ElectionProof>>(json.clone()).unwrap();
+        assert_eq!(
+            election_proof,
+            ElectionProof {
+                win_count: 1,
+                vrfproof: proof()
+            }
+        );
+        assert!(verifies(&election_proof.vrfproof));
+        assert_eq!(
+            serde_json::to_value(LotusJson(election_proof)).unwrap(),
+            json
+        );
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::*;
+use crate::blocks::{BlockHeader, TipSet, TipSetKey};
+use fvm_shared::clock::ChainEpoch;
+
+impl HasLotusJson for TipSetKey {
+    type LotusJson = <Vec<Cid> as HasLotusJson>::LotusJson;
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        self.0.into_lotus_json()
+    }
+
+    fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+        Self(Vec::from_lotus_json(lotus_json))
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TipSetLotusJson {
+    #[serde(with = "crate::lotus_json")]
+    cids: TipSetKey,
+    #[serde(with = "crate::lotus_json")]
+    blocks: Vec<BlockHeader>,
+    height: ChainEpoch,
+}
+
+impl HasLotusJson for TipSet {
+    type LotusJson = TipSetLotusJson;
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        let Self {
+            key,
+            blocks,
+            height,
+        } = self;
+        Self::LotusJson {
+            cids: key,
+            blocks,
+            height,
+        }
+    }
+
+    fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+        let Self::LotusJson {
+            cids,
+            blocks,
+            height,
+        } = lotus_json;
+        Self {
+            key: cids,
+            blocks,
+            height,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fvm_shared::{address::Address, bigint::BigInt};
+
+    const CID: &str = "bafy2bzacea3wsdh6y3a36tb3skempjoxqpuyompjbmfeyf34fi3uy6uue42v4";
+
+    #[test]
+    fn test_tipset_snapshot() {
+        let json = serde_json::json!({
+            "Cids": [{ "/": CID }],
+            "Blocks": [{
+                "Miner": "f01234",
+                "Ticket": null,
+                "ElectionProof": null,
+                "Parents": [{ "/": CID }],
+                "ParentWeight": "100",
+                "Height": 4_000_000,
+                "Messages": { "/": CID },
+                "Timestamp": 1_720_000_000
+            }],
+            "Height": 4_000_000
+        });
+        let LotusJson(tipset) = serde_json::from_value::<LotusJson<TipSet>>(json.clone()).unwrap();
+        let cid = Cid::from_str(CID).unwrap();
+        assert_eq!(
+            tipset,
+            TipSet {
+                key: TipSetKey(vec![cid]),
+                blocks: vec![BlockHeader {
+                    miner: Address::new_id(1234),
+                    ticket: None,
+                    election_proof: None,
+                    parents: TipSetKey(vec![cid]),
+                    parent_weight: BigInt::from(100),
+                    height: 4_000_000,
+                    messages: cid,
+                    timestamp: 1_720_000_000,
+                }],
+                height: 4_000_000
+            }
+        );
+        assert_eq!(serde_json::to_value(LotusJson(tipset)).unwrap(), json);
+    }
+
+    #[test]
+    fn test_tipset_ignores_unknown_fields() {
+        let json = serde_json::json!({
+            "Cids": [{ "/": CID }],
+            "Blocks": [{
+                "Miner": "f01234",
+                "Ticket": { "VRFProof": "AA==" },
+                "ElectionProof": { "WinCount": 1, "VRFProof": "AA==" },
+                "BeaconEntries": [],
+                "WinPoStProof": [],
+                "Parents": [{ "/": CID }],
+                "ParentWeight": "100",
+                "Height": 10,
+                "ParentStateRoot": { "/": CID },
+                "ParentMessageReceipts": { "/": CID },
+                "Messages": { "/": CID },
+                "BLSAggregate": null,
+                "Timestamp": 1700000000,
+                "BlockSig": null,
+                "ForkSignaling": 0,
+                "ParentBaseFee": "100"
+            }],
+            "Height": 10
+        });
+        let LotusJson(tipset) = serde_json::from_value::<LotusJson<TipSet>>(json).unwrap();
+        assert_eq!(tipset.blocks[0].miner, Address::new_id(1234));
+    }
+}
@@ -15,6 +15,17 @@ pub struct TokenAmountLotusJson {
 impl HasLotusJson for TokenAmount {
     type LotusJson = TokenAmountLotusJson;
 
+    #[cfg(test)]
+    fn snapshots() -> Vec<(serde_json::Value, Self)> {
+        vec![
+            (serde_json::json!("0"), TokenAmount::from_atto(0)),
+            (
+                serde_json::json!("1000000000000000000"),
+                TokenAmount::from_whole(1),
+            ),
+        ]
+    }
+
     fn into_lotus_json(self) -> Self::LotusJson {
         Self::LotusJson {
             attos: self.atto().clone(),
@@ -26,3 +37,22 @@ impl HasLotusJson for TokenAmount {
         Self::from_atto(attos)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng as _;
+
+    #[test]
+    fn test_snapshots() {
+        assert_all_snapshots::<TokenAmount>();
+    }
+
+    #[test]
+    fn test_unchanged_via_json() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            assert_unchanged_via_json(TokenAmount::from_atto(rng.gen::<u128>()));
+        }
+    }
+}
@@ -0,0 +1,22 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::*;
+use crate::blocks::VRFProof;
+
+/// VRF proofs are serialized as their base64-encoded bytes.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct VRFProofLotusJson(#[serde(with = "base64_standard")] Vec<u8>);
+
+impl HasLotusJson for VRFProof {
+    type LotusJson = VRFProofLotusJson;
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        VRFProofLotusJson(self.0)
+    }
+
+    fn from_lotus_json(VRFProofLotusJson(bytes): Self::LotusJson) -> Self {
+        VRFProof(bytes)
+    }
+}
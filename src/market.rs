@@ -0,0 +1,45 @@
+use cid::Cid;
+use fvm_shared::{address::Address, clock::ChainEpoch, econ::TokenAmount, piece::PaddedPieceSize};
+use serde::{Deserialize, Serialize};
+
+use crate::lotus_json::lotus_json_with_self;
+
+/// The terms a client and a storage provider agreed on for storing a piece.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DealProposal {
+    pub piece_cid: Cid,
+    pub piece_size: PaddedPieceSize,
+    /// Whether the deal uses the client's DataCap.
+    pub verified_deal: bool,
+    pub client: Address,
+    pub provider: Address,
+    /// Free-form text set by the client, usually the payload CID.
+    pub label: String,
+    pub start_epoch: ChainEpoch,
+    pub end_epoch: ChainEpoch,
+    pub storage_price_per_epoch: TokenAmount,
+    pub provider_collateral: TokenAmount,
+    pub client_collateral: TokenAmount,
+}
+
+/// Where a deal is at in its lifecycle. Epochs are `-1` until they happen.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DealState {
+    /// Missing from nodes older than network version 22.
+    #[serde(default)]
+    pub sector_number: u64,
+    pub sector_start_epoch: ChainEpoch,
+    pub last_updated_epoch: ChainEpoch,
+    pub slash_epoch: ChainEpoch,
+}
+
+/// Result of `Filecoin.StateMarketStorageDeal`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MarketDeal {
+    #[serde(with = "crate::lotus_json")]
+    pub proposal: DealProposal,
+    pub state: DealState,
+}
+lotus_json_with_self!(MarketDeal);
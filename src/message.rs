@@ -19,21 +19,159 @@ fn from_cbor_blake2b256<S: serde::ser::Serialize>(obj: &S) -> Result<Cid, Error>
     ))
 }
 
+#[cfg(test)]
 pub fn message_transfer(from: Address, to: Address, value: TokenAmount) -> Message {
-    Message {
-        from,
-        to,
-        value,
-        method_num: METHOD_SEND,
-        params: RawBytes::new(vec![]),
-        gas_limit: 0,
-        gas_fee_cap: TokenAmount::from_atto(0),
-        gas_premium: TokenAmount::from_atto(0),
-        version: 0,
-        sequence: 0,
+    MessageBuilder::default()
+        .from(from)
+        .to(to)
+        .value(value)
+        .build()
+        .expect("sender and recipient are set")
+}
+
+/// Builds a [`Message`] field by field. Unset fields default to a `Send` of nothing with zero
+/// nonce and gas, which is left for gas estimation to fill in.
+#[derive(Clone, Debug)]
+pub struct MessageBuilder {
+    from: Option<Address>,
+    to: Option<Address>,
+    value: TokenAmount,
+    method_num: u64,
+    params: RawBytes,
+    sequence: u64,
+    gas_limit: u64,
+    gas_fee_cap: TokenAmount,
+    gas_premium: TokenAmount,
+}
+
+impl Default for MessageBuilder {
+    fn default() -> Self {
+        Self {
+            from: None,
+            to: None,
+            value: TokenAmount::from_atto(0),
+            method_num: METHOD_SEND,
+            params: RawBytes::new(vec![]),
+            sequence: 0,
+            gas_limit: 0,
+            gas_fee_cap: TokenAmount::from_atto(0),
+            gas_premium: TokenAmount::from_atto(0),
+        }
+    }
+}
+
+impl MessageBuilder {
+    pub fn from(mut self, from: Address) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    pub fn to(mut self, to: Address) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    pub fn value(mut self, value: TokenAmount) -> Self {
+        self.value = value;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn method(mut self, method_num: u64) -> Self {
+        self.method_num = method_num;
+        self
+    }
+
+    pub fn params(mut self, params: RawBytes) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn nonce(mut self, sequence: u64) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = gas_limit;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn gas_fee_cap(mut self, gas_fee_cap: TokenAmount) -> Self {
+        self.gas_fee_cap = gas_fee_cap;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn gas_premium(mut self, gas_premium: TokenAmount) -> Self {
+        self.gas_premium = gas_premium;
+        self
+    }
+
+    /// The message, as long as both its sender and recipient are set.
+    pub fn build(self) -> anyhow::Result<Message> {
+        let Self {
+            from,
+            to,
+            value,
+            method_num,
+            params,
+            sequence,
+            gas_limit,
+            gas_fee_cap,
+            gas_premium,
+        } = self;
+        Ok(Message {
+            version: 0,
+            from: from.ok_or_else(|| anyhow::anyhow!("Message has no sender"))?,
+            to: to.ok_or_else(|| anyhow::anyhow!("Message has no recipient"))?,
+            sequence,
+            value,
+            method_num,
+            params,
+            gas_limit,
+            gas_fee_cap,
+            gas_premium,
+        })
+    }
+}
+
+/// The most the sender may pay in gas for `msg`, i.e. the fee cap times the gas limit.
+pub fn max_fee(msg: &Message) -> TokenAmount {
+    msg.gas_fee_cap.clone() * msg.gas_limit
+}
+
+/// The gas settings of an estimated message, and the most it may cost.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EstimatedGas {
+    pub gas_limit: u64,
+    pub fee_cap: TokenAmount,
+    pub premium: TokenAmount,
+    pub max_fee: TokenAmount,
+}
+
+impl From<&Message> for EstimatedGas {
+    fn from(msg: &Message) -> Self {
+        Self {
+            gas_limit: msg.gas_limit,
+            fee_cap: msg.gas_fee_cap.clone(),
+            premium: msg.gas_premium.clone(),
+            max_fee: max_fee(msg),
+        }
     }
 }
 
+/// Whether the maximum fee of `msg` is at least `ratio` of the value it transfers.
+#[cfg(feature = "ssr")]
+pub fn fee_exceeds_ratio(msg: &Message, ratio: f64) -> bool {
+    // Compare in basis points to stay in integer arithmetic.
+    const SCALE: u64 = 10_000;
+    let ratio = (ratio * SCALE as f64).round() as u64;
+    max_fee(msg) * SCALE >= msg.value.clone() * ratio
+}
+
 pub fn message_cid(msg: &Message) -> cid::Cid {
     from_cbor_blake2b256(msg).expect("message serialization is infallible")
 }
@@ -60,4 +198,122 @@ impl SignedMessage {
             from_cbor_blake2b256(self).expect("message serialization is infallible")
         }
     }
+
+    /// Checks that the signature was produced by `message.from`. Both kinds of signatures cover
+    /// the CID of the unsigned message, whichever [`SignedMessage::cid`] identifies the message
+    /// by. Fails on malformed signatures as well as on signatures by another key.
+    #[cfg(feature = "ssr")]
+    pub fn verify(&self) -> anyhow::Result<()> {
+        crate::key::verify(
+            &self.signature,
+            &message_cid(&self.message).to_bytes(),
+            &self.message.from,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn test_signed_message_verify() {
+        use crate::key::{new_address, sign, to_public};
+
+        for (sig_type, private_key) in [
+            (SignatureType::Secp256k1, [1; 32].to_vec()),
+            (
+                SignatureType::BLS,
+                bls_signatures::Serialize::as_bytes(&bls_signatures::PrivateKey::new([1; 32])),
+            ),
+        ] {
+            let from = new_address(sig_type, &to_public(sig_type, &private_key).unwrap()).unwrap();
+            let message = MessageBuilder::default()
+                .from(from)
+                .to(Address::new_id(2))
+                .value(TokenAmount::from_whole(1))
+                .build()
+                .unwrap();
+            let signature =
+                sign(sig_type, &private_key, &message_cid(&message).to_bytes()).unwrap();
+            let mut smsg = SignedMessage { message, signature };
+            smsg.verify().unwrap();
+
+            // Tampering with the message invalidates the signature.
+            smsg.message.value = TokenAmount::from_whole(1000);
+            assert!(smsg.verify().is_err());
+        }
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn test_fee_exceeds_ratio() {
+        let mut msg = MessageBuilder::default()
+            .from(Address::new_id(1))
+            .to(Address::new_id(2))
+            .value(TokenAmount::from_atto(1_000_000))
+            .gas_limit(1_000)
+            .gas_fee_cap(TokenAmount::from_atto(400))
+            .build()
+            .unwrap();
+        assert_eq!(max_fee(&msg), TokenAmount::from_atto(400_000));
+        assert!(!fee_exceeds_ratio(&msg, 0.5));
+
+        msg.gas_fee_cap = TokenAmount::from_atto(500);
+        assert!(fee_exceeds_ratio(&msg, 0.5));
+        assert!(!fee_exceeds_ratio(&msg, 0.6));
+    }
+
+    #[test]
+    fn test_estimated_gas() {
+        let msg = MessageBuilder::default()
+            .from(Address::new_id(1))
+            .to(Address::new_id(2))
+            .gas_limit(1_000)
+            .gas_fee_cap(TokenAmount::from_atto(400))
+            .gas_premium(TokenAmount::from_atto(100))
+            .build()
+            .unwrap();
+        assert_eq!(
+            EstimatedGas::from(&msg),
+            EstimatedGas {
+                gas_limit: 1_000,
+                fee_cap: TokenAmount::from_atto(400),
+                premium: TokenAmount::from_atto(100),
+                max_fee: TokenAmount::from_atto(400_000),
+            }
+        );
+    }
+
+    #[test]
+    fn test_message_builder() {
+        let (from, to) = (Address::new_id(1), Address::new_id(2));
+        assert_eq!(
+            MessageBuilder::default()
+                .from(from)
+                .to(to)
+                .value(TokenAmount::from_whole(1))
+                .build()
+                .unwrap(),
+            message_transfer(from, to, TokenAmount::from_whole(1))
+        );
+
+        let msg = MessageBuilder::default()
+            .from(from)
+            .to(to)
+            .method(2)
+            .params(RawBytes::new(vec![0x80]))
+            .nonce(3)
+            .gas_premium(TokenAmount::from_atto(10))
+            .build()
+            .unwrap();
+        assert_eq!(msg.method_num, 2);
+        assert_eq!(msg.params, RawBytes::new(vec![0x80]));
+        assert_eq!(msg.sequence, 3);
+        assert_eq!(msg.gas_premium, TokenAmount::from_atto(10));
+        assert!(msg.gas_fee_cap.is_zero());
+
+        assert!(MessageBuilder::default().from(from).build().is_err());
+    }
 }
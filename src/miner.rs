@@ -0,0 +1,172 @@
+use std::fmt;
+
+use fvm_shared::address::Address;
+use serde::{Deserialize, Serialize};
+
+use crate::lotus_json::lotus_json_with_self;
+
+/// A libp2p peer ID in its base58 string form, e.g. `12D3KooW...`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeerId(pub String);
+
+impl fmt::Display for PeerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A multiaddress in its binary form. Displays as e.g. `/ip4/1.2.3.4/tcp/24001`, or as hex if it
+/// uses protocols this doesn't know about.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Multiaddr(pub Vec<u8>);
+
+impl Multiaddr {
+    fn decode(&self) -> Option<String> {
+        let mut bytes = self.0.as_slice();
+        let mut out = String::new();
+        while !bytes.is_empty() {
+            let code = read_varint(&mut bytes)?;
+            let (name, value) = match code {
+                4 => (
+                    "ip4",
+                    std::net::Ipv4Addr::from(take::<4>(&mut bytes)?).to_string(),
+                ),
+                41 => (
+                    "ip6",
+                    std::net::Ipv6Addr::from(take::<16>(&mut bytes)?).to_string(),
+                ),
+                6 => (
+                    "tcp",
+                    u16::from_be_bytes(take::<2>(&mut bytes)?).to_string(),
+                ),
+                273 => (
+                    "udp",
+                    u16::from_be_bytes(take::<2>(&mut bytes)?).to_string(),
+                ),
+                53..=56 => {
+                    let len = read_varint(&mut bytes)? as usize;
+                    let (name, rest) = bytes.split_at_checked(len)?;
+                    bytes = rest;
+                    let protocol = match code {
+                        53 => "dns",
+                        54 => "dns4",
+                        55 => "dns6",
+                        _ => "dnsaddr",
+                    };
+                    (protocol, String::from_utf8(name.to_vec()).ok()?)
+                }
+                460 => ("quic", String::new()),
+                461 => ("quic-v1", String::new()),
+                465 => ("webtransport", String::new()),
+                477 => ("ws", String::new()),
+                478 => ("wss", String::new()),
+                _ => return None,
+            };
+            out.push('/');
+            out.push_str(name);
+            if !value.is_empty() {
+                out.push('/');
+                out.push_str(&value);
+            }
+        }
+        Some(out)
+    }
+}
+
+impl fmt::Display for Multiaddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.decode() {
+            Some(addr) => f.write_str(&addr),
+            None => write!(f, "0x{}", hex::encode(&self.0)),
+        }
+    }
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn take<const N: usize>(bytes: &mut &[u8]) -> Option<[u8; N]> {
+    let (head, rest) = bytes.split_first_chunk::<N>()?;
+    *bytes = rest;
+    Some(*head)
+}
+
+/// Result of `Filecoin.StateMinerInfo`, limited to the fields the explorer shows.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MinerInfo {
+    #[serde(with = "crate::lotus_json")]
+    pub owner: Address,
+    #[serde(with = "crate::lotus_json")]
+    pub worker: Address,
+    /// Unset for miners that never announced themselves on the network.
+    #[serde(with = "crate::lotus_json", default)]
+    pub peer_id: Option<PeerId>,
+    #[serde(with = "crate::lotus_json", default)]
+    pub multiaddrs: Vec<Multiaddr>,
+    pub sector_size: u64,
+}
+lotus_json_with_self!(MinerInfo);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multiaddr_display() {
+        let addr = Multiaddr(vec![0x04, 1, 2, 3, 4, 0x06, 0x5d, 0xc1]);
+        assert_eq!(addr.to_string(), "/ip4/1.2.3.4/tcp/24001");
+
+        let mut dns = vec![0x36, 11];
+        dns.extend_from_slice(b"example.com");
+        dns.extend_from_slice(&[0x91, 0x02, 0x1f, 0x90, 0xcd, 0x03]);
+        assert_eq!(
+            Multiaddr(dns).to_string(),
+            "/dns4/example.com/udp/8080/quic-v1"
+        );
+
+        assert_eq!(Multiaddr(vec![0x04, 1, 2]).to_string(), "0x040102");
+    }
+
+    #[test]
+    fn test_miner_info_lotus_json() {
+        let json = serde_json::json!({
+            "Owner": "f01234",
+            "Worker": "f01235",
+            "NewWorker": "<empty>",
+            "ControlAddresses": null,
+            "PeerId": null,
+            "Multiaddrs": ["BAECAwQGXcE="],
+            "SectorSize": 34359738368u64,
+            "WindowPoStPartitionSectors": 2349
+        });
+        let info: MinerInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(info.owner, Address::new_id(1234));
+        assert_eq!(info.peer_id, None);
+        assert_eq!(info.multiaddrs[0].to_string(), "/ip4/1.2.3.4/tcp/24001");
+
+        let info: MinerInfo = serde_json::from_value(serde_json::json!({
+            "Owner": "f01234",
+            "Worker": "f01235",
+            "PeerId": "12D3KooWQxTf8pSYgD7szTYfxJTzkYEFt4gLKEM4qFNSiYBfvCR4",
+            "Multiaddrs": null,
+            "SectorSize": 2048
+        }))
+        .unwrap();
+        assert_eq!(
+            info.peer_id.unwrap().to_string(),
+            "12D3KooWQxTf8pSYgD7szTYfxJTzkYEFt4gLKEM4qFNSiYBfvCR4"
+        );
+        assert!(info.multiaddrs.is_empty());
+    }
+}
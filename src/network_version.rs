@@ -0,0 +1,49 @@
+use std::fmt;
+
+/// Version of the Filecoin protocol, bumped by every network upgrade.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NetworkVersion(pub u64);
+
+impl NetworkVersion {
+    /// Name of the upgrade introducing this version, e.g. `Waffle` for 23, or `nv{n}` for
+    /// versions without a known name.
+    pub fn name(&self) -> String {
+        let name = match self.0 {
+            12 => "Turbo",
+            13 => "HyperDrive",
+            14 => "Chocolate",
+            15 => "OhSnap",
+            16 => "Skyr",
+            17 => "Shark",
+            18 => "Hygge",
+            19 => "Lightning",
+            20 => "Thunder",
+            21 => "Watermelon",
+            22 => "Dragon",
+            23 => "Waffle",
+            24 => "TukTuk",
+            25 => "Teep",
+            n => return format!("nv{n}"),
+        };
+        name.to_string()
+    }
+}
+
+impl fmt::Display for NetworkVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Network Version {}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_version_name() {
+        assert_eq!(NetworkVersion(23).name(), "Waffle");
+        assert_eq!(NetworkVersion(99).name(), "nv99");
+        assert_eq!(NetworkVersion(23).to_string(), "Network Version 23");
+        assert!(NetworkVersion(22) < NetworkVersion(23));
+    }
+}
@@ -0,0 +1,91 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use worker::*;
+
+use crate::store::Store;
+
+/// How long the locally tracked nonce is trusted before it is reset to the one reported by the
+/// node. This recovers from messages that were signed but never made it into the message pool.
+const NONCE_RECONCILE_SECONDS: i64 = 60;
+
+#[derive(Serialize, Deserialize)]
+pub struct NonceRequest {
+    /// Next nonce as reported by `Filecoin.MpoolGetNonce`.
+    pub chain_nonce: u64,
+}
+
+/// Hands out monotonically increasing nonces for a single faucet key, so concurrent signing
+/// requests never reuse a nonce. One instance exists per faucet address.
+#[durable_object]
+pub struct NonceManager {
+    state: State,
+}
+
+#[durable_object]
+impl DurableObject for NonceManager {
+    fn new(state: State, _env: Env) -> Self {
+        Self { state }
+    }
+
+    async fn fetch(&mut self, mut req: Request) -> Result<Response> {
+        let NonceRequest { chain_nonce } = req.json().await?;
+        let nonce = allocate(
+            &mut self.state.storage(),
+            chain_nonce,
+            Utc::now().timestamp(),
+        )
+        .await?;
+        console_log!(
+            "Nonce manager invoked: chain_nonce={}, allocated={}",
+            chain_nonce,
+            nonce
+        );
+        Response::from_json(&nonce)
+    }
+}
+
+/// Allocates the next nonce at unix time `now`, given the one reported by the node.
+async fn allocate(store: &mut impl Store, chain_nonce: u64, now: i64) -> Result<u64> {
+    let next_nonce = store.get::<u64>("next_nonce").await;
+    let reconciled_at = store.get::<i64>("reconciled_at").await;
+    let stale = reconciled_at.is_none_or(|at| now - at >= NONCE_RECONCILE_SECONDS);
+    let nonce = reconcile(next_nonce, chain_nonce, stale);
+    if stale {
+        store.put("reconciled_at", now).await?;
+    }
+    store.put("next_nonce", nonce + 1).await?;
+    Ok(nonce)
+}
+
+/// Picks the nonce to allocate. The tracked nonce may run ahead of the node while messages are
+/// in flight, but it never goes backwards unless it is due for reconciliation.
+fn reconcile(tracked: Option<u64>, chain_nonce: u64, stale: bool) -> u64 {
+    match tracked {
+        Some(tracked) if !stale => tracked.max(chain_nonce),
+        _ => chain_nonce,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconcile() {
+        assert_eq!(reconcile(None, 5, false), 5);
+        assert_eq!(reconcile(Some(7), 5, false), 7);
+        assert_eq!(reconcile(Some(3), 5, false), 5);
+        assert_eq!(reconcile(Some(7), 5, true), 5);
+    }
+
+    #[test]
+    fn test_allocate() {
+        use crate::store::MemoryStore;
+        use futures::executor::block_on;
+
+        let mut store = MemoryStore::default();
+        assert_eq!(block_on(allocate(&mut store, 5, 0)).unwrap(), 5);
+        assert_eq!(block_on(allocate(&mut store, 5, 10)).unwrap(), 6);
+        assert_eq!(block_on(allocate(&mut store, 5, 60)).unwrap(), 5);
+    }
+}
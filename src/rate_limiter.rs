@@ -1,55 +1,63 @@
 use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 use worker::*;
 
+use crate::store::Store;
+
+/// Path answered without counting as a request, for health checks.
+pub const STATUS_PATH: &str = "/status";
+/// Path limited to one request per `topup_interval_seconds` rather than per
+/// `rate_limit_seconds`, for automatic top-up requests.
+pub const TOPUP_PATH: &str = "/topup";
+/// Query parameter holding the number of drips a signing request pays for. A batch of `n` drips
+/// blocks signing for `n` times `rate_limit_seconds`.
+pub const RECIPIENTS_PARAM: &str = "recipients";
+
+/// The rate limiter's answer to a signing request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RateLimit {
+    Allowed,
+    /// Signing is blocked for another `retry_after` seconds.
+    Blocked {
+        retry_after: i64,
+    },
+}
+
 #[durable_object]
 pub struct RateLimiter {
     state: State,
     #[allow(unused)]
     block_until: DateTime<Utc>,
+    rate_limit_seconds: i64,
+    topup_interval_seconds: i64,
 }
 
 #[durable_object]
 impl DurableObject for RateLimiter {
-    fn new(state: State, _env: Env) -> Self {
+    fn new(state: State, env: Env) -> Self {
+        let config = crate::config::Config::from_env(&env);
         Self {
             state,
             block_until: Utc::now(),
+            rate_limit_seconds: config.rate_limit_seconds,
+            topup_interval_seconds: config.topup_interval_seconds,
         }
     }
 
-    async fn fetch(&mut self, _req: Request) -> Result<Response> {
-        let now = Utc::now();
-        let block_until = self
-            .state
-            .storage()
-            .get("block_until")
-            .await
-            .map(|v| DateTime::<Utc>::from_timestamp(v, 0).unwrap_or_default())
-            .unwrap_or(Utc::now());
-        console_log!(
-            "Rate limiter invoked: now={:?}, block_until={:?}, may_sign={:?}",
-            now,
-            block_until,
-            block_until <= now
-        );
-        if block_until <= now {
-            // This Durable Object will be deleted after the alarm is triggered
-            self.state
-                .storage()
-                .set_alarm(std::time::Duration::from_secs(
-                    crate::constants::RATE_LIMIT_SECONDS as u64 + 1,
-                ))
-                .await?;
-            let block_until = now + Duration::seconds(crate::constants::RATE_LIMIT_SECONDS);
-            self.state
-                .storage()
-                .put("block_until", block_until.timestamp())
-                .await?;
-
-            Response::from_json(&true)
-        } else {
-            Response::from_json(&false)
+    async fn fetch(&mut self, req: Request) -> Result<Response> {
+        if req.path() == STATUS_PATH {
+            return Response::from_json(&true);
         }
+        let seconds = if req.path() == TOPUP_PATH {
+            self.topup_interval_seconds
+        } else {
+            self.rate_limit_seconds
+                .saturating_mul(recipients(&req.url()?))
+        };
+        let now = Utc::now();
+        let limit = try_acquire(&mut self.state.storage(), now, seconds).await?;
+        console_log!("Rate limiter invoked: now={:?}, limit={:?}", now, limit);
+        Response::from_json(&limit)
     }
 
     async fn alarm(&mut self) -> Result<Response> {
@@ -57,3 +65,134 @@ impl DurableObject for RateLimiter {
         Response::ok("OK")
     }
 }
+
+/// Number of drips paid for by a request to `url`, at least one.
+fn recipients(url: &Url) -> i64 {
+    url.query_pairs()
+        .find(|(key, _)| key == RECIPIENTS_PARAM)
+        .and_then(|(_, value)| value.parse::<i64>().ok())
+        .unwrap_or(1)
+        .max(1)
+}
+
+/// Whether signing is allowed at `now`. If it is, signing is blocked for the next
+/// `rate_limit_seconds`; if not, how long the current block lasts.
+async fn try_acquire(
+    store: &mut impl Store,
+    now: DateTime<Utc>,
+    rate_limit_seconds: i64,
+) -> Result<RateLimit> {
+    let block_until = store
+        .get("block_until")
+        .await
+        .map(|v| DateTime::<Utc>::from_timestamp(v, 0).unwrap_or_default())
+        .unwrap_or(now);
+    if block_until <= now {
+        // This Durable Object will be deleted after the alarm is triggered
+        store
+            .set_alarm(std::time::Duration::from_secs(
+                rate_limit_seconds as u64 + 1,
+            ))
+            .await?;
+        let block_until = now + Duration::seconds(rate_limit_seconds);
+        store.put("block_until", block_until.timestamp()).await?;
+        Ok(RateLimit::Allowed)
+    } else {
+        // Round up, so clients never retry a moment too early.
+        let remaining = block_until - now;
+        let retry_after = remaining.num_seconds()
+            + i64::from(remaining > Duration::seconds(remaining.num_seconds()));
+        Ok(RateLimit::Blocked { retry_after })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+    use futures::executor::block_on;
+
+    const LIMIT: i64 = 600;
+
+    fn acquire(store: &mut MemoryStore, now: DateTime<Utc>) -> bool {
+        block_on(try_acquire(store, now, LIMIT)).unwrap() == RateLimit::Allowed
+    }
+
+    fn block_until(store: &MemoryStore) -> Option<i64> {
+        block_on(store.get("block_until"))
+    }
+
+    #[test]
+    fn test_first_request_allowed() {
+        let mut store = MemoryStore::default();
+        let now = Utc::now();
+        assert!(acquire(&mut store, now));
+        assert_eq!(block_until(&store), Some(now.timestamp() + LIMIT));
+    }
+
+    #[test]
+    fn test_second_request_blocked() {
+        let mut store = MemoryStore::default();
+        let now = Utc::now();
+        assert!(acquire(&mut store, now));
+        assert!(!acquire(&mut store, now));
+        assert!(!acquire(&mut store, now + Duration::seconds(LIMIT - 1)));
+        // Blocked requests don't extend the block.
+        assert_eq!(block_until(&store), Some(now.timestamp() + LIMIT));
+    }
+
+    #[test]
+    fn test_blocked_request_reports_remaining_time() {
+        let mut store = MemoryStore::default();
+        let now = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).unwrap();
+        assert!(acquire(&mut store, now));
+        let later = now + Duration::seconds(LIMIT - 42);
+        assert_eq!(
+            block_on(try_acquire(&mut store, later, LIMIT)).unwrap(),
+            RateLimit::Blocked { retry_after: 42 }
+        );
+        let later = later + Duration::milliseconds(500);
+        assert_eq!(
+            block_on(try_acquire(&mut store, later, LIMIT)).unwrap(),
+            RateLimit::Blocked { retry_after: 42 }
+        );
+    }
+
+    #[test]
+    fn test_request_after_expiry_allowed() {
+        let mut store = MemoryStore::default();
+        let now = Utc::now();
+        assert!(acquire(&mut store, now));
+        let later = now + Duration::seconds(LIMIT);
+        assert!(acquire(&mut store, later));
+        assert_eq!(block_until(&store), Some(later.timestamp() + LIMIT));
+    }
+
+    #[test]
+    fn test_recipients() {
+        let recipients = |url: &str| recipients(&Url::parse(url).unwrap());
+        assert_eq!(recipients("http://do/rate_limiter"), 1);
+        assert_eq!(recipients("http://do/rate_limiter?recipients=20"), 20);
+        assert_eq!(recipients("http://do/rate_limiter?recipients=0"), 1);
+        assert_eq!(recipients("http://do/rate_limiter?recipients=-3"), 1);
+        assert_eq!(recipients("http://do/rate_limiter?recipients=many"), 1);
+    }
+
+    #[test]
+    fn test_alarm_fires_after_block_expires() {
+        let mut store = MemoryStore::default();
+        assert!(acquire(&mut store, Utc::now()));
+        // The object is deleted when the alarm fires, which must not happen while it's blocking.
+        assert_eq!(
+            store.alarm,
+            Some(std::time::Duration::from_secs(LIMIT as u64 + 1))
+        );
+
+        store.alarm = None;
+        assert!(!acquire(&mut store, Utc::now()));
+        assert_eq!(store.alarm, None);
+
+        // A deleted object starts over with empty storage.
+        assert!(acquire(&mut MemoryStore::default(), Utc::now()));
+    }
+}
@@ -0,0 +1,101 @@
+use fvm_shared::address::Address;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Number of resolved addresses remembered.
+const ADDRESS_CACHE_SIZE: usize = 512;
+
+/// A map holding at most `capacity` entries, evicting the least recently used one when full.
+pub struct Lru<K, V> {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<K, (V, u64)>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Lru<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            entries: HashMap::with_capacity(capacity),
+        }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        self.tick += 1;
+        let (value, used) = self.entries.get_mut(key)?;
+        *used = self.tick;
+        Some(value.clone())
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.tick += 1;
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (value, self.tick));
+    }
+}
+
+/// Which form of an address a lookup resolves to.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lookup {
+    Id,
+    AccountKey,
+}
+
+thread_local! {
+    /// Keyed by node URL as well, since the same address maps to different actors per network.
+    static ADDRESSES: RefCell<Lru<(String, Lookup, Address), Address>> =
+        RefCell::new(Lru::new(ADDRESS_CACHE_SIZE));
+}
+
+pub fn cached_address(url: &str, lookup: Lookup, addr: Address) -> Option<Address> {
+    ADDRESSES.with_borrow_mut(|cache| cache.get(&(url.to_string(), lookup, addr)))
+}
+
+/// Remembers a resolved address. Only successful lookups are stable: an address without an
+/// actor may get one at any time.
+pub fn cache_address(url: &str, lookup: Lookup, addr: Address, resolved: Address) {
+    ADDRESSES.with_borrow_mut(|cache| cache.insert((url.to_string(), lookup, addr), resolved));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru_evicts_least_recently_used() {
+        let mut lru = Lru::new(2);
+        lru.insert("a", 1);
+        lru.insert("b", 2);
+        assert_eq!(lru.get(&"a"), Some(1));
+        lru.insert("c", 3);
+        assert_eq!(lru.get(&"b"), None);
+        assert_eq!(lru.get(&"a"), Some(1));
+        assert_eq!(lru.get(&"c"), Some(3));
+
+        // Updating an entry doesn't evict another one.
+        lru.insert("c", 4);
+        assert_eq!(lru.get(&"a"), Some(1));
+        assert_eq!(lru.get(&"c"), Some(4));
+    }
+
+    #[test]
+    fn test_address_cache_is_per_node() {
+        let addr = Address::new_id(1234);
+        let id = Address::new_id(1);
+        cache_address("https://a", Lookup::Id, addr, id);
+        assert_eq!(cached_address("https://a", Lookup::Id, addr), Some(id));
+        assert_eq!(cached_address("https://b", Lookup::Id, addr), None);
+        assert_eq!(cached_address("https://a", Lookup::AccountKey, addr), None);
+    }
+}
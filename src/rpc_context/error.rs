@@ -0,0 +1,142 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+/// JSON-RPC code for calls to methods the node doesn't serve.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// How much of a non-JSON response body is kept for the error message.
+const BODY_SNIPPET_LENGTH: usize = 120;
+
+/// Errors reported by the RPC node, as opposed to failures to reach it or to decode its response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpcError {
+    /// The node answered with a JSON-RPC error object.
+    JsonRpc { code: i64, message: String },
+    /// The response isn't JSON at all, e.g. an HTML error page from a gateway in front of the
+    /// node.
+    Transport { status: u16, body: String },
+}
+
+impl RpcError {
+    /// Whether the node rejected a message because its nonce was already used.
+    pub fn is_nonce_too_low(&self) -> bool {
+        match self {
+            RpcError::JsonRpc { message, .. } => message.to_lowercase().contains("nonce too low"),
+            RpcError::Transport { .. } => false,
+        }
+    }
+
+    /// Whether the queried address has no actor on chain yet.
+    pub fn is_actor_not_found(&self) -> bool {
+        match self {
+            RpcError::JsonRpc { message, .. } => message.to_lowercase().contains("actor not found"),
+            RpcError::Transport { .. } => false,
+        }
+    }
+
+    /// Whether the node doesn't serve the called method, e.g. because it's restricted.
+    pub fn is_method_not_found(&self) -> bool {
+        match self {
+            RpcError::JsonRpc { code, .. } => *code == METHOD_NOT_FOUND,
+            RpcError::Transport { .. } => false,
+        }
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcError::JsonRpc { code, message } => write!(f, "RPC error {code}: {message}"),
+            RpcError::Transport { status, body } => {
+                write!(
+                    f,
+                    "RPC node returned HTTP {status} with a non-JSON body: {body}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// Rejects responses that aren't JSON, so e.g. a gateway's HTML error page reports its status
+/// rather than a JSON syntax error.
+pub(super) fn check_response(
+    status: u16,
+    content_type: Option<&str>,
+    body: &str,
+) -> Result<(), RpcError> {
+    let is_json = content_type.is_some_and(|content_type| content_type.contains("json"))
+        || serde_json::from_str::<serde::de::IgnoredAny>(body).is_ok();
+    if is_json {
+        return Ok(());
+    }
+    let body = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    let body = match body.char_indices().nth(BODY_SNIPPET_LENGTH) {
+        Some((end, _)) => format!("{}...", &body[..end]),
+        None => body,
+    };
+    Err(RpcError::Transport { status, body })
+}
+
+#[derive(Deserialize)]
+pub(super) struct ErrorObject {
+    code: i64,
+    message: String,
+}
+
+impl From<ErrorObject> for RpcError {
+    fn from(ErrorObject { code, message }: ErrorObject) -> Self {
+        RpcError::JsonRpc { code, message }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_nonce_too_low() {
+        let err = RpcError::JsonRpc {
+            code: 1,
+            message: "minimum expected nonce is 5: message nonce too low".to_string(),
+        };
+        assert!(err.is_nonce_too_low());
+
+        let err = RpcError::JsonRpc {
+            code: 1,
+            message: "not enough funds".to_string(),
+        };
+        assert!(!err.is_nonce_too_low());
+    }
+
+    #[test]
+    fn test_check_response_html() {
+        let html = "<html>\n<head><title>502 Bad Gateway</title></head>\n<body>".to_string()
+            + &"x".repeat(500)
+            + "</body></html>";
+        let err = check_response(502, Some("text/html"), &html).unwrap_err();
+        let RpcError::Transport { status, body } = &err else {
+            panic!("expected a transport error, got {err:?}");
+        };
+        assert_eq!(*status, 502);
+        assert!(body.starts_with("<html> <head><title>502 Bad Gateway</title></head> <body>"));
+        assert!(body.ends_with("..."));
+        assert!(err.to_string().contains("HTTP 502"));
+
+        assert!(check_response(200, Some("application/json"), "{}").is_ok());
+        // Some nodes don't set the content type.
+        assert!(check_response(200, None, r#"{"result": 1}"#).is_ok());
+    }
+
+    #[test]
+    fn test_is_method_not_found() {
+        let err = RpcError::JsonRpc {
+            code: -32601,
+            message: "the method Filecoin.MpoolGetNonce does not exist/is not available"
+                .to_string(),
+        };
+        assert!(err.is_method_not_found());
+        assert!(!err.is_nonce_too_low());
+    }
+}
@@ -0,0 +1,88 @@
+use anyhow::{anyhow, bail, Context as _};
+use fvm_shared::address::{Address, Payload};
+use serde_json::Value;
+
+use super::{invoke_rpc_method, Provider};
+use crate::address::f4_to_eth;
+
+// Lotus masks ID addresses as `0xff` followed by zeroes and the big-endian actor ID.
+const ETH_ID_MASK_PREFIX: u8 = 0xff;
+
+/// Wrapper around [`Provider`] for the `eth_*` namespace. Parameters and results use the
+/// Ethereum conventions (`0x`-prefixed hex) rather than lotus JSON.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EthProvider {
+    provider: Provider,
+}
+
+impl EthProvider {
+    pub(super) fn new(provider: Provider) -> Self {
+        Self { provider }
+    }
+
+    async fn invoke(&self, method: &str, params: &[Value]) -> anyhow::Result<String> {
+        invoke_rpc_method::<String>(&self.provider, method, params)
+            .await
+            .with_context(|| format!("{method} failed, does the node support the eth namespace?"))
+    }
+
+    pub async fn eth_chain_id(&self) -> anyhow::Result<u64> {
+        parse_hex_u64(&self.invoke("Filecoin.EthChainId", &[]).await?)
+    }
+}
+
+/// Converts a Filecoin address to its `0x` form. Only delegated (EAM) and ID addresses have one.
+pub fn eth_address(address: &Address) -> anyhow::Result<String> {
+    if let Some(eth) = f4_to_eth(address) {
+        return Ok(eth);
+    }
+    match address.payload() {
+        Payload::ID(id) => {
+            let mut bytes = [0u8; 20];
+            bytes[0] = ETH_ID_MASK_PREFIX;
+            bytes[12..].copy_from_slice(&id.to_be_bytes());
+            Ok(format!("0x{}", hex::encode(bytes)))
+        }
+        _ => bail!("Address {address} has no Ethereum equivalent"),
+    }
+}
+
+fn strip_hex_prefix(s: &str) -> anyhow::Result<&str> {
+    s.strip_prefix("0x")
+        .ok_or_else(|| anyhow!("Expected 0x-prefixed hex, got {s}"))
+}
+
+fn parse_hex_u64(s: &str) -> anyhow::Result<u64> {
+    Ok(u64::from_str_radix(strip_hex_prefix(s)?, 16)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::EAM_NAMESPACE;
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(parse_hex_u64("0x4cb2f").unwrap(), 314159);
+        assert_eq!(parse_hex_u64("0x0").unwrap(), 0);
+        assert!(parse_hex_u64("4cb2f").is_err());
+    }
+
+    #[test]
+    fn test_eth_address() {
+        let addr = Address::new_delegated(
+            EAM_NAMESPACE,
+            &hex::decode("d388ab098ed3e84c0d808776440b48f685198498").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            eth_address(&addr).unwrap(),
+            "0xd388ab098ed3e84c0d808776440b48f685198498"
+        );
+        assert_eq!(
+            eth_address(&Address::new_id(1234)).unwrap(),
+            "0xff000000000000000000000000000000000004d2"
+        );
+        assert!(eth_address(&Address::new_secp256k1(&[0u8; 65]).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context as _;
+use cid::Cid;
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::message::Message;
+
+use super::{ResolvedAddress, RpcProvider};
+use crate::actor::ActorKind;
+use crate::blocks::{TipSet, TipSetKey};
+use crate::lotus_json::{InvocResult, MessageLookup};
+use crate::message::SignedMessage;
+
+pub const CID: &str = "bafy2bzacea3wsdh6y3a36tb3skempjoxqpuyompjbmfeyf34fi3uy6uue42v4";
+
+/// A node answering with canned values, which records the tipsets gas was estimated against.
+/// Messages never land on chain.
+#[derive(Clone, Default)]
+pub struct MockProvider {
+    /// The key of the head at height 100, or `None` if the node is unreachable.
+    pub head: Option<TipSetKey>,
+    /// ID addresses of the actors behind other addresses.
+    pub ids: Vec<(Address, Address)>,
+    pub estimated_at: Arc<Mutex<Vec<Option<TipSetKey>>>>,
+}
+
+impl RpcProvider for MockProvider {
+    async fn chain_head(&self) -> anyhow::Result<TipSet> {
+        let key = self.head.clone().context("node unreachable")?;
+        Ok(TipSet {
+            key,
+            blocks: vec![],
+            height: 100,
+        })
+    }
+
+    async fn wallet_balance(&self, _: Address) -> anyhow::Result<TokenAmount> {
+        Ok(TokenAmount::from_whole(1000))
+    }
+
+    async fn estimate_gas(
+        &self,
+        mut msg: Message,
+        tsk: Option<TipSetKey>,
+    ) -> anyhow::Result<Message> {
+        self.estimated_at.lock().unwrap().push(tsk);
+        msg.gas_limit = 1_000_000;
+        msg.gas_fee_cap = TokenAmount::from_atto(100);
+        msg.gas_premium = TokenAmount::from_atto(10);
+        Ok(msg)
+    }
+
+    async fn next_nonce(&self, _: Address) -> anyhow::Result<u64> {
+        Ok(0)
+    }
+
+    async fn mpool_push(&self, smsg: SignedMessage) -> anyhow::Result<Cid> {
+        Ok(smsg.cid())
+    }
+
+    async fn state_lookup_id(&self, addr: Address) -> anyhow::Result<Option<Address>> {
+        Ok(self.ids.iter().find(|(a, _)| *a == addr).map(|(_, id)| *id))
+    }
+
+    async fn resolve_address(&self, addr: Address) -> anyhow::Result<ResolvedAddress> {
+        Ok(ResolvedAddress {
+            id: self.state_lookup_id(addr).await?,
+            key: Some(addr),
+        })
+    }
+
+    async fn actor_kind(&self, _: Address) -> anyhow::Result<Option<ActorKind>> {
+        Ok(None)
+    }
+
+    async fn state_call(&self, msg: Message, _: Option<TipSetKey>) -> anyhow::Result<InvocResult> {
+        // Sends to ID 0, the system actor, are rejected.
+        let exit_code = if msg.to == Address::new_id(0) { 16 } else { 0 };
+        Ok(serde_json::from_value(serde_json::json!({
+            "MsgCid": { "/": CID },
+            "MsgRct": { "ExitCode": exit_code, "Return": null, "GasUsed": 488_500, "EventsRoot": null },
+        }))?)
+    }
+
+    async fn state_search_msgs(
+        &self,
+        msgs: &[Cid],
+    ) -> anyhow::Result<Vec<anyhow::Result<Option<MessageLookup>>>> {
+        Ok(msgs.iter().map(|_| Ok(None)).collect())
+    }
+
+    #[cfg(feature = "hydrate")]
+    async fn state_list_messages(
+        &self,
+        _: Address,
+        _: fvm_shared::clock::ChainEpoch,
+    ) -> anyhow::Result<Vec<Cid>> {
+        Ok(Vec::new())
+    }
+
+    #[cfg(feature = "hydrate")]
+    async fn chain_get_messages(
+        &self,
+        cids: &[Cid],
+    ) -> anyhow::Result<Vec<anyhow::Result<Message>>> {
+        Ok(cids
+            .iter()
+            .map(|_| Err(anyhow::anyhow!("message not found")))
+            .collect())
+    }
+}
@@ -0,0 +1,1075 @@
+use cid::Cid;
+use futures::{Stream, StreamExt as _};
+use fvm_shared::address::{set_current_network, Address, Network, Protocol};
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::message::Message;
+use fvm_shared::receipt::Receipt;
+use fvm_shared::state::ActorState;
+use leptos::prelude::*;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use crate::actor::ActorKind;
+use crate::blocks::{BlockHeader, TipSet, TipSetKey};
+use crate::lotus_json::{ApiMessage, HasLotusJson, LotusJson};
+use crate::market::MarketDeal;
+use crate::message::SignedMessage;
+use crate::miner::MinerInfo;
+use crate::network_version::NetworkVersion;
+
+mod cache;
+mod error;
+mod eth;
+#[cfg(test)]
+pub mod mock;
+mod retry;
+mod stream;
+pub use error::RpcError;
+pub use eth::{eth_address, EthProvider};
+
+use cache::Lookup;
+
+/// `User-Agent` of RPC requests sent by the worker, so node providers can tell its traffic apart.
+/// Browsers always send their own.
+#[cfg(feature = "ssr")]
+const USER_AGENT: &str = match option_env!("RPC_USER_AGENT") {
+    Some(user_agent) => user_agent,
+    None => concat!("forest-explorer/", env!("CARGO_PKG_VERSION")),
+};
+
+static CLIENT: LazyLock<Client> = LazyLock::new(|| {
+    let builder = Client::builder();
+    #[cfg(feature = "ssr")]
+    let builder = builder.user_agent(USER_AGENT);
+    builder.build().expect("RPC client configuration is valid")
+});
+
+const GLIF_CALIBNET: &str = "https://api.calibration.node.glif.io";
+const GLIF_MAINNET: &str = "https://api.node.glif.io";
+/// Time after which unanswered RPC requests fail, so a hung node doesn't leave the UI waiting.
+const DEFAULT_RPC_TIMEOUT: Duration = Duration::from_secs(30);
+/// Times a request is repeated after a transient failure, e.g. a dropped connection.
+const DEFAULT_RPC_RETRIES: u32 = 2;
+/// Delay before the first retry, doubled for each following one.
+const DEFAULT_RPC_RETRY_DELAY: Duration = Duration::from_millis(500);
+/// Largest response body read from a node, so a misbehaving endpoint can't exhaust the memory of
+/// the worker or the browser.
+const DEFAULT_MAX_RESPONSE_SIZE: usize = 16 * 1024 * 1024;
+
+#[derive(Clone, Copy)]
+pub struct RpcContext {
+    #[allow(unused)]
+    network: LocalResource<Network>,
+    provider: RwSignal<Provider>,
+}
+
+impl RpcContext {
+    pub fn new() -> Self {
+        let provider = RwSignal::new(Provider::new(GLIF_CALIBNET.to_string()));
+        let network = LocalResource::new(move || {
+            let provider = provider.get();
+            async move {
+                if provider.network_name().await.ok() != Some("mainnet".to_string()) {
+                    Network::Testnet
+                } else {
+                    Network::Mainnet
+                }
+            }
+        });
+        Effect::new(move |_| {
+            log::info!("Updating network: {:?}", network.get());
+            set_current_network(
+                network
+                    .get()
+                    .as_deref()
+                    .cloned()
+                    .unwrap_or(Network::Testnet),
+            );
+        });
+        Self { network, provider }
+    }
+
+    pub fn provide_context() {
+        provide_context(RpcContext::new());
+    }
+
+    pub fn use_context() -> Self {
+        use_context::<Self>().expect("RpcContext should be provided")
+    }
+
+    pub fn get(&self) -> Provider {
+        self.provider.get()
+    }
+
+    pub fn set(&self, provider: String) {
+        self.provider.set(Provider::new(provider));
+    }
+
+    /// Switches to the node at `url`, leaving the provider unchanged if the URL is invalid.
+    pub fn try_set(&self, url: &str) -> anyhow::Result<()> {
+        self.provider.set(Provider::from_url(url)?);
+        Ok(())
+    }
+
+    /// The network served by the current provider, assuming calibnet until it's known.
+    pub fn network(&self) -> Network {
+        self.network
+            .get()
+            .as_deref()
+            .cloned()
+            .unwrap_or(Network::Testnet)
+    }
+}
+
+/// The ID and public key forms of an address, as far as they resolve.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolvedAddress {
+    /// `None` if there's no actor at the address yet.
+    pub id: Option<Address>,
+    /// `None` for actors without a key, e.g. multisigs.
+    pub key: Option<Address>,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Provider {
+    url: String,
+    /// How long to wait for the node before giving up on a request.
+    timeout: Duration,
+    retries: u32,
+    retry_base_delay: Duration,
+    /// Responses with a larger body, in bytes, are abandoned.
+    max_response_size: usize,
+}
+
+/// Sends a JSON-RPC request and returns the raw response body once it's known to be JSON. The
+/// request is repeated up to `retries` times after transient failures.
+async fn post_rpc(
+    provider: &Provider,
+    method: &str,
+    params: &[Value],
+    retries: u32,
+) -> anyhow::Result<String> {
+    retry::with_retries(retries, provider.retry_base_delay, retry::sleep, || {
+        post_rpc_once(provider, method, params)
+    })
+    .await
+}
+
+async fn post_rpc_once(
+    provider: &Provider,
+    method: &str,
+    params: &[Value],
+) -> anyhow::Result<String> {
+    post_json_once(provider, method, &request_object(method, params, 0)).await
+}
+
+fn request_object(method: &str, params: &[Value], id: usize) -> Value {
+    json! {
+        {
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": id
+        }
+    }
+}
+
+/// Posts a JSON-RPC request body, a single request or a batch. `method` names it in errors.
+async fn post_json_once(
+    provider: &Provider,
+    method: &str,
+    request: &Value,
+) -> anyhow::Result<String> {
+    let res = CLIENT
+        .post(&provider.url)
+        .timeout(provider.timeout)
+        .json(request)
+        .send()
+        .await
+        .map_err(|e| timeout_error(e, method, provider.timeout))?;
+    let status = res.status().as_u16();
+    let content_type = res
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .map(str::to_string);
+    if res
+        .content_length()
+        .is_some_and(|len| len > provider.max_response_size as u64)
+    {
+        return Err(response_too_large(
+            status,
+            method,
+            provider.max_response_size,
+        ));
+    }
+    let chunks = res
+        .bytes_stream()
+        .map(|chunk| chunk.map_err(|e| timeout_error(e, method, provider.timeout)));
+    let body = read_body(chunks, status, method, provider.max_response_size).await?;
+    error::check_response(status, content_type.as_deref(), &body)?;
+    Ok(body)
+}
+
+/// Collects a response body, giving up as soon as it grows past `limit` bytes.
+async fn read_body<B: AsRef<[u8]>>(
+    chunks: impl Stream<Item = anyhow::Result<B>>,
+    status: u16,
+    method: &str,
+    limit: usize,
+) -> anyhow::Result<String> {
+    let mut chunks = std::pin::pin!(chunks);
+    let mut body = Vec::new();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk?;
+        if body.len() + chunk.as_ref().len() > limit {
+            return Err(response_too_large(status, method, limit));
+        }
+        body.extend_from_slice(chunk.as_ref());
+    }
+    Ok(String::from_utf8(body)?)
+}
+
+fn response_too_large(status: u16, method: &str, limit: usize) -> anyhow::Error {
+    anyhow::Error::new(RpcError::Transport {
+        status,
+        body: format!("over {limit} bytes, not read"),
+    })
+    .context(format!(
+        "RPC response to {method} exceeds the {limit} byte limit"
+    ))
+}
+
+/// Explains timeouts, which reqwest only reports as "operation timed out".
+fn timeout_error(e: reqwest::Error, method: &str, timeout: Duration) -> anyhow::Error {
+    let timed_out = e.is_timeout();
+    let e = anyhow::Error::new(e);
+    if timed_out {
+        e.context(format!(
+            "RPC request to {method} timed out after {}s",
+            timeout.as_secs()
+        ))
+    } else {
+        e
+    }
+}
+
+async fn invoke_rpc_method<T: HasLotusJson + Clone>(
+    provider: &Provider,
+    method: &str,
+    params: &[Value],
+) -> anyhow::Result<T> {
+    call(provider, method, params, provider.retries).await
+}
+
+/// Like [`invoke_rpc_method`], but never repeats the request, for methods which mustn't run twice,
+/// e.g. `Filecoin.MpoolPush`.
+async fn invoke_rpc_method_once<T: HasLotusJson + Clone>(
+    provider: &Provider,
+    method: &str,
+    params: &[Value],
+) -> anyhow::Result<T> {
+    call(provider, method, params, 0).await
+}
+
+async fn call<T: HasLotusJson + Clone>(
+    provider: &Provider,
+    method: &str,
+    params: &[Value],
+    retries: u32,
+) -> anyhow::Result<T> {
+    let body = post_rpc(provider, method, params, retries).await?;
+    parse_response(&body)
+}
+
+/// Decodes the result of a JSON-RPC response body. Error objects become an [`RpcError`], so the
+/// node's reason, e.g. "insufficient funds", reaches the user.
+fn parse_response<T: HasLotusJson + Clone>(body: &str) -> anyhow::Result<T> {
+    parse_response_value(serde_json::from_str(body)?)
+}
+
+fn parse_response_value<T: HasLotusJson + Clone>(mut body: Value) -> anyhow::Result<T> {
+    match body.get_mut("error").map(Value::take) {
+        Some(Value::Null) | None => (),
+        Some(error) => {
+            let error: error::ErrorObject = serde_json::from_value(error)?;
+            return Err(RpcError::from(error).into());
+        }
+    }
+    let LotusJson(ret) = serde_json::from_value(
+        body.get_mut("result")
+            .map(Value::take)
+            .ok_or(anyhow::anyhow!("No result"))?,
+    )?;
+    Ok(ret)
+}
+
+/// Decodes the response to a batch of `len` calls. Servers may answer in any order, so entries
+/// are matched to calls by `id`. A whole-request error, e.g. because the server doesn't support
+/// batches, fails the batch; errors of single calls only fail their entry.
+fn parse_batch_response<T: HasLotusJson + Clone>(
+    body: &str,
+    len: usize,
+) -> anyhow::Result<Vec<anyhow::Result<T>>> {
+    let entries = match serde_json::from_str::<Value>(body)? {
+        Value::Array(entries) => entries,
+        object => {
+            parse_response_value::<Value>(object)?;
+            anyhow::bail!("Expected an array in response to a batch request")
+        }
+    };
+    let mut results: Vec<Option<anyhow::Result<T>>> = (0..len).map(|_| None).collect();
+    for entry in entries {
+        let id = entry.get("id").and_then(Value::as_u64);
+        match id.and_then(|id| results.get_mut(id as usize)) {
+            Some(slot) => *slot = Some(parse_response_value(entry)),
+            None => log::warn!("Ignoring batch response entry with unknown id {id:?}"),
+        }
+    }
+    Ok(results
+        .into_iter()
+        .enumerate()
+        .map(|(id, result)| {
+            result.unwrap_or_else(|| Err(anyhow::anyhow!("No response to batch call {id}")))
+        })
+        .collect())
+}
+
+/// Like [`invoke_rpc_method`] for methods returning large arrays, e.g. `Filecoin.StateListMiners`.
+/// Elements are passed to `f` one by one as they're decoded from the body, without building an
+/// intermediate `Value` for the whole response.
+async fn invoke_rpc_method_each<T: HasLotusJson + Clone>(
+    provider: &Provider,
+    method: &str,
+    params: &[Value],
+    f: impl FnMut(T),
+) -> anyhow::Result<()> {
+    let body = post_rpc(provider, method, params, provider.retries).await?;
+    stream::for_each_result(&body, f)
+}
+
+/// The RPC methods the faucet uses. Implemented by [`Provider`] and, in tests, by mocks returning
+/// canned values, so the faucet controller can be tested without a node.
+pub trait RpcProvider {
+    async fn chain_head(&self) -> anyhow::Result<TipSet>;
+    async fn wallet_balance(&self, address: Address) -> anyhow::Result<TokenAmount>;
+    async fn estimate_gas(&self, msg: Message, tsk: Option<TipSetKey>) -> anyhow::Result<Message>;
+    async fn next_nonce(&self, addr: Address) -> anyhow::Result<u64>;
+    async fn mpool_push(&self, smsg: SignedMessage) -> anyhow::Result<Cid>;
+    async fn state_lookup_id(&self, addr: Address) -> anyhow::Result<Option<Address>>;
+    async fn resolve_address(&self, addr: Address) -> anyhow::Result<ResolvedAddress>;
+    async fn actor_kind(&self, addr: Address) -> anyhow::Result<Option<ActorKind>>;
+    async fn state_call(
+        &self,
+        msg: Message,
+        tsk: Option<TipSetKey>,
+    ) -> anyhow::Result<crate::lotus_json::InvocResult>;
+    async fn state_search_msgs(
+        &self,
+        msgs: &[Cid],
+    ) -> anyhow::Result<Vec<anyhow::Result<Option<crate::lotus_json::MessageLookup>>>>;
+    #[cfg(feature = "hydrate")]
+    async fn state_list_messages(
+        &self,
+        from: Address,
+        since: fvm_shared::clock::ChainEpoch,
+    ) -> anyhow::Result<Vec<Cid>>;
+    #[cfg(feature = "hydrate")]
+    async fn chain_get_messages(
+        &self,
+        cids: &[Cid],
+    ) -> anyhow::Result<Vec<anyhow::Result<Message>>>;
+}
+
+impl RpcProvider for Provider {
+    async fn chain_head(&self) -> anyhow::Result<TipSet> {
+        Provider::chain_head(self).await
+    }
+
+    async fn wallet_balance(&self, address: Address) -> anyhow::Result<TokenAmount> {
+        Provider::wallet_balance(self, address).await
+    }
+
+    async fn estimate_gas(&self, msg: Message, tsk: Option<TipSetKey>) -> anyhow::Result<Message> {
+        Provider::estimate_gas(self, msg, tsk).await
+    }
+
+    async fn next_nonce(&self, addr: Address) -> anyhow::Result<u64> {
+        Provider::next_nonce(self, addr).await
+    }
+
+    async fn mpool_push(&self, smsg: SignedMessage) -> anyhow::Result<Cid> {
+        Provider::mpool_push(self, smsg).await
+    }
+
+    async fn state_lookup_id(&self, addr: Address) -> anyhow::Result<Option<Address>> {
+        Provider::state_lookup_id(self, addr).await
+    }
+
+    async fn resolve_address(&self, addr: Address) -> anyhow::Result<ResolvedAddress> {
+        Provider::resolve_address(self, addr).await
+    }
+
+    async fn actor_kind(&self, addr: Address) -> anyhow::Result<Option<ActorKind>> {
+        Provider::actor_kind(self, addr).await
+    }
+
+    async fn state_call(
+        &self,
+        msg: Message,
+        tsk: Option<TipSetKey>,
+    ) -> anyhow::Result<crate::lotus_json::InvocResult> {
+        Provider::state_call(self, msg, tsk).await
+    }
+
+    async fn state_search_msgs(
+        &self,
+        msgs: &[Cid],
+    ) -> anyhow::Result<Vec<anyhow::Result<Option<crate::lotus_json::MessageLookup>>>> {
+        Provider::state_search_msgs(self, msgs).await
+    }
+
+    #[cfg(feature = "hydrate")]
+    async fn state_list_messages(
+        &self,
+        from: Address,
+        since: fvm_shared::clock::ChainEpoch,
+    ) -> anyhow::Result<Vec<Cid>> {
+        Provider::state_list_messages(self, from, since).await
+    }
+
+    #[cfg(feature = "hydrate")]
+    async fn chain_get_messages(
+        &self,
+        cids: &[Cid],
+    ) -> anyhow::Result<Vec<anyhow::Result<Message>>> {
+        Provider::chain_get_messages(self, cids).await
+    }
+}
+
+impl Provider {
+    pub fn new(url: String) -> Self {
+        Self::with_timeout(url, DEFAULT_RPC_TIMEOUT)
+    }
+
+    /// A provider giving up on requests to `url` after `timeout`.
+    pub fn with_timeout(url: String, timeout: Duration) -> Self {
+        Self {
+            url,
+            timeout,
+            retries: DEFAULT_RPC_RETRIES,
+            retry_base_delay: DEFAULT_RPC_RETRY_DELAY,
+            max_response_size: DEFAULT_MAX_RESPONSE_SIZE,
+        }
+    }
+
+    /// Repeats requests up to `retries` times after transient failures, waiting `base_delay`
+    /// before the first retry and twice as long before each following one.
+    #[cfg(feature = "ssr")]
+    pub fn with_retries(mut self, retries: u32, base_delay: Duration) -> Self {
+        self.retries = retries;
+        self.retry_base_delay = base_delay;
+        self
+    }
+
+    /// Abandons responses whose body is larger than `bytes`.
+    #[cfg(feature = "ssr")]
+    pub fn with_max_response_size(mut self, bytes: usize) -> Self {
+        self.max_response_size = bytes;
+        self
+    }
+
+    /// A provider for the node at `url`, which must be an `http` or `https` URL.
+    pub fn from_url(url: &str) -> anyhow::Result<Self> {
+        let parsed = url::Url::parse(url.trim())
+            .map_err(|e| anyhow::anyhow!("Invalid RPC URL {url}: {e}"))?;
+        anyhow::ensure!(
+            matches!(parsed.scheme(), "http" | "https"),
+            "Unsupported RPC URL scheme {}, expected http or https",
+            parsed.scheme()
+        );
+        // Keep the URL as typed: `Url` would add a trailing slash, so it no longer matches the
+        // network URLs and the stored one.
+        Ok(Self::new(url.trim().to_string()))
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn get_network_url(network: Network) -> String {
+        match network {
+            Network::Testnet => GLIF_CALIBNET.to_string(),
+            Network::Mainnet => GLIF_MAINNET.to_string(),
+        }
+    }
+
+    pub fn calibnet() -> Self {
+        Self::new(GLIF_CALIBNET.to_string())
+    }
+
+    pub fn mainnet() -> Self {
+        Self::new(GLIF_MAINNET.to_string())
+    }
+
+    pub fn from_network(network: Network) -> Self {
+        match network {
+            Network::Testnet => Self::calibnet(),
+            Network::Mainnet => Self::mainnet(),
+        }
+    }
+
+    /// Access to the `eth_*` methods served by the same endpoint.
+    pub fn eth(&self) -> EthProvider {
+        EthProvider::new(self.clone())
+    }
+
+    pub async fn network_name(&self) -> anyhow::Result<String> {
+        invoke_rpc_method(self, "Filecoin.StateNetworkName", &[]).await
+    }
+
+    pub async fn network_version(&self) -> anyhow::Result<NetworkVersion> {
+        invoke_rpc_method(self, "Filecoin.StateNetworkVersion", &[Value::Null]).await
+    }
+
+    pub async fn chain_head(&self) -> anyhow::Result<TipSet> {
+        invoke_rpc_method(self, "Filecoin.ChainHead", &[]).await
+    }
+
+    /// The header of the block with the given CID.
+    pub async fn chain_get_block(&self, cid: Cid) -> anyhow::Result<BlockHeader> {
+        invoke_rpc_method(
+            self,
+            "Filecoin.ChainGetBlock",
+            &[serde_json::to_value(LotusJson(cid))?],
+        )
+        .await
+    }
+
+    /// The message with the given CID. Only messages included in a block are known, not pending
+    /// ones.
+    pub async fn chain_get_message(&self, cid: Cid) -> anyhow::Result<Message> {
+        invoke_rpc_method(
+            self,
+            "Filecoin.ChainGetMessage",
+            &[serde_json::to_value(LotusJson(cid))?],
+        )
+        .await
+    }
+
+    /// Messages executed in the tipset on top of the parents of `block_cid`. The genesis block
+    /// has no parents, so this is empty for it.
+    pub async fn chain_get_parent_messages(
+        &self,
+        block_cid: Cid,
+    ) -> anyhow::Result<Vec<(Cid, Message)>> {
+        let messages: Vec<ApiMessage> = invoke_rpc_method(
+            self,
+            "Filecoin.ChainGetParentMessages",
+            &[serde_json::to_value(LotusJson(block_cid))?],
+        )
+        .await?;
+        Ok(messages
+            .into_iter()
+            .map(|ApiMessage { cid, message }| (cid, message))
+            .collect())
+    }
+
+    /// Receipts for [`Provider::chain_get_parent_messages`], in the same order. Empty for the
+    /// genesis block.
+    pub async fn chain_get_parent_receipts(&self, block_cid: Cid) -> anyhow::Result<Vec<Receipt>> {
+        invoke_rpc_method(
+            self,
+            "Filecoin.ChainGetParentReceipts",
+            &[serde_json::to_value(LotusJson(block_cid))?],
+        )
+        .await
+    }
+
+    pub async fn wallet_balance(&self, address: Address) -> anyhow::Result<TokenAmount> {
+        invoke_rpc_method(
+            self,
+            "Filecoin.WalletBalance",
+            &[serde_json::to_value(LotusJson(address))?],
+        )
+        .await
+    }
+
+    /// Fills in the gas fields of `msg`, estimated against the tipset `tsk`, or against the
+    /// node's current head if `None`.
+    pub async fn estimate_gas(
+        &self,
+        msg: Message,
+        tsk: Option<TipSetKey>,
+    ) -> anyhow::Result<Message> {
+        invoke_rpc_method(
+            self,
+            "Filecoin.GasEstimateMessageGas",
+            &[
+                serde_json::to_value(LotusJson(msg))?,
+                Value::Null,
+                serde_json::to_value(LotusJson(tsk))?,
+            ],
+        )
+        .await
+    }
+
+    pub async fn mpool_get_nonce(&self, addr: Address) -> anyhow::Result<u64> {
+        invoke_rpc_method(
+            self,
+            "Filecoin.MpoolGetNonce",
+            &[serde_json::to_value(LotusJson(addr))?],
+        )
+        .await
+    }
+
+    /// Next nonce for `addr`. Uses the message pool so pending messages are accounted for, and
+    /// falls back to the on-chain actor nonce on nodes which don't serve mempool methods.
+    pub async fn next_nonce(&self, addr: Address) -> anyhow::Result<u64> {
+        match self.mpool_get_nonce(addr).await {
+            Ok(nonce) => {
+                log::info!("Nonce {nonce} for {addr} from MpoolGetNonce");
+                Ok(nonce)
+            }
+            Err(e)
+                if e.downcast_ref::<RpcError>()
+                    .is_some_and(RpcError::is_method_not_found) =>
+            {
+                let nonce = self.state_get_actor(addr).await?.sequence;
+                log::info!("Nonce {nonce} for {addr} from StateGetActor (mempool unavailable)");
+                Ok(nonce)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Simulates `msg` on top of the tipset `tsk`, or the current head if `None`, without sending
+    /// it.
+    pub async fn state_call(
+        &self,
+        msg: Message,
+        tsk: Option<TipSetKey>,
+    ) -> anyhow::Result<crate::lotus_json::InvocResult> {
+        invoke_rpc_method(
+            self,
+            "Filecoin.StateCall",
+            &[
+                serde_json::to_value(LotusJson(msg))?,
+                serde_json::to_value(LotusJson(tsk))?,
+            ],
+        )
+        .await
+    }
+
+    /// Public key address of the account actor behind `addr`, or `None` if the actor has no key.
+    pub async fn state_account_key(&self, addr: Address) -> anyhow::Result<Option<Address>> {
+        if let Some(key) = cache::cached_address(&self.url, Lookup::AccountKey, addr) {
+            return Ok(Some(key));
+        }
+        let res = invoke_rpc_method(
+            self,
+            "Filecoin.StateAccountKey",
+            &[serde_json::to_value(LotusJson(addr))?, Value::Null],
+        )
+        .await;
+        let key = account_key(res)?;
+        if let Some(key) = key {
+            cache::cache_address(&self.url, Lookup::AccountKey, addr, key);
+        }
+        Ok(key)
+    }
+
+    /// Resolves both the ID and public key forms of `addr`.
+    pub async fn resolve_address(&self, addr: Address) -> anyhow::Result<ResolvedAddress> {
+        Ok(if addr.protocol() == Protocol::ID {
+            ResolvedAddress {
+                id: Some(addr),
+                key: self.state_account_key(addr).await?,
+            }
+        } else {
+            ResolvedAddress {
+                id: self.state_lookup_id(addr).await?,
+                key: Some(addr),
+            }
+        })
+    }
+
+    /// ID address of the actor behind `addr`, or `None` if there's no actor yet.
+    pub async fn state_lookup_id(&self, addr: Address) -> anyhow::Result<Option<Address>> {
+        if let Some(id) = cache::cached_address(&self.url, Lookup::Id, addr) {
+            return Ok(Some(id));
+        }
+        let res = invoke_rpc_method(
+            self,
+            "Filecoin.StateLookupID",
+            &[serde_json::to_value(LotusJson(addr))?, Value::Null],
+        )
+        .await;
+        match res {
+            Ok(id) => {
+                cache::cache_address(&self.url, Lookup::Id, addr, id);
+                Ok(Some(id))
+            }
+            Err(e)
+                if e.downcast_ref::<RpcError>()
+                    .is_some_and(RpcError::is_actor_not_found) =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn state_get_actor(&self, addr: Address) -> anyhow::Result<ActorState> {
+        invoke_rpc_method(
+            self,
+            "Filecoin.StateGetActor",
+            &[serde_json::to_value(LotusJson(addr))?, Value::Null],
+        )
+        .await
+    }
+
+    /// Built-in actor code CIDs by actor name, e.g. `"evm"`, for the given network version.
+    pub async fn state_actor_code_cids(
+        &self,
+        network_version: NetworkVersion,
+    ) -> anyhow::Result<HashMap<String, Cid>> {
+        invoke_rpc_method(
+            self,
+            "Filecoin.StateActorCodeCIDs",
+            &[serde_json::to_value(LotusJson(network_version))?],
+        )
+        .await
+    }
+
+    /// Owner, worker and network details of the storage provider at `miner`.
+    pub async fn state_miner_info(&self, miner: Address) -> anyhow::Result<MinerInfo> {
+        invoke_rpc_method(
+            self,
+            "Filecoin.StateMinerInfo",
+            &[serde_json::to_value(LotusJson(miner))?, Value::Null],
+        )
+        .await
+    }
+
+    /// Addresses of all storage providers at the chain head.
+    pub async fn state_list_miners(&self) -> anyhow::Result<Vec<Address>> {
+        let mut miners = Vec::new();
+        invoke_rpc_method_each(self, "Filecoin.StateListMiners", &[Value::Null], |miner| {
+            miners.push(miner)
+        })
+        .await?;
+        Ok(miners)
+    }
+
+    /// The proposal and state of the storage deal `deal_id` at the chain head.
+    pub async fn state_market_storage_deal(&self, deal_id: u64) -> anyhow::Result<MarketDeal> {
+        invoke_rpc_method(
+            self,
+            "Filecoin.StateMarketStorageDeal",
+            &[Value::Number(deal_id.into()), Value::Null],
+        )
+        .await
+    }
+
+    /// Kind of the actor behind `addr`, or `None` if there's no actor yet.
+    pub async fn actor_kind(&self, addr: Address) -> anyhow::Result<Option<ActorKind>> {
+        let actor = match self.state_get_actor(addr).await {
+            Ok(actor) => actor,
+            Err(e)
+                if e.downcast_ref::<RpcError>()
+                    .is_some_and(RpcError::is_actor_not_found) =>
+            {
+                return Ok(None)
+            }
+            Err(e) => return Err(e),
+        };
+        let code_cids = self
+            .state_actor_code_cids(self.network_version().await?)
+            .await?;
+        Ok(Some(ActorKind::from_code(&actor.code, &code_cids)))
+    }
+
+    /// Sends `calls` as a single JSON-RPC batch request, saving round trips when e.g. polling
+    /// several messages. Results are in the order of `calls`, and each may fail on its own. Calls
+    /// must be safe to repeat, as the whole batch is retried after transient failures.
+    pub async fn batch<T: HasLotusJson + Clone>(
+        &self,
+        calls: Vec<(&str, Vec<Value>)>,
+    ) -> anyhow::Result<Vec<anyhow::Result<T>>> {
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+        let request = Value::Array(
+            calls
+                .iter()
+                .enumerate()
+                .map(|(id, (method, params))| request_object(method, params, id))
+                .collect(),
+        );
+        let label = format!("a batch of {} calls", calls.len());
+        let body = retry::with_retries(self.retries, self.retry_base_delay, retry::sleep, || {
+            post_json_once(self, &label, &request)
+        })
+        .await?;
+        parse_batch_response(&body, calls.len())
+    }
+
+    /// CIDs of the messages sent by `from` since the epoch `since`, newest first.
+    #[cfg(feature = "hydrate")]
+    pub async fn state_list_messages(
+        &self,
+        from: Address,
+        since: fvm_shared::clock::ChainEpoch,
+    ) -> anyhow::Result<Vec<Cid>> {
+        invoke_rpc_method(
+            self,
+            "Filecoin.StateListMessages",
+            &[
+                json!({ "From": LotusJson(from) }),
+                Value::Null,
+                Value::Number(since.into()),
+            ],
+        )
+        .await
+    }
+
+    /// [`Self::chain_get_message`] for several messages in one batch request. Each may fail on its
+    /// own, e.g. if the node doesn't know it.
+    #[cfg(feature = "hydrate")]
+    pub async fn chain_get_messages(
+        &self,
+        cids: &[Cid],
+    ) -> anyhow::Result<Vec<anyhow::Result<Message>>> {
+        let calls = cids
+            .iter()
+            .map(|cid| {
+                Ok((
+                    "Filecoin.ChainGetMessage",
+                    vec![serde_json::to_value(LotusJson(*cid))?],
+                ))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        self.batch(calls).await
+    }
+
+    /// Pushes a signed message to the message pool. Never retried, so a message is never submitted
+    /// twice.
+    pub async fn mpool_push(&self, smsg: SignedMessage) -> anyhow::Result<Cid> {
+        invoke_rpc_method_once(
+            self,
+            "Filecoin.MpoolPush",
+            &[serde_json::to_value(LotusJson(smsg))?],
+        )
+        .await
+    }
+
+    pub async fn state_search_msg(
+        &self,
+        msg: Cid,
+    ) -> anyhow::Result<Option<crate::lotus_json::MessageLookup>> {
+        invoke_rpc_method(self, "Filecoin.StateSearchMsg", &search_msg_params(msg)?).await
+    }
+
+    /// [`Self::state_search_msg`] for several messages in one batch request, falling back to
+    /// one request per message if the node doesn't answer the batch.
+    pub async fn state_search_msgs(
+        &self,
+        msgs: &[Cid],
+    ) -> anyhow::Result<Vec<anyhow::Result<Option<crate::lotus_json::MessageLookup>>>> {
+        let calls = msgs
+            .iter()
+            .map(|msg| Ok(("Filecoin.StateSearchMsg", search_msg_params(*msg)?)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        match self.batch(calls).await {
+            Ok(results) => Ok(results),
+            Err(e) => {
+                log::warn!("Batch request failed, searching messages one by one: {e}");
+                let mut results = Vec::with_capacity(msgs.len());
+                for msg in msgs {
+                    results.push(self.state_search_msg(*msg).await);
+                }
+                Ok(results)
+            }
+        }
+    }
+}
+
+/// The result of `Filecoin.StateAccountKey`, taking the node rejecting the call to mean the actor
+/// has no key. Failing to reach the node is still an error.
+fn account_key(res: anyhow::Result<Address>) -> anyhow::Result<Option<Address>> {
+    match res {
+        Ok(key) => Ok(Some(key)),
+        // Lotus reports non-account actors with a variety of messages.
+        Err(e) if matches!(e.downcast_ref::<RpcError>(), Some(RpcError::JsonRpc { .. })) => {
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn search_msg_params(msg: Cid) -> anyhow::Result<Vec<Value>> {
+    Ok(vec![
+        Value::Null,
+        serde_json::to_value(LotusJson(msg))?,
+        Value::Number(10.into()),
+        Value::Bool(false),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn test_user_agent() {
+        if option_env!("RPC_USER_AGENT").is_none() {
+            assert_eq!(
+                USER_AGENT,
+                format!("forest-explorer/{}", env!("CARGO_PKG_VERSION"))
+            );
+        }
+        assert!(reqwest::header::HeaderValue::from_str(USER_AGENT).is_ok());
+    }
+
+    #[test]
+    fn test_account_key() {
+        let key = Address::new_id(1234);
+        assert_eq!(account_key(Ok(key)).unwrap(), Some(key));
+
+        let not_an_account = RpcError::JsonRpc {
+            code: 1,
+            message: "failed to get account actor state: actor code is not account".to_string(),
+        };
+        assert_eq!(account_key(Err(not_an_account.into())).unwrap(), None);
+
+        let gateway = RpcError::Transport {
+            status: 502,
+            body: "<html>Bad Gateway</html>".to_string(),
+        };
+        let err = account_key(Err(gateway.into())).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<RpcError>(),
+            Some(RpcError::Transport { status: 502, .. })
+        ));
+    }
+
+    #[test]
+    fn test_read_body_limit() {
+        use futures::executor::block_on;
+        use futures::stream;
+
+        let chunks = || stream::iter(["{\"result\":", " 1}"].map(anyhow::Ok));
+        assert_eq!(
+            block_on(read_body(chunks(), 200, "Filecoin.ChainHead", 16)).unwrap(),
+            "{\"result\": 1}"
+        );
+
+        let err = block_on(read_body(chunks(), 200, "Filecoin.ChainHead", 12)).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<RpcError>(),
+            Some(RpcError::Transport { status: 200, .. })
+        ));
+        assert_eq!(
+            err.to_string(),
+            "RPC response to Filecoin.ChainHead exceeds the 12 byte limit"
+        );
+    }
+
+    #[test]
+    fn test_parse_response_error() {
+        let err = parse_response::<Cid>(
+            r#"{"jsonrpc":"2.0","id":0,"error":{"code":-32000,"message":"mpool push: not enough funds (required: 1.5 FIL, balance: 1 FIL)"}}"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<RpcError>(),
+            Some(&RpcError::JsonRpc {
+                code: -32000,
+                message: "mpool push: not enough funds (required: 1.5 FIL, balance: 1 FIL)"
+                    .to_string()
+            })
+        );
+        assert_eq!(
+            err.to_string(),
+            "RPC error -32000: mpool push: not enough funds (required: 1.5 FIL, balance: 1 FIL)"
+        );
+
+        let err = parse_response::<Cid>(
+            r#"{"jsonrpc":"2.0","id":0,"error":{"code":1,"message":"minimum expected nonce is 5: message nonce too low"}}"#,
+        )
+        .unwrap_err();
+        assert!(err
+            .downcast_ref::<RpcError>()
+            .is_some_and(RpcError::is_nonce_too_low));
+    }
+
+    #[test]
+    fn test_parse_batch_response() {
+        // Entries are matched by id, whatever their order.
+        let results = parse_batch_response::<u64>(
+            r#"[
+                {"jsonrpc":"2.0","id":2,"result":30},
+                {"jsonrpc":"2.0","id":0,"result":10},
+                {"jsonrpc":"2.0","id":1,"error":{"code":1,"message":"actor not found"}}
+            ]"#,
+            4,
+        )
+        .unwrap();
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].as_ref().unwrap(), &10);
+        assert!(results[1]
+            .as_ref()
+            .unwrap_err()
+            .downcast_ref::<RpcError>()
+            .is_some_and(RpcError::is_actor_not_found));
+        assert_eq!(results[2].as_ref().unwrap(), &30);
+        assert_eq!(
+            results[3].as_ref().unwrap_err().to_string(),
+            "No response to batch call 3"
+        );
+
+        // Servers without batch support answer with a single error.
+        let err = parse_batch_response::<u64>(
+            r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32600,"message":"batch not supported"}}"#,
+            2,
+        )
+        .unwrap_err();
+        assert!(err.downcast_ref::<RpcError>().is_some());
+    }
+
+    #[test]
+    fn test_parse_response_result() {
+        assert_eq!(
+            parse_response::<u64>(r#"{"jsonrpc":"2.0","id":0,"result":23}"#).unwrap(),
+            23
+        );
+        assert_eq!(
+            parse_response::<u64>(r#"{"jsonrpc":"2.0","id":0,"result":23,"error":null}"#).unwrap(),
+            23
+        );
+        let err = parse_response::<u64>(r#"{"jsonrpc":"2.0","id":0}"#).unwrap_err();
+        assert_eq!(err.to_string(), "No result");
+    }
+
+    #[test]
+    fn test_provider_from_url() {
+        assert_eq!(
+            Provider::from_url(" http://localhost:2345/rpc/v1 ")
+                .unwrap()
+                .url(),
+            "http://localhost:2345/rpc/v1"
+        );
+        assert_eq!(
+            Provider::from_url(GLIF_MAINNET).unwrap().url(),
+            Provider::get_network_url(Network::Mainnet)
+        );
+        assert!(Provider::from_url("api.node.glif.io").is_err());
+        assert!(Provider::from_url("ws://localhost:2345/rpc/v1").is_err());
+        assert!(Provider::from_url("").is_err());
+    }
+}
@@ -0,0 +1,153 @@
+use std::future::Future;
+use std::time::Duration;
+
+use wasm_bindgen::prelude::*;
+
+use super::RpcError;
+
+/// Longest wait between two attempts, however many retries are configured.
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Whether a failed request may succeed when repeated: the node couldn't be reached, or failed
+/// with a 5xx without answering the call. JSON-RPC errors are answers, and are never retried.
+/// Neither are timeouts, as repeating a request that took too long only makes the user wait
+/// longer.
+pub(super) fn is_transient(e: &anyhow::Error) -> bool {
+    if let Some(e) = e.downcast_ref::<reqwest::Error>() {
+        return !e.is_builder() && !e.is_decode() && !e.is_timeout();
+    }
+    matches!(
+        e.downcast_ref::<RpcError>(),
+        Some(RpcError::Transport { status, .. }) if *status >= 500
+    )
+}
+
+/// Runs `op`, repeating it up to `retries` times after transient failures. The delay between
+/// attempts starts at `base_delay` and doubles after each one, up to [`MAX_DELAY`].
+pub(super) async fn with_retries<
+    T,
+    F: Future<Output = anyhow::Result<T>>,
+    S: Future<Output = ()>,
+>(
+    retries: u32,
+    base_delay: Duration,
+    sleep: impl Fn(Duration) -> S,
+    mut op: impl FnMut() -> F,
+) -> anyhow::Result<T> {
+    let mut attempt = 0;
+    loop {
+        match op().await {
+            Err(e) if is_transient(&e) && attempt < retries => {
+                let delay = base_delay
+                    .saturating_mul(2u32.saturating_pow(attempt))
+                    .min(MAX_DELAY);
+                log::warn!("Retrying in {delay:?} after a transient RPC failure: {e}");
+                sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) if attempt > 0 => {
+                let message = format!("{e} (gave up after {} attempts)", attempt + 1);
+                return Err(e.context(message));
+            }
+            res => return res,
+        }
+    }
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &js_sys::Function, timeout: i32) -> JsValue;
+}
+
+/// Waits for `duration` using the JS timer, which exists both in browsers and in workers.
+pub(super) async fn sleep(duration: Duration) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        set_timeout(&resolve, duration.as_millis() as i32);
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use std::cell::RefCell;
+
+    fn transport(status: u16) -> anyhow::Error {
+        RpcError::Transport {
+            status,
+            body: "<html>".to_string(),
+        }
+        .into()
+    }
+
+    /// Runs `with_retries` over `results`, returning the outcome and the delays slept.
+    fn run(
+        retries: u32,
+        results: Vec<anyhow::Result<u32>>,
+    ) -> (anyhow::Result<u32>, Vec<Duration>) {
+        let results = RefCell::new(results.into_iter());
+        let delays = RefCell::new(Vec::new());
+        let res = block_on(with_retries(
+            retries,
+            Duration::from_millis(100),
+            |delay| {
+                delays.borrow_mut().push(delay);
+                async {}
+            },
+            || {
+                let next = results.borrow_mut().next().unwrap();
+                async { next }
+            },
+        ));
+        (res, delays.into_inner())
+    }
+
+    #[test]
+    fn test_retries_transient_failures_with_backoff() {
+        let (res, delays) = run(3, vec![Err(transport(502)), Err(transport(503)), Ok(7)]);
+        assert_eq!(res.unwrap(), 7);
+        assert_eq!(
+            delays,
+            vec![Duration::from_millis(100), Duration::from_millis(200)]
+        );
+    }
+
+    #[test]
+    fn test_caps_delay() {
+        let (res, delays) = run(40, (0..41).map(|_| Err(transport(502))).collect());
+        assert!(res.is_err());
+        assert_eq!(delays.len(), 40);
+        assert_eq!(delays[8], Duration::from_millis(25_600));
+        assert!(delays[9..].iter().all(|delay| *delay == MAX_DELAY));
+    }
+
+    #[test]
+    fn test_reports_attempts_when_exhausted() {
+        let (res, delays) = run(2, (0..3).map(|_| Err(transport(502))).collect());
+        let e = res.unwrap_err();
+        assert!(e.to_string().contains("gave up after 3 attempts"), "{e}");
+        assert!(e.downcast_ref::<RpcError>().is_some());
+        assert_eq!(delays.len(), 2);
+    }
+
+    #[test]
+    fn test_does_not_retry_answers() {
+        let json_rpc = RpcError::JsonRpc {
+            code: 1,
+            message: "actor not found".to_string(),
+        };
+        let (res, delays) = run(3, vec![Err(json_rpc.into())]);
+        assert!(res.is_err());
+        assert!(delays.is_empty());
+
+        let (res, delays) = run(3, vec![Err(transport(404))]);
+        assert!(res.is_err());
+        assert!(delays.is_empty());
+
+        let (res, delays) = run(0, vec![Err(transport(502))]);
+        assert_eq!(res.unwrap_err().to_string(), transport(502).to_string());
+        assert!(delays.is_empty());
+    }
+}
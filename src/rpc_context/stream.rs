@@ -0,0 +1,148 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::Deserializer;
+
+use super::error::{ErrorObject, RpcError};
+use crate::lotus_json::{HasLotusJson, LotusJson};
+
+/// Decodes a JSON-RPC response whose result is an array, handing each element to `f` as soon as
+/// it's decoded. Unlike the regular path, neither a `Value` tree of the response nor the whole
+/// result array is ever held in memory. A `null` result is treated as an empty array.
+pub(super) fn for_each_result<T: HasLotusJson + Clone>(
+    body: &str,
+    mut f: impl FnMut(T),
+) -> anyhow::Result<()> {
+    let mut deserializer = serde_json::Deserializer::from_str(body);
+    let envelope = Envelope {
+        f: &mut f,
+        item: PhantomData,
+    }
+    .deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    match envelope {
+        Outcome::Result => Ok(()),
+        Outcome::Error(error) => Err(RpcError::from(error).into()),
+        Outcome::Missing => Err(anyhow::anyhow!("No result")),
+    }
+}
+
+enum Outcome {
+    Result,
+    Error(ErrorObject),
+    Missing,
+}
+
+/// The `{"jsonrpc": ..., "result": [...], "error": ..., "id": ...}` object.
+struct Envelope<'a, T, F> {
+    f: &'a mut F,
+    item: PhantomData<T>,
+}
+
+impl<'de, T: HasLotusJson + Clone, F: FnMut(T)> DeserializeSeed<'de> for Envelope<'_, T, F> {
+    type Value = Outcome;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Outcome, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, T: HasLotusJson + Clone, F: FnMut(T)> Visitor<'de> for Envelope<'_, T, F> {
+    type Value = Outcome;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON-RPC response object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Outcome, A::Error> {
+        let mut outcome = Outcome::Missing;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "result" => {
+                    map.next_value_seed(Items {
+                        f: &mut *self.f,
+                        item: PhantomData,
+                    })?;
+                    outcome = Outcome::Result;
+                }
+                "error" => {
+                    if let Some(error) = map.next_value::<Option<ErrorObject>>()? {
+                        outcome = Outcome::Error(error);
+                    }
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(outcome)
+    }
+}
+
+/// The result array, decoded one element at a time.
+struct Items<'a, T, F> {
+    f: &'a mut F,
+    item: PhantomData<T>,
+}
+
+impl<'de, T: HasLotusJson + Clone, F: FnMut(T)> DeserializeSeed<'de> for Items<'_, T, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, T: HasLotusJson + Clone, F: FnMut(T)> Visitor<'de> for Items<'_, T, F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array or null")
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(LotusJson(item)) = seq.next_element::<LotusJson<T>>()? {
+            (self.f)(item);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fvm_shared::address::Address;
+
+    fn collect(body: &str) -> anyhow::Result<Vec<Address>> {
+        let mut items = Vec::new();
+        for_each_result(body, |item| items.push(item))?;
+        Ok(items)
+    }
+
+    #[test]
+    fn test_for_each_result() {
+        let items =
+            collect(r#"{"jsonrpc":"2.0","result":["f01000","f01001"],"id":0,"extra":{"a":[1]}}"#)
+                .unwrap();
+        assert_eq!(items, vec![Address::new_id(1000), Address::new_id(1001)]);
+
+        assert!(collect(r#"{"jsonrpc":"2.0","result":null,"id":0}"#)
+            .unwrap()
+            .is_empty());
+        assert!(collect(r#"{"jsonrpc":"2.0","id":0}"#).is_err());
+        assert!(collect(r#"{"jsonrpc":"2.0","result":["f01000", 5],"id":0}"#).is_err());
+    }
+
+    #[test]
+    fn test_for_each_result_error() {
+        let err =
+            collect(r#"{"jsonrpc":"2.0","error":{"code":1,"message":"actor not found"},"id":0}"#)
+                .unwrap_err();
+        assert!(err.downcast_ref::<RpcError>().is_some());
+    }
+}
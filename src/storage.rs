@@ -0,0 +1,169 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use leptos::prelude::*;
+use wasm_bindgen::prelude::*;
+
+/// Where a value is persisted in the browser.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageKind {
+    /// Kept across sessions.
+    Local,
+    /// Cleared when the browser session ends.
+    Session,
+}
+
+/// Raw access to a key-value store. Any call may fail, e.g. when storage is disabled.
+trait Backend {
+    fn get_item(&self, key: &str) -> Result<Option<String>, String>;
+    fn set_item(&self, key: &str, value: &str) -> Result<(), String>;
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = localStorage, js_name = getItem)]
+    fn local_get_item(key: &str) -> Result<Option<String>, JsValue>;
+    #[wasm_bindgen(catch, js_namespace = localStorage, js_name = setItem)]
+    fn local_set_item(key: &str, value: &str) -> Result<(), JsValue>;
+    #[wasm_bindgen(catch, js_namespace = sessionStorage, js_name = getItem)]
+    fn session_get_item(key: &str) -> Result<Option<String>, JsValue>;
+    #[wasm_bindgen(catch, js_namespace = sessionStorage, js_name = setItem)]
+    fn session_set_item(key: &str, value: &str) -> Result<(), JsValue>;
+}
+
+fn js_error(e: JsValue) -> String {
+    e.as_string()
+        .or_else(|| js_sys::JSON::stringify(&e).ok().map(String::from))
+        .unwrap_or_default()
+}
+
+impl Backend for StorageKind {
+    fn get_item(&self, key: &str) -> Result<Option<String>, String> {
+        match self {
+            StorageKind::Local => local_get_item(key),
+            StorageKind::Session => session_get_item(key),
+        }
+        .map_err(js_error)
+    }
+
+    fn set_item(&self, key: &str, value: &str) -> Result<(), String> {
+        match self {
+            StorageKind::Local => local_set_item(key, value),
+            StorageKind::Session => session_set_item(key, value),
+        }
+        .map_err(js_error)
+    }
+}
+
+/// Storage that switches to memory for the rest of the page's lifetime once the backend fails,
+/// e.g. in private browsing or sandboxed iframes.
+struct FallbackStorage<B> {
+    backend: B,
+    failed: Cell<bool>,
+    memory: RefCell<HashMap<String, String>>,
+}
+
+impl<B: Backend> FallbackStorage<B> {
+    fn new(backend: B) -> Self {
+        Self {
+            backend,
+            failed: Cell::new(false),
+            memory: RefCell::default(),
+        }
+    }
+
+    fn fail(&self, e: String) {
+        if !self.failed.replace(true) {
+            log::warn!("Browser storage is unavailable, keeping state in memory: {e}");
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        if !self.failed.get() {
+            match self.backend.get_item(key) {
+                Ok(value) => return value,
+                Err(e) => self.fail(e),
+            }
+        }
+        self.memory.borrow().get(key).cloned()
+    }
+
+    fn set(&self, key: &str, value: String) {
+        if !self.failed.get() {
+            if let Err(e) = self.backend.set_item(key, &value) {
+                self.fail(e);
+            }
+        }
+        self.memory.borrow_mut().insert(key.to_string(), value);
+    }
+}
+
+thread_local! {
+    static LOCAL: FallbackStorage<StorageKind> = FallbackStorage::new(StorageKind::Local);
+    static SESSION: FallbackStorage<StorageKind> = FallbackStorage::new(StorageKind::Session);
+}
+
+fn with_storage<R>(kind: StorageKind, f: impl FnOnce(&FallbackStorage<StorageKind>) -> R) -> R {
+    match kind {
+        StorageKind::Local => LOCAL.with(f),
+        StorageKind::Session => SESSION.with(f),
+    }
+}
+
+/// A signal persisted under `key`. It starts as `default` and picks up the stored value once
+/// mounted in the browser, so server and client render the same markup. Values that fail to
+/// parse are ignored.
+pub fn use_stored<T>(
+    kind: StorageKind,
+    key: impl Into<String>,
+    default: T,
+) -> (Signal<T>, Callback<T>)
+where
+    T: FromStr + ToString + Clone + Send + Sync + 'static,
+{
+    let key = key.into();
+    let value = RwSignal::new(default);
+    Effect::new({
+        let key = key.clone();
+        move |_| {
+            if let Some(stored) = with_storage(kind, |storage| storage.get(&key))
+                .and_then(|stored| stored.parse().ok())
+            {
+                value.set(stored);
+            }
+        }
+    });
+    let set = Callback::new(move |new: T| {
+        with_storage(kind, |storage| storage.set(&key, new.to_string()));
+        value.set(new);
+    });
+    (value.into(), set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Backend behaving like storage in a sandboxed iframe.
+    struct Denied;
+
+    impl Backend for Denied {
+        fn get_item(&self, _: &str) -> Result<Option<String>, String> {
+            Err("SecurityError: The operation is insecure.".to_string())
+        }
+
+        fn set_item(&self, _: &str, _: &str) -> Result<(), String> {
+            Err("SecurityError: The operation is insecure.".to_string())
+        }
+    }
+
+    #[test]
+    fn test_falls_back_to_memory() {
+        let storage = FallbackStorage::new(Denied);
+        assert_eq!(storage.get("theme"), None);
+        storage.set("theme", "dark".to_string());
+        assert_eq!(storage.get("theme"), Some("dark".to_string()));
+        assert!(storage.failed.get());
+    }
+}
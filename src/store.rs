@@ -0,0 +1,62 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::time::Duration;
+use worker::{Result, Storage};
+
+/// Persistent state of a durable object. Implemented by the durable object [`Storage`] and, in
+/// tests, by an in-memory store, so durable object logic can be tested without the worker
+/// runtime.
+#[allow(async_fn_in_trait)]
+pub trait Store {
+    /// The value stored under `key`, or `None` if there's none or it can't be read.
+    async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T>;
+    async fn put<T: Serialize>(&mut self, key: &str, value: T) -> Result<()>;
+    /// Schedules the durable object's alarm to fire `after` from now.
+    async fn set_alarm(&mut self, after: Duration) -> Result<()>;
+}
+
+impl Store for Storage {
+    async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        Storage::get(self, key).await.ok()
+    }
+
+    async fn put<T: Serialize>(&mut self, key: &str, value: T) -> Result<()> {
+        Storage::put(self, key, value).await
+    }
+
+    async fn set_alarm(&mut self, after: Duration) -> Result<()> {
+        Storage::set_alarm(self, after).await
+    }
+}
+
+#[cfg(test)]
+pub use memory::MemoryStore;
+
+#[cfg(test)]
+mod memory {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    pub struct MemoryStore {
+        values: HashMap<String, serde_json::Value>,
+        pub alarm: Option<Duration>,
+    }
+
+    impl Store for MemoryStore {
+        async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+            serde_json::from_value(self.values.get(key)?.clone()).ok()
+        }
+
+        async fn put<T: Serialize>(&mut self, key: &str, value: T) -> Result<()> {
+            let value =
+                serde_json::to_value(value).map_err(|e| worker::Error::RustError(e.to_string()))?;
+            self.values.insert(key.to_string(), value);
+            Ok(())
+        }
+
+        async fn set_alarm(&mut self, after: Duration) -> Result<()> {
+            self.alarm = Some(after);
+            Ok(())
+        }
+    }
+}
@@ -1,8 +1,30 @@
 use std::future::Future;
 
+use chrono::{DateTime, Duration, Utc};
 use leptos::prelude::{RwSignal, Update};
 use uuid::Uuid;
 
+/// Formats how long ago something happened, e.g. `2 minutes ago`. Only the largest unit is kept.
+pub fn format_time_ago(elapsed: Duration) -> String {
+    let seconds = elapsed.num_seconds().max(0);
+    let (count, unit) = match seconds {
+        0..10 => return "just now".to_string(),
+        10..60 => (seconds, "second"),
+        60..3600 => (seconds / 60, "minute"),
+        3600..86400 => (seconds / 3600, "hour"),
+        _ => (seconds / 86400, "day"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{count} {unit}{plural} ago")
+}
+
+/// Whole seconds left until `deadline`, rounded up so a countdown shows zero only once it's due.
+pub fn seconds_until(deadline: DateTime<Utc>, now: DateTime<Utc>) -> i64 {
+    ((deadline - now).num_milliseconds() + 999)
+        .div_euclid(1000)
+        .max(0)
+}
+
 pub async fn catch_all(
     errors: RwSignal<Vec<(Uuid, String)>>,
     cb: impl Future<Output = Result<(), anyhow::Error>>,
@@ -12,3 +34,87 @@ pub async fn catch_all(
         Err(e) => errors.update(|errors| errors.push((Uuid::new_v4(), e.to_string()))),
     }
 }
+
+/// Lets tests spawn tasks, e.g. when creating resources. They only run in
+/// [`run_until_stalled`].
+#[cfg(test)]
+pub fn init_test_executor() {
+    test_executor::init();
+}
+
+/// Runs the tasks spawned with [`leptos::task::spawn_local`] on the calling test thread, e.g. a
+/// controller's requests to a mock provider, until none of them can make progress.
+#[cfg(test)]
+pub fn run_until_stalled() {
+    test_executor::init();
+    test_executor::POOL.with(|pool| pool.borrow_mut().run_until_stalled());
+}
+
+#[cfg(test)]
+mod test_executor {
+    use std::cell::RefCell;
+
+    use futures::executor::{LocalPool, LocalSpawner};
+    use futures::task::LocalSpawnExt as _;
+    use leptos::task::{
+        any_spawner::{PinnedFuture, PinnedLocalFuture},
+        CustomExecutor, Executor,
+    };
+
+    thread_local! {
+        pub static POOL: RefCell<LocalPool> = RefCell::new(LocalPool::new());
+        static SPAWNER: LocalSpawner = POOL.with(|pool| pool.borrow().spawner());
+        static PARKED: RefCell<Vec<PinnedFuture<()>>> = const { RefCell::new(Vec::new()) };
+    }
+
+    struct TestExecutor;
+
+    impl CustomExecutor for TestExecutor {
+        /// Thread-safe tasks are kept but never run. Leptos only spawns them to wait a tick before
+        /// loading resources, so resources stay loading rather than calling server functions,
+        /// which only work in a browser.
+        fn spawn(&self, fut: PinnedFuture<()>) {
+            PARKED.with(|parked| parked.borrow_mut().push(fut));
+        }
+
+        fn spawn_local(&self, fut: PinnedLocalFuture<()>) {
+            SPAWNER.with(|spawner| spawner.spawn_local(fut).expect("test pool is running"));
+        }
+
+        fn poll_local(&self) {}
+    }
+
+    /// Installs the executor for the calling thread. The global hooks are only set by the first
+    /// thread, and dispatch to whichever thread spawns.
+    pub fn init() {
+        let _ = Executor::init_local_custom_executor(TestExecutor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seconds_until() {
+        let now = Utc::now();
+        assert_eq!(seconds_until(now, now), 0);
+        assert_eq!(seconds_until(now + Duration::milliseconds(1), now), 1);
+        assert_eq!(seconds_until(now + Duration::seconds(90), now), 90);
+        assert_eq!(seconds_until(now - Duration::seconds(5), now), 0);
+    }
+
+    #[test]
+    fn test_format_time_ago() {
+        assert_eq!(format_time_ago(Duration::seconds(-5)), "just now");
+        assert_eq!(format_time_ago(Duration::seconds(9)), "just now");
+        assert_eq!(format_time_ago(Duration::seconds(10)), "10 seconds ago");
+        assert_eq!(format_time_ago(Duration::seconds(59)), "59 seconds ago");
+        assert_eq!(format_time_ago(Duration::seconds(60)), "1 minute ago");
+        assert_eq!(format_time_ago(Duration::seconds(150)), "2 minutes ago");
+        assert_eq!(format_time_ago(Duration::seconds(3599)), "59 minutes ago");
+        assert_eq!(format_time_ago(Duration::seconds(3600)), "1 hour ago");
+        assert_eq!(format_time_ago(Duration::seconds(86399)), "23 hours ago");
+        assert_eq!(format_time_ago(Duration::days(3)), "3 days ago");
+    }
+}